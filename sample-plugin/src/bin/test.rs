@@ -8,6 +8,7 @@ use vapoursynth::video_info::Framerate;
 use std::env::current_exe;
 use std::fmt::Debug;
 use std::io::{stdout, Write};
+use std::sync::mpsc::channel;
 
 cfg_if! {
     if #[cfg(windows)] {
@@ -71,7 +72,7 @@ fn test_passthrough() {
     let mut env = make_environment();
     env.eval_file("test-vpy/passthrough.vpy", EvalFlags::Nothing)
         .unwrap();
-    let node = env.get_output(0).unwrap();
+    let node = env.get_output(0).unwrap().node;
 
     verify_pixels::<u8>(&node.get_frame(0).unwrap(), [1 << 6, 1 << 6, 0]);
     verify_pixels::<u16>(&node.get_frame(1).unwrap(), [1 << 7, 1 << 7, 0]);
@@ -88,7 +89,7 @@ fn test_invert() {
     let mut env = make_environment();
     env.eval_file("test-vpy/invert.vpy", EvalFlags::Nothing)
         .unwrap();
-    let node = env.get_output(0).unwrap();
+    let node = env.get_output(0).unwrap().node;
 
     verify_pixels::<u8>(
         &node.get_frame(0).unwrap(),
@@ -114,6 +115,7 @@ fn test_invert() {
         &node.get_frame(3).unwrap(),
         [65535 - (1 << 14), 65535 - (1 << 14), 65535],
     );
+    verify_pixels::<f32>(&node.get_frame(4).unwrap(), [0.75, 0.75, 1.0]);
 
     println!(" ok");
 }
@@ -125,7 +127,7 @@ fn test_random_noise() {
     let mut env = make_environment();
     env.eval_file("test-vpy/random_noise.vpy", EvalFlags::Nothing)
         .unwrap();
-    let node = env.get_output(0).unwrap();
+    let node = env.get_output(0).unwrap().node;
 
     assert_eq!(node.info().num_frames, 10.into());
     assert_eq!(
@@ -153,7 +155,7 @@ fn test_make_random_noise() {
     let mut env = make_environment();
     env.eval_file("test-vpy/make_random_noise.vpy", EvalFlags::Nothing)
         .unwrap();
-    let node = env.get_output(0).unwrap();
+    let node = env.get_output(0).unwrap().node;
 
     assert_eq!(node.info().num_frames, 10.into());
     assert_eq!(
@@ -187,10 +189,126 @@ fn test_arguments() {
     println!(" ok");
 }
 
+fn test_serial_counter() {
+    print!("Running test_serial_counter()...");
+    stdout().flush().unwrap();
+
+    let mut env = make_environment();
+    env.eval_file("test-vpy/serial_counter.vpy", EvalFlags::Nothing)
+        .unwrap();
+    let node = env.get_output(0).unwrap().node;
+
+    // Request all frames at once so that the scheduler has a chance to run them concurrently.
+    let (tx, rx) = channel();
+    for n in 0..100 {
+        let tx = tx.clone();
+        node.get_frame_async(n, move |frame, _, _| {
+            let frame = frame.unwrap();
+            let props = frame.props();
+            let index = props.get_int("SerialIndex").unwrap();
+            let interleaved = props.get_int("SerialInterleaved").unwrap();
+            tx.send((index, interleaved)).unwrap();
+        });
+    }
+
+    // Each frame must have been generated exactly once, one after another.
+    let mut results: Vec<_> = (0..100).map(|_| rx.recv().unwrap()).collect();
+    results.sort_unstable();
+    assert_eq!(
+        results.iter().map(|&(index, _)| index).collect::<Vec<_>>(),
+        (0..100).collect::<Vec<_>>()
+    );
+
+    // Only the serial mode keeps the requests from running interleaved, even with the filter
+    // behind a lock.
+    assert!(results.iter().all(|&(_, interleaved)| interleaved == 0));
+
+    println!(" ok");
+}
+
+fn test_threshold() {
+    print!("Running test_threshold()...");
+    stdout().flush().unwrap();
+
+    let mut env = make_environment();
+    env.eval_file("test-vpy/threshold.vpy", EvalFlags::Nothing)
+        .unwrap();
+    let node = env.get_output(0).unwrap().node;
+
+    // Every concurrently running instance reads the same shared config.
+    let (tx, rx) = channel();
+    for n in 0..100 {
+        let tx = tx.clone();
+        node.get_frame_async(n, move |frame, _, _| {
+            verify_pixels::<u8>(&frame.unwrap(), [0, 255, 255]);
+            tx.send(()).unwrap();
+        });
+    }
+
+    for _ in 0..100 {
+        rx.recv().unwrap();
+    }
+
+    println!(" ok");
+}
+
+fn test_delay() {
+    print!("Running test_delay()...");
+    stdout().flush().unwrap();
+
+    let mut env = make_environment();
+    env.eval_file("test-vpy/delay.vpy", EvalFlags::Nothing)
+        .unwrap();
+    let node = env.get_output(0).unwrap().node;
+
+    // The declared dependencies are advisory and don't change how the filter runs.
+    verify_pixels::<u8>(&node.get_frame(0).unwrap(), [0, 0, 0]);
+    verify_pixels::<u8>(&node.get_frame(1).unwrap(), [0, 0, 0]);
+    for n in 2..100 {
+        verify_pixels::<u8>(&node.get_frame(n).unwrap(), [255, 255, 255]);
+    }
+
+    println!(" ok");
+}
+
+fn test_with_mask() {
+    print!("Running test_with_mask()...");
+    stdout().flush().unwrap();
+
+    let mut env = make_environment();
+    env.eval_file("test-vpy/with_mask.vpy", EvalFlags::Nothing)
+        .unwrap();
+    let clip = env.get_output(0).unwrap().node;
+    let mask = env.get_output(1).unwrap().node;
+
+    // Each output node gets the frames for its own output index.
+    verify_pixels::<u8>(&clip.get_frame(0).unwrap(), [255, 0, 0]);
+    verify_pixels::<u8>(&clip.get_frame(1).unwrap(), [0, 0, 255]);
+
+    let expected_row = vec![255u8; 320];
+    let frame = mask.get_frame(0).unwrap();
+    assert_eq!(frame.format().id(), PresetFormat::Gray8.into());
+    for row in 0..frame.height(0) {
+        assert_eq!(&expected_row[..], frame.plane_row::<u8>(0, row));
+    }
+
+    let expected_row = vec![0u8; 320];
+    let frame = mask.get_frame(1).unwrap();
+    for row in 0..frame.height(0) {
+        assert_eq!(&expected_row[..], frame.plane_row::<u8>(0, row));
+    }
+
+    println!(" ok");
+}
+
 fn main() {
     test_passthrough();
     test_invert();
     test_random_noise();
     test_make_random_noise();
     test_arguments();
+    test_serial_counter();
+    test_threshold();
+    test_delay();
+    test_with_mask();
 }
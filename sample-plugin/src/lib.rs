@@ -5,12 +5,13 @@ extern crate vapoursynth;
 
 use std::ffi::CStr;
 use std::ptr;
+use std::sync::Arc;
 
 use anyhow::{anyhow, bail, ensure, Context, Error};
 
 use rand::Rng;
 use vapoursynth::core::CoreRef;
-use vapoursynth::format::FormatID;
+use vapoursynth::format::{Format, FormatID};
 use vapoursynth::function::Function;
 use vapoursynth::map::ValueIter;
 use vapoursynth::node::Flags;
@@ -99,39 +100,8 @@ impl<'core> Filter<'core> for Invert<'core> {
             .get_frame_filter(context, n)
             .ok_or_else(|| anyhow!("Couldn't get the source frame"))?;
 
-        if frame.format().sample_type() == SampleType::Float {
-            bail!("Floating point formats are not supported");
-        }
-
         let mut frame = FrameRefMut::copy_of(core, &frame);
-
-        for plane in 0..frame.format().plane_count() {
-            for row in 0..frame.height(plane) {
-                assert_eq!(frame.format().sample_type(), SampleType::Integer);
-
-                let bits_per_sample = frame.format().bits_per_sample();
-                let bytes_per_sample = frame.format().bytes_per_sample();
-
-                match bytes_per_sample {
-                    1 => {
-                        for pixel in frame.plane_row_mut::<u8>(plane, row) {
-                            *pixel = 255 - *pixel;
-                        }
-                    }
-                    2 => {
-                        for pixel in frame.plane_row_mut::<u16>(plane, row) {
-                            *pixel = ((1u64 << bits_per_sample) - 1) as u16 - *pixel;
-                        }
-                    }
-                    4 => {
-                        for pixel in frame.plane_row_mut::<u32>(plane, row) {
-                            *pixel = ((1u64 << bits_per_sample) - 1) as u32 - *pixel;
-                        }
-                    }
-                    _ => unreachable!(),
-                }
-            }
-        }
+        frame.invert()?;
 
         Ok(frame.into())
     }
@@ -346,6 +316,318 @@ make_filter_function! {
     }
 }
 
+// A serial filter that numbers the frames in the order they are generated.
+struct SerialCounter<'core> {
+    source: Node<'core>,
+    // Mutated without synchronization thanks to the serial mode.
+    generated: i64,
+    // The frame between its initial and final call, and whether another frame started meanwhile.
+    in_progress: Option<usize>,
+    interleaved: bool,
+}
+
+impl<'core> SerialFilter<'core> for SerialCounter<'core> {
+    fn video_info(&self, _api: API, _core: CoreRef<'core>) -> Vec<VideoInfo<'core>> {
+        vec![self.source.info()]
+    }
+
+    fn get_frame_initial(
+        &mut self,
+        _api: API,
+        _core: CoreRef<'core>,
+        context: FrameContext,
+        n: usize,
+    ) -> Result<Option<FrameRef<'core>>, Error> {
+        // The serial mode processes one frame at a time, so no other frame can be in progress.
+        self.interleaved |= self.in_progress.is_some();
+        self.in_progress = Some(n);
+
+        self.source.request_frame_filter(context, n);
+        Ok(None)
+    }
+
+    fn get_frame(
+        &mut self,
+        _api: API,
+        core: CoreRef<'core>,
+        context: FrameContext,
+        n: usize,
+    ) -> Result<FrameRef<'core>, Error> {
+        self.interleaved |= self.in_progress != Some(n);
+        self.in_progress = None;
+
+        let frame = self
+            .source
+            .get_frame_filter(context, n)
+            .ok_or_else(|| anyhow!("Couldn't get the source frame"))?;
+
+        let mut frame = FrameRefMut::copy_of(core, &frame);
+        frame.props_mut().set_int("SerialIndex", self.generated)?;
+        frame
+            .props_mut()
+            .set_int("SerialInterleaved", i64::from(self.interleaved))?;
+        self.generated += 1;
+
+        Ok(frame.into())
+    }
+}
+
+make_filter_function! {
+    SerialCounterFunction, "SerialCounter"
+
+    fn create_serial_counter<'core>(
+        _api: API,
+        _core: CoreRef<'core>,
+        clip: Node<'core>,
+    ) -> Result<Option<Box<dyn Filter<'core> + 'core>>, Error> {
+        Ok(Some(
+            SerialCounter {
+                source: clip,
+                generated: 0,
+                in_progress: None,
+                interleaved: false,
+            }
+            .into_filter(),
+        ))
+    }
+}
+
+// A filter that delays the clip by one frame, repeating the first frame.
+struct Delay<'core> {
+    source: Node<'core>,
+}
+
+impl<'core> Filter<'core> for Delay<'core> {
+    fn video_info(&self, _api: API, _core: CoreRef<'core>) -> Vec<VideoInfo<'core>> {
+        vec![self.source.info()]
+    }
+
+    // Frame `n` is made from a different source frame, so the dependency isn't strictly spatial.
+    fn dependencies(&self) -> Vec<(Node<'core>, RequestPattern)> {
+        vec![(self.source.clone(), RequestPattern::General)]
+    }
+
+    fn get_frame_initial(
+        &self,
+        _api: API,
+        _core: CoreRef<'core>,
+        context: FrameContext,
+        n: usize,
+    ) -> Result<Option<FrameRef<'core>>, Error> {
+        self.source
+            .request_frame_filter(context, n.saturating_sub(1));
+        Ok(None)
+    }
+
+    fn get_frame(
+        &self,
+        _api: API,
+        _core: CoreRef<'core>,
+        context: FrameContext,
+        n: usize,
+    ) -> Result<FrameRef<'core>, Error> {
+        self.source
+            .get_frame_filter(context, n.saturating_sub(1))
+            .ok_or_else(|| anyhow!("Couldn't get the source frame"))
+    }
+}
+
+make_filter_function! {
+    DelayFunction, "Delay"
+
+    fn create_delay<'core>(
+        _api: API,
+        _core: CoreRef<'core>,
+        clip: Node<'core>,
+    ) -> Result<Option<Box<dyn Filter<'core> + 'core>>, Error> {
+        Ok(Some(Box::new(Delay { source: clip })))
+    }
+}
+
+// A filter with two outputs: the clip itself and a mask of its bright pixels.
+//
+// Both output nodes share one filter instance, which tells them apart by the output index.
+struct WithMask<'core> {
+    source: Node<'core>,
+    mask_format: Format<'core>,
+}
+
+impl<'core> Filter<'core> for WithMask<'core> {
+    fn video_info(&self, _api: API, _core: CoreRef<'core>) -> Vec<VideoInfo<'core>> {
+        let clip_info = self.source.info();
+
+        let mut mask_info = clip_info;
+        mask_info.format = Property::Constant(self.mask_format);
+
+        vec![clip_info, mask_info]
+    }
+
+    fn get_frame_initial(
+        &self,
+        _api: API,
+        _core: CoreRef<'core>,
+        context: FrameContext,
+        n: usize,
+    ) -> Result<Option<FrameRef<'core>>, Error> {
+        self.source.request_frame_filter(context, n);
+        Ok(None)
+    }
+
+    fn get_frame(
+        &self,
+        _api: API,
+        core: CoreRef<'core>,
+        context: FrameContext,
+        n: usize,
+    ) -> Result<FrameRef<'core>, Error> {
+        let frame = self
+            .source
+            .get_frame_filter(context, n)
+            .ok_or_else(|| anyhow!("Couldn't get the source frame"))?;
+
+        match context.output_index() {
+            0 => Ok(frame),
+            1 => {
+                let format = frame.format();
+                ensure!(
+                    format.sample_type() == SampleType::Integer && format.bits_per_sample() == 8,
+                    "Only 8-bit integer formats are supported"
+                );
+
+                let mut mask = FrameRefMut::new_zeroed(
+                    core,
+                    Some(&frame),
+                    self.mask_format,
+                    frame.resolution(0),
+                );
+                for row in 0..frame.height(0) {
+                    for (x, &pixel) in mask
+                        .plane_row_mut::<u8>(0, row)
+                        .iter_mut()
+                        .zip(frame.plane_row::<u8>(0, row))
+                    {
+                        *x = if pixel < 128 { 0 } else { 255 };
+                    }
+                }
+
+                Ok(mask.into())
+            }
+            index => bail!("Unexpected output index {}", index),
+        }
+    }
+}
+
+make_filter_function! {
+    WithMaskFunction, "WithMask"
+
+    fn create_with_mask<'core>(
+        _api: API,
+        core: CoreRef<'core>,
+        clip: Node<'core>,
+    ) -> Result<Option<Box<dyn Filter<'core> + 'core>>, Error> {
+        let mask_format = core.get_format(PresetFormat::Gray8.into()).unwrap();
+        Ok(Some(Box::new(WithMask { source: clip, mask_format })))
+    }
+}
+
+// Configuration shared between all instances of the Threshold filter.
+struct ThresholdConfig {
+    lut: [u8; 256],
+}
+
+// A filter that sets pixels to either 0 or 255, using a lookup table from the shared config.
+struct Threshold<'core> {
+    source: Node<'core>,
+    config: Arc<ThresholdConfig>,
+}
+
+impl<'core> Filter<'core> for Threshold<'core> {
+    fn video_info(&self, _api: API, _core: CoreRef<'core>) -> Vec<VideoInfo<'core>> {
+        vec![self.source.info()]
+    }
+
+    fn get_frame_initial(
+        &self,
+        _api: API,
+        _core: CoreRef<'core>,
+        context: FrameContext,
+        n: usize,
+    ) -> Result<Option<FrameRef<'core>>, Error> {
+        self.source.request_frame_filter(context, n);
+        Ok(None)
+    }
+
+    fn get_frame(
+        &self,
+        _api: API,
+        core: CoreRef<'core>,
+        context: FrameContext,
+        n: usize,
+    ) -> Result<FrameRef<'core>, Error> {
+        let frame = self
+            .source
+            .get_frame_filter(context, n)
+            .ok_or_else(|| anyhow!("Couldn't get the source frame"))?;
+
+        let format = frame.format();
+        ensure!(
+            format.sample_type() == SampleType::Integer && format.bits_per_sample() == 8,
+            "Only 8-bit integer formats are supported"
+        );
+
+        let mut frame = FrameRefMut::copy_of(core, &frame);
+        for plane in 0..format.plane_count() {
+            for row in 0..frame.height(plane) {
+                for pixel in frame.plane_row_mut::<u8>(plane, row) {
+                    *pixel = self.config.lut[usize::from(*pixel)];
+                }
+            }
+        }
+
+        Ok(frame.into())
+    }
+}
+
+// A filter function which computes the config once and shares it with every filter it creates.
+struct ThresholdFunction {
+    config: Arc<ThresholdConfig>,
+}
+
+impl ThresholdFunction {
+    fn new() -> Self {
+        let mut lut = [0; 256];
+        for (i, x) in lut.iter_mut().enumerate() {
+            *x = if i < 128 { 0 } else { 255 };
+        }
+
+        Self {
+            config: Arc::new(ThresholdConfig { lut }),
+        }
+    }
+}
+
+impl FilterFunction for ThresholdFunction {
+    fn name(&self) -> &str {
+        "Threshold"
+    }
+
+    fn args(&self) -> &str {
+        "clip:clip;"
+    }
+
+    fn create<'core>(
+        &self,
+        _api: API,
+        _core: CoreRef<'core>,
+        args: &Map<'core>,
+    ) -> Result<Option<Box<dyn Filter<'core> + 'core>>, Error> {
+        Ok(Some(Box::new(Threshold {
+            source: args.get_node("clip")?,
+            config: self.config.clone(),
+        })))
+    }
+}
+
 // A filter for testing different kinds of argument passing.
 struct ArgumentTestFilter<'core> {
     clip: Node<'core>,
@@ -446,5 +728,9 @@ export_vapoursynth_plugin! {
         RandomNoiseFunction::new(),
         MakeRandomNoiseFunction::new(),
         ArgumentTestFilterFunction::new(),
+        SerialCounterFunction::new(),
+        ThresholdFunction::new(),
+        DelayFunction::new(),
+        WithMaskFunction::new(),
     ]
 }
@@ -42,19 +42,8 @@ fn print_node_info(node: &Node) {
         "Format: {}",
         map_or_variable(&info.format, |x| x.name().to_owned())
     );
-    println!(
-        "Resolution: {}",
-        map_or_variable(&info.resolution, |x| format!("{}×{}", x.width, x.height))
-    );
-    println!(
-        "Framerate: {}",
-        map_or_variable(&info.framerate, |x| format!(
-            "{}/{} ({})",
-            x.numerator,
-            x.denominator,
-            x.numerator as f64 / x.denominator as f64
-        ))
-    );
+    println!("Resolution: {}", info.resolution.display_resolution());
+    println!("Framerate: {}", info.framerate.display_fps());
 
     #[cfg(feature = "gte-vapoursynth-api-32")]
     println!("Frame count: {}", info.num_frames);
@@ -83,17 +72,12 @@ fn run() -> Result<(), Error> {
         .context("Couldn't get the VapourSynth core")?;
     println!("{}", core.info());
 
-    #[cfg(feature = "gte-vsscript-api-31")]
-    let (node, alpha_node) = environment
+    let vsscript::Output {
+        node,
+        alpha: alpha_node,
+    } = environment
         .get_output(0)
         .context("Couldn't get the output at index 0")?;
-    #[cfg(not(feature = "gte-vsscript-api-31"))]
-    let (node, alpha_node) = (
-        environment
-            .get_output(0)
-            .context("Couldn't get the output at index 0")?,
-        None::<Node>,
-    );
 
     print_node_info(&node);
 
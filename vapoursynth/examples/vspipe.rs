@@ -141,16 +141,7 @@ mod inner {
             }
         )?;
 
-        writeln!(
-            writer,
-            "FPS: {}",
-            map_or_variable(&info.framerate, |x| format!(
-                "{}/{} ({:.3} fps)",
-                x.numerator,
-                x.denominator,
-                x.numerator as f64 / x.denominator as f64
-            ))
-        )?;
+        writeln!(writer, "FPS: {}", info.framerate.display_fps())?;
 
         match info.format {
             Property::Variable => writeln!(writer, "Format Name: Variable")?,
@@ -757,19 +748,13 @@ mod inner {
             .unwrap_or(Ok(0))
             .context("Couldn't convert the output index to an integer")?;
 
-        #[cfg(feature = "gte-vsscript-api-31")]
-        let (node, alpha_node) = environment.get_output(output_index).context(format!(
+        let vsscript::Output {
+            node,
+            alpha: alpha_node,
+        } = environment.get_output(output_index).context(format!(
             "Couldn't get the output node at index {}",
             output_index
         ))?;
-        #[cfg(not(feature = "gte-vsscript-api-31"))]
-        let (node, alpha_node) = (
-            environment.get_output(output_index).context(format!(
-                "Couldn't get the output node at index {}",
-                output_index
-            ))?,
-            None::<Node>,
-        );
 
         if matches.is_present("info") {
             print_info(&mut output_target, &node, alpha_node.as_ref())
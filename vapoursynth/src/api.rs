@@ -3,8 +3,11 @@
 use std::ffi::{CStr, CString, NulError};
 use std::os::raw::{c_char, c_int, c_void};
 use std::ptr::{self, NonNull};
-use std::sync::atomic::{AtomicPtr, Ordering};
+use std::sync::atomic::{AtomicI32, AtomicPtr, Ordering};
+use std::sync::Once;
 use std::{mem, panic, process};
+#[cfg(feature = "gte-vapoursynth-api-34")]
+use thiserror::Error;
 use vapoursynth_sys as ffi;
 
 use crate::core::CoreRef;
@@ -22,6 +25,47 @@ unsafe impl Sync for API {}
 /// A cached API pointer. Note that this is `*const ffi::VSAPI`, not `*mut`.
 static RAW_API: AtomicPtr<ffi::VSAPI> = AtomicPtr::new(ptr::null_mut());
 
+/// The API version of the loaded library, or 0 if it's unknown.
+///
+/// When the API is retrieved with the requested version, this is a lower bound: the library
+/// supports at least that version.
+static API_VERSION: AtomicI32 = AtomicI32::new(0);
+
+/// Guards the core info query in `API::set()`, which runs on every filter creation.
+static API_VERSION_QUERY: Once = Once::new();
+
+/// The API version which introduced `logMessage()`.
+#[cfg(feature = "gte-vapoursynth-api-34")]
+const LOG_MESSAGE_API_VERSION: i32 = (3 << 16) | 4;
+
+/// An error returned from `API::log()`.
+#[cfg(feature = "gte-vapoursynth-api-34")]
+#[derive(Error, Debug, Clone, Eq, PartialEq)]
+pub enum LogError {
+    #[error("The loaded VapourSynth library doesn't support logging")]
+    Unsupported,
+    #[error("Couldn't convert the message to a C string")]
+    CStringConversion(#[source] NulError),
+}
+
+#[cfg(feature = "gte-vapoursynth-api-34")]
+impl From<NulError> for LogError {
+    #[inline]
+    fn from(x: NulError) -> Self {
+        LogError::CStringConversion(x)
+    }
+}
+
+/// Information about the loaded VapourSynth library.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct LibraryInfo {
+    /// String containing the name of the library, copyright notice, core and API versions.
+    pub version_string: String,
+
+    /// Path to the library, if it could be determined.
+    pub path: Option<String>,
+}
+
 /// VapourSynth log message types.
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub enum MessageType {
@@ -97,6 +141,7 @@ impl API {
             if !handle.is_null() {
                 // If we successfully retrieved the API, cache it.
                 RAW_API.store(handle, Ordering::Relaxed);
+                API_VERSION.fetch_max(ffi::VAPOURSYNTH_API_VERSION, Ordering::Relaxed);
             }
             handle
         } else {
@@ -133,6 +178,7 @@ impl API {
             if !handle.is_null() {
                 // If we successfully retrieved the API, cache it.
                 RAW_API.store(handle, Ordering::Relaxed);
+                API_VERSION.fetch_max(ffi::VAPOURSYNTH_API_VERSION, Ordering::Relaxed);
             }
             handle
         } else {
@@ -159,19 +205,52 @@ impl API {
         }
     }
 
-    /// Stores the API in the cache.
+    /// Returns the cached API, or `None` if it hasn't been cached yet.
+    #[cfg(feature = "serde")]
+    #[inline]
+    pub(crate) fn get_cached_opt() -> Option<Self> {
+        NonNull::new(RAW_API.load(Ordering::Relaxed)).map(|handle| Self { handle })
+    }
+
+    /// Stores the API in the cache, along with the API version of `core`.
+    ///
+    /// The API version is only queried on the first call since it's the same for every core of the
+    /// loaded library.
     ///
     /// # Safety
-    /// The given pointer should be valid.
+    /// The given pointers should be valid.
     #[inline]
-    pub(crate) unsafe fn set(handle: *const ffi::VSAPI) {
+    pub(crate) unsafe fn set(handle: *const ffi::VSAPI, core: *mut ffi::VSCore) {
         RAW_API.store(handle as *mut _, Ordering::Relaxed);
+
+        API_VERSION_QUERY.call_once(|| {
+            let version = CoreRef::from_ptr(core).info().api_version;
+            API_VERSION.store(version, Ordering::Relaxed);
+        });
+    }
+
+    /// Returns `true` if the loaded VapourSynth library supports `log()`.
+    ///
+    /// The crate might be built against a newer API version than the library it runs with, for
+    /// example when a plugin is loaded by an older VapourSynth. This checks the version of the
+    /// library rather than the enabled features.
+    #[cfg(feature = "gte-vapoursynth-api-34")]
+    #[inline]
+    pub fn can_log(self) -> bool {
+        API_VERSION.load(Ordering::Relaxed) >= LOG_MESSAGE_API_VERSION
     }
 
     /// Sends a message through VapourSynth’s logging framework.
+    ///
+    /// Returns `LogError::Unsupported` if the loaded library doesn't support logging, see
+    /// `can_log()`.
     #[cfg(feature = "gte-vapoursynth-api-34")]
     #[inline]
-    pub fn log(self, message_type: MessageType, message: &str) -> Result<(), NulError> {
+    pub fn log(self, message_type: MessageType, message: &str) -> Result<(), LogError> {
+        if !self.can_log() {
+            return Err(LogError::Unsupported);
+        }
+
         let message = CString::new(message)?;
         unsafe {
             (self.handle.as_ref().logMessage)(message_type.ffi_type(), message.as_ptr());
@@ -979,6 +1058,37 @@ impl API {
         (self.handle.as_ref().getFrameFilter)(n, node, frame_ctx)
     }
 
+    /// Retrieves the node and the number of the requested frame which completed last.
+    ///
+    /// # Safety
+    /// The caller must ensure all pointers are valid and this is called from a filter "get frame"
+    /// function.
+    #[inline]
+    pub(crate) unsafe fn query_completed_frame(
+        self,
+        node: &mut *mut ffi::VSNodeRef,
+        n: &mut i32,
+        frame_ctx: *mut ffi::VSFrameContext,
+    ) {
+        (self.handle.as_ref().queryCompletedFrame)(node, n, frame_ctx);
+    }
+
+    /// Tells the core that a frame previously requested with `request_frame_filter()` won't be
+    /// retrieved, so it can be released early.
+    ///
+    /// # Safety
+    /// The caller must ensure all pointers are valid and this is called from a filter "get frame"
+    /// function.
+    #[inline]
+    pub(crate) unsafe fn release_frame_early(
+        self,
+        node: *mut ffi::VSNodeRef,
+        n: i32,
+        frame_ctx: *mut ffi::VSFrameContext,
+    ) {
+        (self.handle.as_ref().releaseFrameEarly)(node, n, frame_ctx);
+    }
+
     /// Duplicates the frame (not just the reference). As the frame buffer is shared in a
     /// copy-on-write fashion, the frame content is not really duplicated until a write operation
     /// occurs. This is transparent for the user.
@@ -994,6 +1104,20 @@ impl API {
         (self.handle.as_ref().copyFrame)(f, core)
     }
 
+    /// Replaces the properties of `dst` with a copy of the properties of `src`.
+    ///
+    /// # Safety
+    /// The caller must ensure all pointers are valid.
+    #[inline]
+    pub(crate) unsafe fn copy_frame_props(
+        self,
+        src: &ffi::VSFrameRef,
+        dst: &mut ffi::VSFrameRef,
+        core: *mut ffi::VSCore,
+    ) {
+        (self.handle.as_ref().copyFrameProps)(src, dst, core)
+    }
+
     /// Creates a new frame, optionally copying the properties attached to another frame. The new
     /// frame contains uninitialised memory.
     ///
@@ -1012,6 +1136,27 @@ impl API {
         (self.handle.as_ref().newVideoFrame)(format, width, height, prop_src, core)
     }
 
+    /// Creates a new video frame, reusing the planes of other frames.
+    ///
+    /// # Safety
+    /// The caller must ensure all pointers are valid.
+    #[allow(clippy::too_many_arguments)]
+    #[inline]
+    pub(crate) unsafe fn new_video_frame2(
+        self,
+        format: &ffi::VSFormat,
+        width: i32,
+        height: i32,
+        plane_src: *mut *const ffi::VSFrameRef,
+        planes: *const i32,
+        prop_src: *const ffi::VSFrameRef,
+        core: *mut ffi::VSCore,
+    ) -> *mut ffi::VSFrameRef {
+        (self.handle.as_ref().newVideoFrame2)(
+            format, width, height, plane_src, planes, prop_src, core,
+        )
+    }
+
     /// Returns a pointer to the plugin with the given identifier, or a null pointer if not found.
     ///
     /// # Safety
@@ -1168,10 +1313,8 @@ impl API {
 
     /// Creates and returns a new core.
     ///
-    /// Note that there's currently no safe way of freeing the returned core, and the lifetime is
-    /// unbounded, because it can live for an arbitrary long time. You may use the (unsafe)
-    /// `vapoursynth_sys::VSAPI::freeCore()` after ensuring that all frame requests have completed
-    /// and all objects belonging to the core have been released.
+    /// The returned core is never freed and the lifetime is unbounded, because it can live for an
+    /// arbitrary long time. Use `core::Core` for a core which is freed when it's dropped.
     #[inline]
     pub fn create_core<'core>(self, threads: i32) -> CoreRef<'core> {
         unsafe {
@@ -1179,6 +1322,93 @@ impl API {
             CoreRef::from_ptr(handle)
         }
     }
+
+    /// Frees the core.
+    ///
+    /// # Safety
+    /// The caller must ensure all pointers are valid, all frame requests have completed and all
+    /// objects belonging to the core have been released.
+    #[inline]
+    pub(crate) unsafe fn free_core(self, core: *mut ffi::VSCore) {
+        (self.handle.as_ref().freeCore)(core)
+    }
+
+    /// Returns information about the loaded VapourSynth library.
+    ///
+    /// This is meant for diagnostics, for example to find out whether the expected VapourSynth
+    /// installation was picked up. A temporary core is created and freed to query the version
+    /// string, which involves loading the autoloaded plugins, so this isn't cheap.
+    ///
+    /// On Linux the library path is looked up in the memory mappings of the process. Elsewhere
+    /// it's taken from the path of the built-in `std` plugin, which VapourSynth reports only in
+    /// some versions and configurations (and only starting from API 3.1). The path is `None` if it
+    /// couldn't be determined.
+    pub fn library_info(self) -> LibraryInfo {
+        let core = self.create_core(1);
+        let version_string = core.info().version_string.to_owned();
+
+        let address = unsafe { self.handle.as_ref().createCore } as usize;
+        let path = mapped_library_path(address).or_else(|| std_plugin_path(core));
+
+        // Nothing else has seen the core, so it's safe to free it.
+        unsafe { self.free_core(core.ptr()) };
+
+        LibraryInfo {
+            version_string,
+            path,
+        }
+    }
+}
+
+/// Returns the path of the file mapped at `address` in the current process.
+#[cfg(target_os = "linux")]
+fn mapped_library_path(address: usize) -> Option<String> {
+    let maps = std::fs::read_to_string("/proc/self/maps").ok()?;
+
+    // Lines look like `7f3c4a000000-7f3c4a100000 r-xp 00000000 08:01 1234    /path/to/lib.so`.
+    maps.lines().find_map(|line| {
+        let mut fields = line.splitn(6, ' ');
+        let (start, end) = fields.next()?.split_once('-')?;
+        let start = usize::from_str_radix(start, 16).ok()?;
+        let end = usize::from_str_radix(end, 16).ok()?;
+        if !(start..end).contains(&address) {
+            return None;
+        }
+
+        let path = fields.nth(4)?.trim();
+        if path.starts_with('/') {
+            Some(path.to_owned())
+        } else {
+            None
+        }
+    })
+}
+
+/// Returns the path of the file mapped at `address` in the current process.
+#[cfg(not(target_os = "linux"))]
+#[inline]
+fn mapped_library_path(_address: usize) -> Option<String> {
+    None
+}
+
+/// Returns the path of the built-in `std` plugin, if VapourSynth reports one.
+#[cfg(feature = "gte-vapoursynth-api-31")]
+fn std_plugin_path(core: CoreRef) -> Option<String> {
+    let plugin = core.get_plugin_by_id("com.vapoursynth.std").ok()??;
+    let path = plugin.path()?.to_string_lossy();
+
+    if path.is_empty() {
+        None
+    } else {
+        Some(path.into_owned())
+    }
+}
+
+/// Returns the path of the built-in `std` plugin, if VapourSynth reports one.
+#[cfg(not(feature = "gte-vapoursynth-api-31"))]
+#[inline]
+fn std_plugin_path(_core: CoreRef) -> Option<String> {
+    None
 }
 
 impl MessageType {
@@ -50,3 +50,59 @@ unsafe impl Component for f32 {
         format.sample_type() == SampleType::Float && format.bytes_per_sample() == 4
     }
 }
+
+/// A pixel component which can be converted to and from `f64` for arithmetic.
+pub(crate) trait Sample: Component + Copy {
+    /// Converts the component into an `f64`.
+    fn to_f64(self) -> f64;
+
+    /// Converts an `f64` back into a component.
+    ///
+    /// Integer components are rounded to the nearest value and saturated at their type bounds.
+    fn from_f64(x: f64) -> Self;
+}
+
+macro_rules! impl_sample_int {
+    ($($t:ty),*) => {
+        $(
+            impl Sample for $t {
+                #[inline]
+                fn to_f64(self) -> f64 {
+                    f64::from(self)
+                }
+
+                #[inline]
+                fn from_f64(x: f64) -> Self {
+                    x.round() as $t
+                }
+            }
+        )*
+    };
+}
+
+impl_sample_int!(u8, u16, u32);
+
+impl Sample for f32 {
+    #[inline]
+    fn to_f64(self) -> f64 {
+        f64::from(self)
+    }
+
+    #[inline]
+    fn from_f64(x: f64) -> Self {
+        x as f32
+    }
+}
+
+#[cfg(feature = "f16-pixel-type")]
+impl Sample for f16 {
+    #[inline]
+    fn to_f64(self) -> f64 {
+        f64::from(self)
+    }
+
+    #[inline]
+    fn from_f64(x: f64) -> Self {
+        f16::from_f64(x)
+    }
+}
@@ -6,10 +6,20 @@ use std::marker::PhantomData;
 use std::ptr::NonNull;
 use vapoursynth_sys as ffi;
 
+#[cfg(feature = "f16-pixel-type")]
+use half::f16;
+
+use anyhow::{anyhow, bail, ensure, Error};
+
 use crate::api::API;
-use crate::format::{ColorFamily, Format, FormatID, SampleType};
+use crate::component::{Component, Sample};
+use crate::format::{ColorFamily, Format, FormatID, SampleRange, SampleType};
+use crate::frame::{Frame, FrameRef, FrameRefMut, PlaneDataError};
+use crate::function::Function;
 use crate::map::OwnedMap;
+use crate::node::Node;
 use crate::plugin::Plugin;
+use crate::video_info::Resolution;
 
 /// Contains information about a VapourSynth core.
 #[derive(Debug, Clone, Copy, Hash)]
@@ -34,6 +44,55 @@ pub struct Info {
     pub used_framebuffer_size: u64,
 }
 
+/// An owned VapourSynth core.
+///
+/// Unlike the cores obtained from `vsscript::Environment::get_core()` or
+/// `API::create_core()`, this core is freed when it's dropped. Everything created through the
+/// `CoreRef` returned from `core_ref()` borrows the core, so it can't outlive it.
+#[derive(Debug)]
+pub struct Core {
+    handle: NonNull<ffi::VSCore>,
+}
+
+unsafe impl Send for Core {}
+unsafe impl Sync for Core {}
+
+impl Drop for Core {
+    #[inline]
+    fn drop(&mut self) {
+        unsafe {
+            API::get_cached().free_core(self.handle.as_ptr());
+        }
+    }
+}
+
+impl Core {
+    /// Creates a new core with `threads` worker threads.
+    ///
+    /// If `threads` is 0, the number of hardware threads is detected and used. The autoloaded
+    /// plugins are loaded into the new core.
+    ///
+    /// # Panics
+    /// Panics if `threads` is greater than `i32::MAX`.
+    #[inline]
+    pub fn new(api: API, threads: usize) -> Self {
+        assert!(threads <= i32::MAX as usize);
+
+        Self {
+            handle: unsafe { NonNull::new_unchecked(api.create_core(threads as i32).ptr()) },
+        }
+    }
+
+    /// Returns a reference to the core.
+    ///
+    /// Asynchronous frame requests made through the reference must complete before the core is
+    /// dropped.
+    #[inline]
+    pub fn core_ref(&self) -> CoreRef<'_> {
+        unsafe { CoreRef::from_ptr(self.handle.as_ptr()) }
+    }
+}
+
 /// A reference to a VapourSynth core.
 #[derive(Debug, Clone, Copy)]
 pub struct CoreRef<'core> {
@@ -90,6 +149,15 @@ impl<'core> CoreRef<'core> {
         }
     }
 
+    /// Returns the number of worker threads of the core.
+    ///
+    /// This re-reads the core info on every call, so it reflects the effect of
+    /// `set_thread_count()`.
+    #[inline]
+    pub fn thread_count(self) -> usize {
+        self.info().num_threads
+    }
+
     /// Retrieves a registered or preset `Format` by its id. The id can be of a previously
     /// registered format, or one of the `PresetFormat`.
     #[inline]
@@ -130,6 +198,192 @@ impl<'core> CoreRef<'core> {
         }
     }
 
+    /// Creates a new frame and fills it with the given plane data.
+    ///
+    /// `planes` must contain one slice per plane of `format`, each holding exactly
+    /// `width * height` pixels of that plane in row-major order, without any padding. The plane
+    /// resolution takes chroma subsampling into account.
+    ///
+    /// Optionally copies the frame properties from the provided `prop_src` frame.
+    ///
+    /// # Panics
+    /// Panics if the given resolution has components that don't fit into an `i32`.
+    pub fn new_frame_with_data<T: Component + Copy>(
+        &self,
+        format: Format<'core>,
+        resolution: Resolution,
+        planes: &[&[T]],
+        prop_src: Option<&Frame<'core>>,
+    ) -> Result<FrameRefMut<'core>, PlaneDataError> {
+        if !T::is_valid(format) {
+            return Err(PlaneDataError::InvalidComponentType);
+        }
+
+        if planes.len() != format.plane_count() {
+            return Err(PlaneDataError::WrongPlaneCount {
+                expected: format.plane_count(),
+                got: planes.len(),
+            });
+        }
+
+        // The data is fully overwritten below before anyone gets to see it.
+        let mut frame =
+            unsafe { FrameRefMut::new_uninitialized(*self, prop_src, format, resolution) };
+
+        for (plane, data) in planes.iter().enumerate() {
+            let width = frame.width(plane);
+            let expected = width * frame.height(plane);
+            if data.len() != expected {
+                return Err(PlaneDataError::WrongPlaneLength {
+                    plane,
+                    expected,
+                    got: data.len(),
+                });
+            }
+
+            for (row, src) in data.chunks_exact(width).enumerate() {
+                frame.plane_row_mut::<T>(plane, row).copy_from_slice(src);
+            }
+        }
+
+        Ok(frame)
+    }
+
+    /// Creates a new frame with every plane filled with a single value.
+    ///
+    /// `color` must contain one value per plane of `format`. The values are clamped to the range
+    /// allowed by the bit depth, so for example 300 becomes 255 in an 8-bit format. Use
+    /// `solid_frame_f()` for floating point formats.
+    ///
+    /// Returns an error if `format` isn't an integer format or `color` has the wrong length.
+    ///
+    /// # Panics
+    /// Panics if the given resolution has components that don't fit into an `i32`.
+    pub fn solid_frame(
+        &self,
+        format: Format<'core>,
+        resolution: Resolution,
+        color: &[i64],
+    ) -> Result<FrameRefMut<'core>, PlaneDataError> {
+        let max = match format.sample_value_range() {
+            SampleRange::Integer { max } => i64::from(max),
+            SampleRange::Float { .. } => return Err(PlaneDataError::InvalidComponentType),
+        };
+
+        let color: Vec<f64> = color.iter().map(|&x| x.clamp(0, max) as f64).collect();
+        self.new_solid_frame(format, resolution, &color)
+    }
+
+    /// Creates a new frame of a floating point format with every plane filled with a single
+    /// value.
+    ///
+    /// `color` must contain one value per plane of `format`. The values are stored as is.
+    ///
+    /// Returns an error if `format` isn't a floating point format or `color` has the wrong length.
+    ///
+    /// # Panics
+    /// Panics if the given resolution has components that don't fit into an `i32`.
+    pub fn solid_frame_f(
+        &self,
+        format: Format<'core>,
+        resolution: Resolution,
+        color: &[f64],
+    ) -> Result<FrameRefMut<'core>, PlaneDataError> {
+        if format.sample_type() != SampleType::Float {
+            return Err(PlaneDataError::InvalidComponentType);
+        }
+
+        self.new_solid_frame(format, resolution, color)
+    }
+
+    /// Creates a new frame filled with `color`, which must be in range for `format`.
+    fn new_solid_frame(
+        &self,
+        format: Format<'core>,
+        resolution: Resolution,
+        color: &[f64],
+    ) -> Result<FrameRefMut<'core>, PlaneDataError> {
+        if color.len() != format.plane_count() {
+            return Err(PlaneDataError::WrongPlaneCount {
+                expected: format.plane_count(),
+                got: color.len(),
+            });
+        }
+
+        // The data is fully overwritten below before anyone gets to see it.
+        let mut frame = unsafe { FrameRefMut::new_uninitialized(*self, None, format, resolution) };
+
+        for (plane, &value) in color.iter().enumerate() {
+            match (format.sample_type(), format.bytes_per_sample()) {
+                (SampleType::Integer, 1) => fill_plane::<u8>(&mut frame, plane, value),
+                (SampleType::Integer, 2) => fill_plane::<u16>(&mut frame, plane, value),
+                (SampleType::Integer, 4) => fill_plane::<u32>(&mut frame, plane, value),
+                #[cfg(feature = "f16-pixel-type")]
+                (SampleType::Float, 2) => fill_plane::<f16>(&mut frame, plane, value),
+                (SampleType::Float, 4) => fill_plane::<f32>(&mut frame, plane, value),
+                _ => return Err(PlaneDataError::InvalidComponentType),
+            }
+        }
+
+        Ok(frame)
+    }
+
+    /// Creates a node which generates every frame by calling `f`.
+    ///
+    /// For every requested frame number `n`, `f` receives `n` and frame `n` of every clip in
+    /// `clips`, in order, and returns the output frame. The output clip has the format,
+    /// resolution and length of the first clip, and the returned frames must match it.
+    ///
+    /// This is implemented on top of `std.ModifyFrame`. An error returned from `f` is reported as
+    /// an error generating the frame.
+    ///
+    /// # Example
+    /// Alternating between frames of two clips:
+    ///
+    /// ```ignore
+    /// let node = core.frame_eval(&[first, second], |n, frames| Ok(frames[n % 2].clone()))?;
+    /// ```
+    pub fn frame_eval<F>(&self, clips: &[Node<'core>], f: F) -> Result<Node<'core>, Error>
+    where
+        F: Fn(usize, &[FrameRef<'core>]) -> Result<FrameRef<'core>, Error> + Send + Sync + 'core,
+    {
+        ensure!(!clips.is_empty(), "At least one clip is required");
+
+        let std = self
+            .get_plugin_by_id("com.vapoursynth.std")?
+            .ok_or_else(|| anyhow!("The std plugin isn't loaded"))?;
+
+        // The core exists, so the API has been retrieved.
+        let api = unsafe { API::get_cached() };
+        let selector = Function::new(api, *self, move |_, _, in_, out| {
+            let frame = in_.get_int("n").map_err(Error::from).and_then(|n| {
+                let frames = in_.get_frame_iter("f")?.collect::<Vec<_>>();
+                f(n as usize, &frames)
+            });
+
+            match frame {
+                Ok(frame) => out.set_frame("val", &frame).unwrap(),
+                Err(err) => out
+                    .set_error(&format!("{}", err).replace('\0', "\\0"))
+                    .unwrap(),
+            }
+        });
+
+        let mut args = OwnedMap::new(api);
+        args.set_node("clip", &clips[0])?;
+        for clip in clips {
+            args.append_node("clips", clip)?;
+        }
+        args.set_function("selector", &selector)?;
+
+        let rv = std.invoke("ModifyFrame", &args)?;
+        if let Some(error) = rv.error() {
+            bail!("{}", error);
+        }
+
+        Ok(rv.get_node("clip")?)
+    }
+
     /// Returns a plugin with the given identifier.
     #[inline]
     pub fn get_plugin_by_id(&self, id: &str) -> Result<Option<Plugin<'core>>, NulError> {
@@ -172,6 +426,17 @@ impl<'core> CoreRef<'core> {
     }
 
     /// Sets the maximum size of the framebuffer cache. Returns the new maximum size.
+    ///
+    /// The limit applies to the built-in frame cache of the core, which holds the frames kept by
+    /// the node caches as well as unused frame buffers available for reuse. Once the cache grows
+    /// past the limit, the core releases unused buffers and shrinks the node caches, so a small
+    /// value makes it reclaim memory more aggressively at the cost of recomputing frames more
+    /// often. This is useful for keeping memory usage in check when requesting many frames.
+    ///
+    /// Non-positive sizes are ignored and the current maximum size is returned.
+    ///
+    /// This function requires VapourSynth API 3.6 because before that it isn't safe to call it
+    /// concurrently with anything else that accesses the core info.
     #[cfg(feature = "gte-vapoursynth-api-36")]
     #[inline]
     pub fn set_max_cache_size(&self, bytes: i64) -> i64 {
@@ -191,6 +456,21 @@ impl<'core> CoreRef<'core> {
     }
 }
 
+impl Info {
+    /// Returns the fraction of the maximum framebuffer cache size currently in use.
+    ///
+    /// The result is usually between 0 and 1, but can exceed 1 since the core reclaims memory
+    /// lazily. Returns 0 if the maximum size is 0.
+    #[inline]
+    pub fn memory_usage_ratio(&self) -> f64 {
+        if self.max_framebuffer_size == 0 {
+            0.
+        } else {
+            self.used_framebuffer_size as f64 / self.max_framebuffer_size as f64
+        }
+    }
+}
+
 impl fmt::Display for Info {
     #[inline]
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -203,8 +483,14 @@ impl fmt::Display for Info {
         )?;
         writeln!(
             f,
-            "Current framebuffer cache size: {}",
-            self.used_framebuffer_size
+            "Current framebuffer cache size: {} ({:.1}%)",
+            self.used_framebuffer_size,
+            self.memory_usage_ratio() * 100.
         )
     }
 }
+
+/// Fills `plane` of `frame` with `value`.
+fn fill_plane<T: Sample>(frame: &mut Frame, plane: usize, value: f64) {
+    frame.fill_plane(plane, T::from_f64(value));
+}
@@ -4,6 +4,7 @@ use std::ffi::CStr;
 use std::fmt::{self, Display};
 use std::marker::PhantomData;
 use std::ops::Deref;
+use std::str::Utf8Error;
 use vapoursynth_sys as ffi;
 
 /// Contains information about a video format.
@@ -78,6 +79,17 @@ pub enum SampleType {
     Float,
 }
 
+/// The range of sample values of a format.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SampleRange {
+    /// Integer samples go from 0 to `max`, inclusive.
+    Integer { max: u32 },
+
+    /// Floating point samples have no hard limits, but usually stay within
+    /// `typical_min..=typical_max`.
+    Float { typical_min: f32, typical_max: f32 },
+}
+
 /// A unique format identifier.
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub struct FormatID(pub(crate) i32);
@@ -119,9 +131,19 @@ impl<'core> Format<'core> {
     }
 
     /// Gets the printable name of this format.
+    ///
+    /// # Panics
+    /// Panics if the name isn't valid UTF-8. VapourSynth generates ASCII names for all formats, so
+    /// this shouldn't happen in practice; `try_name()` never panics.
     #[inline]
     pub fn name(self) -> &'core str {
-        unsafe { CStr::from_ptr(&self.handle.name as _).to_str().unwrap() }
+        self.try_name().unwrap()
+    }
+
+    /// Gets the printable name of this format, or an error if the name isn't valid UTF-8.
+    #[inline]
+    pub fn try_name(self) -> Result<&'core str, Utf8Error> {
+        unsafe { CStr::from_ptr(&self.handle.name as _).to_str() }
     }
 
     /// Gets the number of planes of this format.
@@ -145,6 +167,49 @@ impl<'core> Format<'core> {
         }
     }
 
+    /// Returns `true` if the color family of this format is `ColorFamily::RGB`.
+    #[inline]
+    pub fn is_rgb(self) -> bool {
+        self.color_family() == ColorFamily::RGB
+    }
+
+    /// Returns `true` if the color family of this format is `ColorFamily::YUV`.
+    ///
+    /// `ColorFamily::YCoCg` formats don't count as YUV.
+    #[inline]
+    pub fn is_yuv(self) -> bool {
+        self.color_family() == ColorFamily::YUV
+    }
+
+    /// Returns `true` if the color family of this format is `ColorFamily::Gray`.
+    #[inline]
+    pub fn is_gray(self) -> bool {
+        self.color_family() == ColorFamily::Gray
+    }
+
+    /// Returns `true` if the second and third planes of this format hold chroma, that is, if the
+    /// color family is `ColorFamily::YUV` or `ColorFamily::YCoCg`.
+    #[inline]
+    pub fn has_chroma(self) -> bool {
+        matches!(self.color_family(), ColorFamily::YUV | ColorFamily::YCoCg)
+    }
+
+    /// Returns `true` if this is one of the packed `ColorFamily::Compat` formats.
+    ///
+    /// The samples of packed formats are interleaved in a single plane, so native filters can't
+    /// process them. See `Node::to_planar()`.
+    #[inline]
+    pub fn is_packed(self) -> bool {
+        self.color_family() == ColorFamily::Compat
+    }
+
+    /// Returns `true` if the second and third planes of this format are subsampled in either
+    /// direction.
+    #[inline]
+    pub fn is_subsampled(self) -> bool {
+        self.sub_sampling_w() != 0 || self.sub_sampling_h() != 0
+    }
+
     /// Gets the sample type of this format.
     #[inline]
     pub fn sample_type(self) -> SampleType {
@@ -172,6 +237,27 @@ impl<'core> Format<'core> {
         rv as u8
     }
 
+    /// Gets the range of sample values of this format.
+    ///
+    /// For integer formats the maximum is `(1 << bits_per_sample) - 1`, e.g. 255 for 8-bit and
+    /// 1023 for 10-bit formats.
+    ///
+    /// For floating point formats the typical range is `0.0..=1.0`. Note that this only holds for
+    /// the luma plane of YUV formats: their chroma planes are centered around zero and typically
+    /// go from -0.5 to 0.5.
+    #[inline]
+    pub fn sample_value_range(self) -> SampleRange {
+        match self.sample_type() {
+            SampleType::Integer => SampleRange::Integer {
+                max: ((1u64 << self.bits_per_sample()) - 1) as u32,
+            },
+            SampleType::Float => SampleRange::Float {
+                typical_min: 0.0,
+                typical_max: 1.0,
+            },
+        }
+    }
+
     /// log2 subsampling factor, applied to second and third plane.
     #[inline]
     pub fn sub_sampling_w(self) -> u8 {
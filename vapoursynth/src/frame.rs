@@ -6,13 +6,20 @@ use std::ptr::{self, NonNull};
 use std::{mem, slice};
 use vapoursynth_sys as ffi;
 
+#[cfg(feature = "f16-pixel-type")]
+use half::f16;
+#[cfg(feature = "ndarray")]
+use ndarray::{ArrayView2, ArrayViewMut2, ShapeBuilder};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
 use thiserror::Error;
 
 use crate::api::API;
-use crate::component::Component;
+use crate::component::{Component, Sample};
 use crate::core::CoreRef;
-use crate::format::Format;
-use crate::map::{MapRef, MapRefMut};
+use crate::format::{ColorFamily, Format, FormatID, SampleRange, SampleType};
+use crate::map::{MapRef, MapRefMut, ReservedProps, StandardMetadata};
+use crate::tracking::{self, Resource};
 use crate::video_info::Resolution;
 
 /// An error indicating that the frame data has non-zero padding.
@@ -20,6 +27,54 @@ use crate::video_info::Resolution;
 #[error("Frame data has non-zero padding: {}", _0)]
 pub struct NonZeroPadding(usize);
 
+/// An error indicating that the provided plane data doesn't fit the frame.
+#[derive(Error, Debug, Clone, Copy, Eq, PartialEq)]
+pub enum PlaneDataError {
+    #[error("The component type is invalid for the frame format")]
+    InvalidComponentType,
+    #[error("Expected data for {} planes, got {}", expected, got)]
+    WrongPlaneCount { expected: usize, got: usize },
+    #[error("Plane {} has {} pixels, expected {}", plane, got, expected)]
+    WrongPlaneLength {
+        plane: usize,
+        expected: usize,
+        got: usize,
+    },
+    #[error(
+        "The source of plane {} doesn't match its sample type or resolution",
+        _0
+    )]
+    MismatchedSourcePlane(usize),
+}
+
+/// The error type for `Frame::apply_matrix3x3()`.
+#[derive(Error, Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Matrix3x3Error {
+    #[error("The frame format isn't RGB")]
+    NotRgb,
+    #[error("The sample type isn't supported")]
+    UnsupportedSampleType,
+}
+
+/// An error indicating that the frames have different formats or resolutions.
+#[derive(Error, Debug, Clone, Copy, Eq, PartialEq)]
+#[error("The frames have different formats or resolutions")]
+pub struct MismatchedFrames;
+
+/// The error type for `Frame::blend_into()`.
+#[derive(Error, Debug, Clone, Copy, Eq, PartialEq)]
+pub enum BlendError {
+    #[error(transparent)]
+    MismatchedFrames(#[from] MismatchedFrames),
+    #[error("The sample type isn't supported")]
+    UnsupportedSampleType,
+}
+
+/// An error indicating that the requested region doesn't fit within the plane.
+#[derive(Error, Debug, Clone, Copy, Eq, PartialEq)]
+#[error("The region doesn't fit within the plane")]
+pub struct RegionOutOfBounds;
+
 /// One frame of a clip.
 // This type is intended to be publicly used only in reference form.
 #[derive(Debug)]
@@ -74,6 +129,7 @@ impl<'core> Drop for Frame<'core> {
         unsafe {
             API::get_cached().free_frame(self);
         }
+        tracking::released(Resource::Frame);
     }
 }
 
@@ -141,7 +197,8 @@ impl<'core> FrameRefMut<'core> {
 
     /// Creates a copy of the given frame.
     ///
-    /// The plane data is copy-on-write, so this isn't very expensive by itself.
+    /// The plane data is copy-on-write, so this isn't very expensive by itself. See
+    /// `new_from_planes()` for details.
     ///
     /// Judging by the underlying implementation, it seems that any valid `core` can be used.
     #[inline]
@@ -151,10 +208,130 @@ impl<'core> FrameRefMut<'core> {
         }
     }
 
+    /// Creates a new frame with every plane taken from a plane of another frame.
+    ///
+    /// `planes` must contain one `(frame, plane)` pair per plane of `format`. The planes are
+    /// shared with the source frames rather than copied, so this is about as cheap as
+    /// `copy_of()`. The source planes must match the sample type and the plane resolution of the
+    /// new frame.
+    ///
+    /// Optionally copies the frame properties from the provided `prop_src` frame.
+    ///
+    /// # Copy-on-write
+    /// Plane data is shared between frames until it's written to, at which point the whole plane
+    /// is copied: getting a mutable row or slice of a plane copies that plane, even if only a
+    /// single row ends up being changed. There's no finer granularity, so the cheapest way to
+    /// modify a few rows is to share every plane (with `copy_of()` or this function) and only
+    /// request mutable access to the planes that are actually modified. Planes that are never
+    /// written to don't cost any memory.
+    ///
+    /// # Panics
+    /// Panics if the given resolution has components that don't fit into an `i32`.
+    #[inline]
+    pub fn new_from_planes(
+        core: CoreRef<'core>,
+        format: Format<'core>,
+        resolution: Resolution,
+        planes: &[(&Frame<'core>, usize)],
+        prop_src: Option<&Frame<'core>>,
+    ) -> Result<Self, PlaneDataError> {
+        let planes: Vec<_> = planes.iter().copied().map(Some).collect();
+        Self::new_from_some_planes(core, format, resolution, &planes, prop_src)
+    }
+
+    /// Creates a new frame with some planes taken from planes of other frames.
+    ///
+    /// This is `new_from_planes()` where an entry of `planes` can be `None`, in which case the
+    /// corresponding plane of the new frame is newly allocated and filled with zero bytes rather
+    /// than shared with another frame. This is useful for recombining planes of different clips,
+    /// for example replacing the chroma of a clip while computing its luma from scratch.
+    ///
+    /// # Panics
+    /// Panics if the given resolution has components that don't fit into an `i32`.
+    pub fn new_from_some_planes(
+        core: CoreRef<'core>,
+        format: Format<'core>,
+        resolution: Resolution,
+        planes: &[Option<(&Frame<'core>, usize)>],
+        prop_src: Option<&Frame<'core>>,
+    ) -> Result<Self, PlaneDataError> {
+        assert!(resolution.width <= i32::MAX as usize);
+        assert!(resolution.height <= i32::MAX as usize);
+
+        if planes.len() != format.plane_count() {
+            return Err(PlaneDataError::WrongPlaneCount {
+                expected: format.plane_count(),
+                got: planes.len(),
+            });
+        }
+
+        for (plane, &(frame, src_plane)) in planes
+            .iter()
+            .enumerate()
+            .filter_map(|(plane, src)| src.as_ref().map(|src| (plane, src)))
+        {
+            let (width, height) = if plane == 0 {
+                (resolution.width, resolution.height)
+            } else {
+                (
+                    resolution.width >> format.sub_sampling_w(),
+                    resolution.height >> format.sub_sampling_h(),
+                )
+            };
+
+            // VapourSynth aborts the process on a mismatch, so it has to be checked here.
+            let src_format = frame.format();
+            if src_plane >= src_format.plane_count()
+                || src_format.sample_type() != format.sample_type()
+                || src_format.bits_per_sample() != format.bits_per_sample()
+                || frame.width(src_plane) != width
+                || frame.height(src_plane) != height
+            {
+                return Err(PlaneDataError::MismatchedSourcePlane(plane));
+            }
+        }
+
+        // VapourSynth leaves the planes with null sources uninitialized, they are zeroed below.
+        let mut plane_src: Vec<_> = planes
+            .iter()
+            .map(|src| match *src {
+                Some((frame, _)) => frame.deref() as *const ffi::VSFrameRef,
+                None => ptr::null(),
+            })
+            .collect();
+        let plane_numbers: Vec<_> = planes
+            .iter()
+            .map(|src| src.map_or(0, |(_, plane)| plane as i32))
+            .collect();
+
+        let mut rv = Self {
+            frame: unsafe {
+                Frame::from_ptr(API::get_cached().new_video_frame2(
+                    &format,
+                    resolution.width as i32,
+                    resolution.height as i32,
+                    plane_src.as_mut_ptr(),
+                    plane_numbers.as_ptr(),
+                    prop_src.map(|f| f.deref() as _).unwrap_or(ptr::null()),
+                    core.ptr(),
+                ))
+            },
+        };
+
+        for plane in (0..planes.len()).filter(|&plane| planes[plane].is_none()) {
+            rv.zero_plane(plane);
+        }
+
+        Ok(rv)
+    }
+
     /// Creates a new frame with uninitialized plane data.
     ///
     /// Optionally copies the frame properties from the provided `prop_src` frame.
     ///
+    /// The planes of the new frame have the usual alignment guarantees, see
+    /// `Frame::is_plane_aligned()`.
+    ///
     /// # Safety
     /// The returned frame contains uninitialized plane data. This should be handled carefully. See
     /// the docs for `std::mem::uninitialized()` for more information.
@@ -183,6 +360,45 @@ impl<'core> FrameRefMut<'core> {
             },
         }
     }
+
+    /// Creates a new frame with every pixel of every plane set to zero.
+    ///
+    /// This is a safe alternative to `new_uninitialized()` for when the planes are only partially
+    /// written or when zero is a sensible default. Zeroing costs a pass over the plane data; if
+    /// every pixel is going to be overwritten anyway, `new_uninitialized()` followed by
+    /// `Frame::fill()` or `Frame::fill_plane()` avoids it.
+    ///
+    /// Optionally copies the frame properties from the provided `prop_src` frame.
+    ///
+    /// # Panics
+    /// Panics if the given resolution has components that don't fit into an `i32`.
+    #[inline]
+    pub fn new_zeroed(
+        core: CoreRef<'core>,
+        prop_src: Option<&Frame<'core>>,
+        format: Format<'core>,
+        resolution: Resolution,
+    ) -> Self {
+        let mut rv = unsafe { Self::new_uninitialized(core, prop_src, format, resolution) };
+
+        for plane in 0..format.plane_count() {
+            rv.zero_plane(plane);
+        }
+
+        rv
+    }
+
+    /// Replaces the properties of this frame with a copy of the properties of `src`.
+    ///
+    /// This is the same as passing `src` as `prop_src` when creating the frame, but can be done
+    /// after the fact. The existing properties of this frame are discarded, not merged with the
+    /// copied ones.
+    #[inline]
+    pub fn copy_props_from(&mut self, core: CoreRef<'core>, src: &Frame<'core>) {
+        unsafe {
+            API::get_cached().copy_frame_props(src, &mut self.frame, core.ptr());
+        }
+    }
 }
 
 impl<'core> From<FrameRefMut<'core>> for FrameRef<'core> {
@@ -201,6 +417,7 @@ impl<'core> Frame<'core> {
     /// mutability.
     #[inline]
     pub(crate) unsafe fn from_ptr(handle: *const ffi::VSFrameRef) -> Self {
+        tracking::acquired(Resource::Frame);
         Self {
             handle: NonNull::new_unchecked(handle as *mut ffi::VSFrameRef),
             format: unsafe {
@@ -217,6 +434,22 @@ impl<'core> Frame<'core> {
         self.format
     }
 
+    /// Returns the identifier of the frame format.
+    ///
+    /// This is a shorthand for `format().id()`, handy for keying caches or comparing formats.
+    #[inline]
+    pub fn format_id(&self) -> FormatID {
+        self.format.id()
+    }
+
+    /// Returns the number of planes in the frame.
+    ///
+    /// This is a shortcut for `format().plane_count()`.
+    #[inline]
+    pub fn plane_count(&self) -> usize {
+        self.format.plane_count()
+    }
+
     /// Returns the width of a plane, in pixels.
     ///
     /// The width depends on the plane number because of the possible chroma subsampling.
@@ -261,6 +494,9 @@ impl<'core> Frame<'core> {
 
     /// Returns the distance in bytes between two consecutive lines of a plane.
     ///
+    /// VapourSynth allocates planes such that both the stride and the start of the plane data are
+    /// multiples of 32 bytes. See `is_plane_aligned()`.
+    ///
     /// # Panics
     /// Panics if `plane >= format().plane_count()`.
     #[inline]
@@ -270,6 +506,22 @@ impl<'core> Frame<'core> {
         unsafe { API::get_cached().get_frame_stride(self, plane as i32) as usize }
     }
 
+    /// Returns whether the plane data pointer and the stride are both multiples of `align` bytes,
+    /// meaning that every row of the plane starts at an `align`-byte boundary.
+    ///
+    /// VapourSynth guarantees 32-byte alignment for every plane of every frame, including newly
+    /// created ones, which is enough for AVX2. Frames allocated by the core can have an even larger
+    /// alignment, but that's an implementation detail which shouldn't be relied upon.
+    ///
+    /// # Panics
+    /// Panics if `plane >= format().plane_count()` or if `align` is zero.
+    #[inline]
+    pub fn is_plane_aligned(&self, plane: usize, align: usize) -> bool {
+        assert!(align > 0);
+
+        (self.data_ptr(plane) as usize) % align == 0 && self.stride(plane) % align == 0
+    }
+
     /// Returns a slice of a plane's pixel row.
     ///
     /// # Panics
@@ -316,6 +568,127 @@ impl<'core> Frame<'core> {
         unsafe { slice::from_raw_parts_mut(row_ptr as *mut T, width) }
     }
 
+    /// Returns an iterator over the plane's pixel rows, from top to bottom.
+    ///
+    /// The length of every row is `width()`. Unlike `plane()`, this works for padded planes.
+    ///
+    /// # Panics
+    /// Panics if the requested plane or component type is invalid.
+    pub fn plane_rows<'a, T: Component + 'a>(
+        &'a self,
+        plane: usize,
+    ) -> impl ExactSizeIterator<Item = &'a [T]> + DoubleEndedIterator + 'a {
+        assert!(plane < self.format().plane_count());
+        assert!(T::is_valid(self.format()));
+
+        let stride = self.stride(plane);
+        let width = self.width(plane);
+        let ptr = self.data_ptr(plane);
+
+        // The plane is `height() * stride()` bytes long, so every row fits in it.
+        (0..self.height(plane)).map(move |row| unsafe {
+            slice::from_raw_parts(ptr.add(row * stride) as *const T, width)
+        })
+    }
+
+    /// Returns an iterator over the plane's mutable pixel rows, from top to bottom.
+    ///
+    /// The length of every row is `width()`. The rows don't overlap, so they can be kept around
+    /// and used at the same time. Unlike `plane_mut()`, this works for padded planes.
+    ///
+    /// # Panics
+    /// Panics if the requested plane or component type is invalid.
+    pub fn plane_rows_mut<'a, T: Component + 'a>(
+        &'a mut self,
+        plane: usize,
+    ) -> impl ExactSizeIterator<Item = &'a mut [T]> + DoubleEndedIterator + 'a {
+        assert!(plane < self.format().plane_count());
+        assert!(T::is_valid(self.format()));
+
+        let stride = self.stride(plane);
+        let width = self.width(plane);
+        let ptr = self.data_ptr_mut(plane);
+
+        // The plane is `height() * stride()` bytes long, so every row fits in it, and every row is
+        // yielded once.
+        (0..self.height(plane)).map(move |row| unsafe {
+            slice::from_raw_parts_mut(ptr.add(row * stride) as *mut T, width)
+        })
+    }
+
+    /// Returns a parallel iterator over the plane's mutable pixel rows.
+    ///
+    /// This is the `rayon` counterpart of `plane_rows_mut()`, useful for spreading a CPU-heavy
+    /// row loop over several threads within a single `get_frame()` call. The rows don't overlap,
+    /// so every row can be processed on a different thread. Since the frame functions of a
+    /// `Filter` are already required to be thread-safe, the rest of the filter state can be read
+    /// from the closures as usual.
+    ///
+    /// # Panics
+    /// Panics if the requested plane or component type is invalid.
+    #[cfg(feature = "rayon")]
+    pub fn par_plane_rows_mut<'a, T: Component + Send + 'a>(
+        &'a mut self,
+        plane: usize,
+    ) -> impl IndexedParallelIterator<Item = &'a mut [T]> + 'a {
+        self.plane_rows_mut(plane)
+            .collect::<Vec<_>>()
+            .into_par_iter()
+    }
+
+    /// Sets every pixel of the plane to `value`.
+    ///
+    /// The pixels are written without reading them or creating references to them, so this can be
+    /// used to initialize frames created with `FrameRefMut::new_uninitialized()`.
+    ///
+    /// # Panics
+    /// Panics if the requested plane or component type is invalid.
+    pub fn fill_plane<T: Component + Copy>(&mut self, plane: usize, value: T) {
+        assert!(plane < self.format().plane_count());
+        assert!(T::is_valid(self.format()));
+
+        let stride = self.stride(plane);
+        let width = self.width(plane);
+        let ptr = self.data_ptr_mut(plane);
+
+        for row in 0..self.height(plane) {
+            // The plane is `height() * stride()` bytes long, so the row fits in it.
+            unsafe {
+                let row_ptr = ptr.add(row * stride) as *mut T;
+                for x in 0..width {
+                    ptr::write(row_ptr.add(x), value);
+                }
+            }
+        }
+    }
+
+    /// Sets every pixel of every plane to `value`.
+    ///
+    /// See `fill_plane()`.
+    ///
+    /// # Panics
+    /// Panics if the component type is invalid.
+    #[inline]
+    pub fn fill<T: Component + Copy>(&mut self, value: T) {
+        for plane in 0..self.plane_count() {
+            self.fill_plane(plane, value);
+        }
+    }
+
+    /// Sets every byte of the plane's pixels to zero, without touching the padding.
+    fn zero_plane(&mut self, plane: usize) {
+        let stride = self.stride(plane);
+        let width = self.width(plane) * usize::from(self.format().bytes_per_sample());
+        let ptr = self.data_ptr_mut(plane);
+
+        // The plane is `height() * stride()` bytes long, so every row fits in it.
+        for row in 0..self.height(plane) {
+            unsafe {
+                ptr::write_bytes(ptr.add(row * stride), 0, width);
+            }
+        }
+    }
+
     /// Returns a slice of the plane's pixels.
     ///
     /// The length of the returned slice is `height() * width()`. If the pixel data has non-zero
@@ -341,6 +714,22 @@ impl<'core> Frame<'core> {
         Ok(unsafe { slice::from_raw_parts(ptr as *const T, length) })
     }
 
+    /// Returns a slice of the plane's pixels along with the plane's width and height.
+    ///
+    /// This is `plane()` bundled with `width()` and `height()` for passing to code which needs the
+    /// dimensions of the data. The length of the returned slice is `width * height`.
+    ///
+    /// # Panics
+    /// Panics if the requested plane or component type is invalid.
+    #[inline]
+    pub fn plane_with_dims<T: Component>(
+        &self,
+        plane: usize,
+    ) -> Result<(&[T], usize, usize), NonZeroPadding> {
+        let data = self.plane(plane)?;
+        Ok((data, self.width(plane), self.height(plane)))
+    }
+
     /// Returns a mutable slice of the plane's pixels.
     ///
     /// The length of the returned slice is `height() * width()`. If the pixel data has non-zero
@@ -366,6 +755,138 @@ impl<'core> Frame<'core> {
         Ok(unsafe { slice::from_raw_parts_mut(ptr as *mut T, length) })
     }
 
+    /// Returns a mutable packed view of the plane's pixels which works regardless of padding.
+    ///
+    /// The returned value dereferences to a `width() * height()` slice with the rows stored one
+    /// after another. If the plane has no padding, this slice points directly into the frame, like
+    /// `plane_mut()`. Otherwise the pixels are copied into a packed buffer when this function is
+    /// called, and the buffer is copied back into the frame when the returned value is dropped.
+    /// Until then, the changes aren't visible in the frame, which can't be accessed anyway since
+    /// it's borrowed mutably.
+    ///
+    /// # Panics
+    /// Panics if the requested plane or component type is invalid.
+    pub fn plane_packed_mut<T: Component + Copy>(
+        &mut self,
+        plane: usize,
+    ) -> PackedPlaneMut<'_, 'core, T> {
+        let buffer = if self.plane::<T>(plane).is_ok() {
+            None
+        } else {
+            let mut buffer = Vec::with_capacity(self.width(plane) * self.height(plane));
+            for row in 0..self.height(plane) {
+                buffer.extend_from_slice(self.plane_row::<T>(plane, row));
+            }
+            Some(buffer)
+        };
+
+        PackedPlaneMut {
+            frame: self,
+            plane,
+            buffer,
+        }
+    }
+
+    /// Returns a two-dimensional view of the plane's pixels.
+    ///
+    /// The view has the shape `(height(), width())`, so `view[[row, col]]` is the pixel at the
+    /// given row and column. It points directly into the frame and steps over the row padding
+    /// using the stride, so unlike `plane()` it works regardless of padding.
+    ///
+    /// Returns an error if the component type is invalid for the frame format.
+    ///
+    /// # Panics
+    /// Panics if the requested plane is invalid.
+    #[cfg(feature = "ndarray")]
+    pub fn plane_array<T: Component>(
+        &self,
+        plane: usize,
+    ) -> Result<ArrayView2<'_, T>, PlaneDataError> {
+        assert!(plane < self.format().plane_count());
+        if !T::is_valid(self.format()) {
+            return Err(PlaneDataError::InvalidComponentType);
+        }
+
+        let shape = (self.height(plane), self.width(plane));
+        let stride = self.stride(plane) / mem::size_of::<T>();
+        let ptr = self.data_ptr(plane) as *const T;
+
+        Ok(unsafe { ArrayView2::from_shape_ptr(shape.strides((stride, 1)), ptr) })
+    }
+
+    /// Returns a mutable two-dimensional view of the plane's pixels.
+    ///
+    /// See `plane_array()`.
+    ///
+    /// Returns an error if the component type is invalid for the frame format.
+    ///
+    /// # Panics
+    /// Panics if the requested plane is invalid.
+    #[cfg(feature = "ndarray")]
+    pub fn plane_array_mut<T: Component>(
+        &mut self,
+        plane: usize,
+    ) -> Result<ArrayViewMut2<'_, T>, PlaneDataError> {
+        assert!(plane < self.format().plane_count());
+        if !T::is_valid(self.format()) {
+            return Err(PlaneDataError::InvalidComponentType);
+        }
+
+        let shape = (self.height(plane), self.width(plane));
+        let stride = self.stride(plane) / mem::size_of::<T>();
+        let ptr = self.data_ptr_mut(plane) as *mut T;
+
+        Ok(unsafe { ArrayViewMut2::from_shape_ptr(shape.strides((stride, 1)), ptr) })
+    }
+
+    /// Returns a copy of the plane's pixels as a vector of rows.
+    ///
+    /// `plane_to_vec2d(plane)[row][col]` is the pixel at the given row and column. This is
+    /// convenient for quick analysis and assertions, but unlike `plane_row()` it's not zero-copy:
+    /// every call allocates a vector per row and copies the whole plane.
+    ///
+    /// # Panics
+    /// Panics if the requested plane or component type is invalid.
+    pub fn plane_to_vec2d<T: Component + Clone>(&self, plane: usize) -> Vec<Vec<T>> {
+        (0..self.height(plane))
+            .map(|row| self.plane_row::<T>(plane, row).to_vec())
+            .collect()
+    }
+
+    /// Returns a copy of a rectangular region of the plane's pixels.
+    ///
+    /// The region is `width` pixels wide and `height` pixels tall, with its top left corner at
+    /// column `x` and row `y`. The pixels are returned row by row, without any padding.
+    ///
+    /// Returns an error if the region doesn't fit within the plane.
+    ///
+    /// # Panics
+    /// Panics if the requested plane or component type is invalid.
+    pub fn plane_region<T: Component + Clone>(
+        &self,
+        plane: usize,
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+    ) -> Result<Vec<T>, RegionOutOfBounds> {
+        assert!(plane < self.plane_count());
+
+        let fits = |start: usize, len: usize, size: usize| {
+            start.checked_add(len).is_some_and(|end| end <= size)
+        };
+        if !fits(x, width, self.width(plane)) || !fits(y, height, self.height(plane)) {
+            return Err(RegionOutOfBounds);
+        }
+
+        let mut region = Vec::with_capacity(width * height);
+        for row in y..y + height {
+            region.extend_from_slice(&self.plane_row::<T>(plane, row)[x..x + width]);
+        }
+
+        Ok(region)
+    }
+
     /// Returns a pointer to the plane's pixels.
     ///
     /// The pointer points to an array with a length of `height() * stride()` and is valid for as
@@ -396,7 +917,9 @@ impl<'core> Frame<'core> {
 
     /// Returns a slice of a plane's pixel row.
     ///
-    /// The length of the returned slice is equal to `width() * format().bytes_per_sample()`.
+    /// The length of the returned slice is equal to `width() * format().bytes_per_sample()`. The
+    /// row is returned as raw bytes, so this works for any format, including the compat ones, and
+    /// is suitable for format-agnostic copying and hashing.
     ///
     /// # Panics
     /// Panics if `plane >= format().plane_count()` or if `row >= height()`.
@@ -488,15 +1011,684 @@ impl<'core> Frame<'core> {
         Ok(unsafe { slice::from_raw_parts_mut(ptr, length) })
     }
 
+    /// Returns the pixel data of every plane packed into a single buffer.
+    ///
+    /// The planes are stored one after another and their rows without any padding, as described
+    /// by `node::FrameLayout`. Like `data_row()`, this works for any format.
+    pub fn all_plane_bytes(&self) -> Vec<u8> {
+        let bytes_per_sample = usize::from(self.format().bytes_per_sample());
+        let size = (0..self.plane_count())
+            .map(|plane| self.width(plane) * self.height(plane) * bytes_per_sample)
+            .sum();
+
+        let mut rv = Vec::with_capacity(size);
+        for plane in 0..self.plane_count() {
+            for row in 0..self.height(plane) {
+                rv.extend_from_slice(self.data_row(plane, row));
+            }
+        }
+
+        rv
+    }
+
+    /// Applies a 3×3 color matrix to every pixel of an RGB frame.
+    ///
+    /// The matrix is in row-major order: the new value of plane `i` is
+    /// `matrix[i][0] * r + matrix[i][1] * g + matrix[i][2] * b`. For integer formats the results
+    /// are rounded and clamped to the valid sample range, floating point results are stored as is.
+    ///
+    /// Returns an error if the frame format isn't RGB or if the sample type isn't supported, which
+    /// is the case for half precision floating point samples without the `f16-pixel-type`
+    /// feature. The frame is left unchanged in that case.
+    pub fn apply_matrix3x3(&mut self, matrix: [[f64; 3]; 3]) -> Result<(), Matrix3x3Error> {
+        let format = self.format();
+        if format.color_family() != ColorFamily::RGB || format.plane_count() != 3 {
+            return Err(Matrix3x3Error::NotRgb);
+        }
+
+        let max = match format.sample_value_range() {
+            SampleRange::Integer { max } => Some(f64::from(max)),
+            SampleRange::Float { .. } => None,
+        };
+
+        match (format.sample_type(), format.bytes_per_sample()) {
+            (SampleType::Integer, 1) => self.apply_matrix3x3_typed::<u8>(&matrix, max),
+            (SampleType::Integer, 2) => self.apply_matrix3x3_typed::<u16>(&matrix, max),
+            (SampleType::Integer, 4) => self.apply_matrix3x3_typed::<u32>(&matrix, max),
+            #[cfg(feature = "f16-pixel-type")]
+            (SampleType::Float, 2) => self.apply_matrix3x3_typed::<f16>(&matrix, max),
+            (SampleType::Float, 4) => self.apply_matrix3x3_typed::<f32>(&matrix, max),
+            _ => return Err(Matrix3x3Error::UnsupportedSampleType),
+        }
+
+        Ok(())
+    }
+
+    /// Applies a 3×3 color matrix to the pixels of type `T`, clamping the results to `0..=max`.
+    fn apply_matrix3x3_typed<T: Sample>(&mut self, matrix: &[[f64; 3]; 3], max: Option<f64>) {
+        let width = self.width(0);
+        let mut input = [vec![0f64; width], vec![0f64; width], vec![0f64; width]];
+
+        for row in 0..self.height(0) {
+            for (plane, values) in input.iter_mut().enumerate() {
+                for (value, &x) in values.iter_mut().zip(self.plane_row::<T>(plane, row)) {
+                    *value = x.to_f64();
+                }
+            }
+
+            for (plane, coefs) in matrix.iter().enumerate() {
+                for (i, x) in self.plane_row_mut::<T>(plane, row).iter_mut().enumerate() {
+                    let mut value =
+                        coefs[0] * input[0][i] + coefs[1] * input[1][i] + coefs[2] * input[2][i];
+                    if let Some(max) = max {
+                        value = value.clamp(0., max);
+                    }
+                    *x = T::from_f64(value);
+                }
+            }
+        }
+    }
+
+    /// Inverts every sample of the frame in place.
+    ///
+    /// Integer samples become `max - value`, where `max` is the largest value allowed by the bit
+    /// depth. Floating point samples are assumed to be in the typical `0.0..=1.0` range and become
+    /// `1.0 - value`, except for the chroma planes of YUV formats, which are centered around zero
+    /// and become `-value`. Floating point samples outside of the typical range aren't clamped.
+    ///
+    /// Returns an error if the sample type isn't supported, which is the case for half precision
+    /// floating point samples without the `f16-pixel-type` feature. The frame is left unchanged
+    /// in that case.
+    pub fn invert(&mut self) -> Result<(), PlaneDataError> {
+        let format = self.format();
+
+        for plane in 0..format.plane_count() {
+            // The sum of a sample and its inverse.
+            let sum = match format.sample_value_range() {
+                SampleRange::Integer { max } => f64::from(max),
+                SampleRange::Float { .. }
+                    if format.color_family() == ColorFamily::YUV && plane > 0 =>
+                {
+                    0.
+                }
+                SampleRange::Float {
+                    typical_min,
+                    typical_max,
+                } => f64::from(typical_min) + f64::from(typical_max),
+            };
+
+            match (format.sample_type(), format.bytes_per_sample()) {
+                (SampleType::Integer, 1) => self.invert_typed::<u8>(plane, sum),
+                (SampleType::Integer, 2) => self.invert_typed::<u16>(plane, sum),
+                (SampleType::Integer, 4) => self.invert_typed::<u32>(plane, sum),
+                #[cfg(feature = "f16-pixel-type")]
+                (SampleType::Float, 2) => self.invert_typed::<f16>(plane, sum),
+                (SampleType::Float, 4) => self.invert_typed::<f32>(plane, sum),
+                _ => return Err(PlaneDataError::InvalidComponentType),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Replaces the samples of type `T` in `plane` with `sum - value`.
+    fn invert_typed<T: Sample>(&mut self, plane: usize, sum: f64) {
+        for row in 0..self.height(plane) {
+            for x in self.plane_row_mut::<T>(plane, row) {
+                *x = T::from_f64(sum - x.to_f64());
+            }
+        }
+    }
+
+    /// Checks that `other` has the same format and resolution as this frame.
+    fn check_matches(&self, other: &Frame<'core>) -> Result<(), MismatchedFrames> {
+        let format = self.format();
+        if other.format() != format
+            || (0..format.plane_count()).any(|p| other.resolution(p) != self.resolution(p))
+        {
+            return Err(MismatchedFrames);
+        }
+
+        Ok(())
+    }
+
+    /// Copies this frame into `dst`, flipped upside down.
+    ///
+    /// The frame properties of `dst` are left untouched.
+    ///
+    /// Returns an error if `dst` doesn't have the same format and resolution as this frame.
+    pub fn flip_vertical_into(&self, dst: &mut Frame<'core>) -> Result<(), MismatchedFrames> {
+        self.check_matches(dst)?;
+
+        for plane in 0..self.plane_count() {
+            let height = self.height(plane);
+            for row in 0..height {
+                dst.data_row_mut(plane, row)
+                    .copy_from_slice(self.data_row(plane, height - 1 - row));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Copies this frame into `dst`, mirrored left to right.
+    ///
+    /// The frame properties of `dst` are left untouched.
+    ///
+    /// Returns an error if `dst` doesn't have the same format and resolution as this frame.
+    pub fn flip_horizontal_into(&self, dst: &mut Frame<'core>) -> Result<(), MismatchedFrames> {
+        self.check_matches(dst)?;
+
+        let bytes_per_sample = usize::from(self.format().bytes_per_sample());
+        for plane in 0..self.plane_count() {
+            for row in 0..self.height(plane) {
+                let src = self
+                    .data_row(plane, row)
+                    .chunks_exact(bytes_per_sample)
+                    .rev();
+                let dst = dst
+                    .data_row_mut(plane, row)
+                    .chunks_exact_mut(bytes_per_sample);
+                for (dst, src) in dst.zip(src) {
+                    dst.copy_from_slice(src);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Blends this frame with `other` and stores the result in `dst`.
+    ///
+    /// Every pixel of `dst` is set to `alpha * self + (1 - alpha) * other`. For integer formats
+    /// the results are rounded and clamped to the valid sample range, floating point results are
+    /// stored as is. The frame properties of `dst` are left untouched.
+    ///
+    /// Returns an error if the three frames don't all have the same format and resolution or if
+    /// the sample type isn't supported, which is the case for half precision floating point
+    /// samples without the `f16-pixel-type` feature. `dst` is left unchanged in that case.
+    pub fn blend_into(
+        &self,
+        other: &Frame<'core>,
+        alpha: f64,
+        dst: &mut Frame<'core>,
+    ) -> Result<(), BlendError> {
+        self.check_matches(other)?;
+        self.check_matches(dst)?;
+
+        let format = self.format();
+        let max = match format.sample_value_range() {
+            SampleRange::Integer { max } => Some(f64::from(max)),
+            SampleRange::Float { .. } => None,
+        };
+
+        match (format.sample_type(), format.bytes_per_sample()) {
+            (SampleType::Integer, 1) => self.blend_into_typed::<u8>(other, alpha, dst, max),
+            (SampleType::Integer, 2) => self.blend_into_typed::<u16>(other, alpha, dst, max),
+            (SampleType::Integer, 4) => self.blend_into_typed::<u32>(other, alpha, dst, max),
+            #[cfg(feature = "f16-pixel-type")]
+            (SampleType::Float, 2) => self.blend_into_typed::<f16>(other, alpha, dst, max),
+            (SampleType::Float, 4) => self.blend_into_typed::<f32>(other, alpha, dst, max),
+            _ => return Err(BlendError::UnsupportedSampleType),
+        }
+
+        Ok(())
+    }
+
+    /// Blends the pixels of type `T`, clamping the results to `0..=max`.
+    fn blend_into_typed<T: Sample>(
+        &self,
+        other: &Frame<'core>,
+        alpha: f64,
+        dst: &mut Frame<'core>,
+        max: Option<f64>,
+    ) {
+        for plane in 0..self.plane_count() {
+            for row in 0..self.height(plane) {
+                let a = self.plane_row::<T>(plane, row);
+                let b = other.plane_row::<T>(plane, row);
+                let out = dst.plane_row_mut::<T>(plane, row);
+
+                for ((x, &a), &b) in out.iter_mut().zip(a).zip(b) {
+                    let mut value = alpha * a.to_f64() + (1. - alpha) * b.to_f64();
+                    if let Some(max) = max {
+                        value = value.clamp(0., max);
+                    }
+                    *x = T::from_f64(value);
+                }
+            }
+        }
+    }
+
+    /// Creates a copy of this frame with its own, freshly allocated plane data.
+    ///
+    /// Unlike `FrameRefMut::copy_of()`, which shares the planes until they are written to, this
+    /// allocates and copies every plane right away, so the cost is that of allocating and filling
+    /// a whole new frame. This is useful when the copy must not share memory with anything else,
+    /// for example before handing its planes over to external code. The frame properties are
+    /// copied as well.
+    pub fn deep_copy(&self, core: CoreRef<'core>) -> FrameRefMut<'core> {
+        // The data is fully overwritten below before anyone gets to see it.
+        let mut copy = unsafe {
+            FrameRefMut::new_uninitialized(core, Some(self), self.format(), self.resolution(0))
+        };
+
+        for plane in 0..self.plane_count() {
+            for row in 0..self.height(plane) {
+                copy.data_row_mut(plane, row)
+                    .copy_from_slice(self.data_row(plane, row));
+            }
+        }
+
+        copy
+    }
+
+    /// Returns an iterator over the planes of the frame.
+    #[inline]
+    pub fn planes(&self) -> Planes<'_, 'core> {
+        Planes {
+            frame: self,
+            index: 0,
+        }
+    }
+
+    /// Returns an iterator over the planes of the frame allowing modification of their pixels.
+    ///
+    /// Every plane is yielded exactly once, and the planes don't overlap in memory, so the yielded
+    /// `PlaneRefMut`s can be kept around and used at the same time, for example to process the
+    /// chroma planes together. They borrow the frame mutably, so the frame itself can't be
+    /// accessed until all of them are dropped.
+    #[inline]
+    pub fn planes_mut(&mut self) -> PlanesMut<'_, 'core> {
+        PlanesMut {
+            frame: self,
+            index: 0,
+        }
+    }
+
     /// Returns a map of frame's properties.
     #[inline]
-    pub fn props(&self) -> MapRef {
+    pub fn props(&self) -> MapRef<'_, 'core> {
         unsafe { MapRef::from_ptr(API::get_cached().get_frame_props_ro(self)) }
     }
 
     /// Returns a mutable map of frame's properties.
     #[inline]
-    pub fn props_mut(&mut self) -> MapRefMut {
+    pub fn props_mut(&mut self) -> MapRefMut<'_, 'core> {
         unsafe { MapRefMut::from_ptr(API::get_cached().get_frame_props_rw(self)) }
     }
+
+    /// Returns the commonly used reserved properties of this frame.
+    ///
+    /// This is a shorthand for `props().standard_metadata()`, see `ReservedProps`.
+    #[inline]
+    pub fn standard_metadata(&self) -> StandardMetadata {
+        self.props().standard_metadata()
+    }
+
+    /// Returns the alpha frame attached to this frame.
+    ///
+    /// Filters which produce alpha, such as image sources, attach it to every frame in the
+    /// `_Alpha` property rather than returning a separate clip. Returns `None` if there's no such
+    /// property or if it doesn't hold a frame.
+    #[inline]
+    pub fn alpha_frame(&self) -> Option<FrameRef<'core>> {
+        self.props().get_frame("_Alpha").ok()
+    }
+
+    /// Returns a hash of the format, the resolution and the pixel data of this frame.
+    ///
+    /// The row padding is skipped, so frames with the same pixels hash the same regardless of
+    /// their stride. The frame properties are ignored. The hash is computed with 64-bit FNV-1a
+    /// and doesn't depend on the platform or the Rust version, so it can be stored, for example
+    /// as the expected output in regression tests.
+    pub fn content_hash(&self) -> u64 {
+        let mut hasher = Fnv1a::new();
+
+        let format = self.format();
+        hasher.write(&format.id().0.to_le_bytes());
+
+        for plane in 0..format.plane_count() {
+            hasher.write(&(self.width(plane) as u64).to_le_bytes());
+            hasher.write(&(self.height(plane) as u64).to_le_bytes());
+
+            for row in 0..self.height(plane) {
+                hasher.write(self.data_row(plane, row));
+            }
+        }
+
+        hasher.finish()
+    }
+}
+
+/// Returns an iterator over pairs of corresponding pixel rows of `plane` of two frames.
+///
+/// This is useful for filters comparing or combining two frames line by line.
+///
+/// Returns an error if the frames have different formats or resolutions.
+///
+/// # Panics
+/// Panics if the requested plane or component type is invalid.
+pub fn zip_rows<'a, 'core, T: Component + 'a>(
+    a: &'a Frame<'core>,
+    b: &'a Frame<'core>,
+    plane: usize,
+) -> Result<impl ExactSizeIterator<Item = (&'a [T], &'a [T])> + 'a, MismatchedFrames> {
+    a.check_matches(b)?;
+    Ok(a.plane_rows(plane).zip(b.plane_rows(plane)))
+}
+
+/// A plane of a frame, yielded by `Frame::planes()`.
+#[derive(Debug, Clone, Copy)]
+pub struct PlaneRef<'a, 'core: 'a> {
+    frame: &'a Frame<'core>,
+    index: usize,
+}
+
+impl<'a, 'core> PlaneRef<'a, 'core> {
+    /// Returns the index of the plane in the frame.
+    #[inline]
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the width of the plane, in pixels.
+    #[inline]
+    pub fn width(&self) -> usize {
+        self.frame.width(self.index)
+    }
+
+    /// Returns the height of the plane, in pixels.
+    #[inline]
+    pub fn height(&self) -> usize {
+        self.frame.height(self.index)
+    }
+
+    /// Returns the distance in bytes between two consecutive lines of the plane.
+    #[inline]
+    pub fn stride(&self) -> usize {
+        self.frame.stride(self.index)
+    }
+
+    /// Returns a slice of the plane's pixel row.
+    ///
+    /// See `Frame::data_row()`.
+    ///
+    /// # Panics
+    /// Panics if `row >= height()`.
+    #[inline]
+    pub fn data_row(&self, row: usize) -> &'a [u8] {
+        self.frame.data_row(self.index, row)
+    }
+
+    /// Returns a slice of the plane's pixels.
+    ///
+    /// See `Frame::data()`.
+    #[inline]
+    pub fn data(&self) -> Result<&'a [u8], NonZeroPadding> {
+        self.frame.data(self.index)
+    }
+}
+
+/// An iterator over the planes of a frame, returned from `Frame::planes()`.
+#[derive(Debug, Clone)]
+pub struct Planes<'a, 'core: 'a> {
+    frame: &'a Frame<'core>,
+    index: usize,
+}
+
+impl<'a, 'core> Iterator for Planes<'a, 'core> {
+    type Item = PlaneRef<'a, 'core>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index == self.frame.plane_count() {
+            return None;
+        }
+
+        let plane = PlaneRef {
+            frame: self.frame,
+            index: self.index,
+        };
+        self.index += 1;
+        Some(plane)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.frame.plane_count() - self.index;
+        (len, Some(len))
+    }
+}
+
+impl<'a, 'core> ExactSizeIterator for Planes<'a, 'core> {}
+
+/// A mutable plane of a frame, yielded by `Frame::planes_mut()`.
+///
+/// The plane borrows the frame mutably, but only gives access to its own pixels, so several
+/// planes of the same frame can be used at the same time.
+#[derive(Debug)]
+pub struct PlaneRefMut<'a> {
+    ptr: *mut u8,
+    index: usize,
+    width: usize,
+    height: usize,
+    stride: usize,
+    bytes_per_sample: usize,
+    _frame: PhantomData<&'a mut [u8]>,
+}
+
+unsafe impl<'a> Send for PlaneRefMut<'a> {}
+unsafe impl<'a> Sync for PlaneRefMut<'a> {}
+
+impl<'a> PlaneRefMut<'a> {
+    /// Returns the index of the plane in the frame.
+    #[inline]
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the width of the plane, in pixels.
+    #[inline]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the height of the plane, in pixels.
+    #[inline]
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the distance in bytes between two consecutive lines of the plane.
+    #[inline]
+    pub fn stride(&self) -> usize {
+        self.stride
+    }
+
+    /// Returns a slice of the plane's pixel row.
+    ///
+    /// See `Frame::data_row()`.
+    ///
+    /// # Panics
+    /// Panics if `row >= height()`.
+    #[inline]
+    pub fn data_row(&self, row: usize) -> &[u8] {
+        assert!(row < self.height);
+
+        // The plane is `height * stride` bytes long and the row fits in it.
+        unsafe {
+            slice::from_raw_parts(
+                self.ptr.add(row * self.stride),
+                self.width * self.bytes_per_sample,
+            )
+        }
+    }
+
+    /// Returns a mutable slice of the plane's pixel row.
+    ///
+    /// See `Frame::data_row_mut()`.
+    ///
+    /// # Panics
+    /// Panics if `row >= height()`.
+    #[inline]
+    pub fn data_row_mut(&mut self, row: usize) -> &mut [u8] {
+        assert!(row < self.height);
+
+        // The plane is `height * stride` bytes long and the row fits in it.
+        unsafe {
+            slice::from_raw_parts_mut(
+                self.ptr.add(row * self.stride),
+                self.width * self.bytes_per_sample,
+            )
+        }
+    }
+
+    /// Returns a slice of the plane's pixels.
+    ///
+    /// See `Frame::data()`.
+    #[inline]
+    pub fn data(&self) -> Result<&[u8], NonZeroPadding> {
+        let length = self.unpadded_length()?;
+        Ok(unsafe { slice::from_raw_parts(self.ptr, length) })
+    }
+
+    /// Returns a mutable slice of the plane's pixels.
+    ///
+    /// See `Frame::data_mut()`.
+    #[inline]
+    pub fn data_mut(&mut self) -> Result<&mut [u8], NonZeroPadding> {
+        let length = self.unpadded_length()?;
+        Ok(unsafe { slice::from_raw_parts_mut(self.ptr, length) })
+    }
+
+    /// Returns the length of the plane data, or an error if the rows are padded.
+    #[inline]
+    fn unpadded_length(&self) -> Result<usize, NonZeroPadding> {
+        let width = self.width * self.bytes_per_sample;
+        if self.stride != width {
+            return Err(NonZeroPadding(self.stride - width));
+        }
+
+        Ok(self.height * self.stride)
+    }
+}
+
+/// An iterator over the mutable planes of a frame, returned from `Frame::planes_mut()`.
+#[derive(Debug)]
+pub struct PlanesMut<'a, 'core: 'a> {
+    frame: &'a mut Frame<'core>,
+    index: usize,
+}
+
+impl<'a, 'core> Iterator for PlanesMut<'a, 'core> {
+    type Item = PlaneRefMut<'a>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let index = self.index;
+        if index == self.frame.plane_count() {
+            return None;
+        }
+        self.index += 1;
+
+        // Every plane is yielded once, so the returned pointer isn't aliased.
+        Some(PlaneRefMut {
+            ptr: self.frame.data_ptr_mut(index),
+            index,
+            width: self.frame.width(index),
+            height: self.frame.height(index),
+            stride: self.frame.stride(index),
+            bytes_per_sample: usize::from(self.frame.format().bytes_per_sample()),
+            _frame: PhantomData,
+        })
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.frame.plane_count() - self.index;
+        (len, Some(len))
+    }
+}
+
+impl<'a, 'core> ExactSizeIterator for PlanesMut<'a, 'core> {}
+
+/// The 64-bit FNV-1a hash function, used for `Frame::content_hash()`.
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    #[inline]
+    fn new() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= u64::from(byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    #[inline]
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+/// A mutable packed view of a plane, returned from `Frame::plane_packed_mut()`.
+///
+/// If the plane is padded, the changes are written back into the frame on drop.
+#[derive(Debug)]
+pub struct PackedPlaneMut<'a, 'core: 'a, T: Component + Copy> {
+    frame: &'a mut Frame<'core>,
+    plane: usize,
+    // The packed copy of a padded plane, or `None` if the plane is accessed directly.
+    buffer: Option<Vec<T>>,
+}
+
+impl<'a, 'core, T: Component + Copy> PackedPlaneMut<'a, 'core, T> {
+    /// Returns `true` if the plane is padded, so the pixels are accessed through a copy which is
+    /// written back on drop.
+    #[inline]
+    pub fn is_copy(&self) -> bool {
+        self.buffer.is_some()
+    }
+}
+
+impl<'a, 'core, T: Component + Copy> Deref for PackedPlaneMut<'a, 'core, T> {
+    type Target = [T];
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        match self.buffer {
+            Some(ref buffer) => buffer,
+            // The plane was checked to have no padding.
+            None => self.frame.plane(self.plane).unwrap(),
+        }
+    }
+}
+
+impl<'a, 'core, T: Component + Copy> DerefMut for PackedPlaneMut<'a, 'core, T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        match self.buffer {
+            Some(ref mut buffer) => buffer,
+            // The plane was checked to have no padding.
+            None => self.frame.plane_mut(self.plane).unwrap(),
+        }
+    }
+}
+
+impl<'a, 'core, T: Component + Copy> Drop for PackedPlaneMut<'a, 'core, T> {
+    fn drop(&mut self) {
+        if let Some(buffer) = self.buffer.take() {
+            let width = self.frame.width(self.plane);
+            for (row, data) in buffer.chunks_exact(width).enumerate() {
+                self.frame
+                    .plane_row_mut::<T>(self.plane, row)
+                    .copy_from_slice(data);
+            }
+        }
+    }
 }
@@ -25,13 +25,12 @@
 //! # extern crate vapoursynth;
 //! # use anyhow::Error;
 //! # #[cfg(all(feature = "vsscript-functions",
-//! #           feature = "gte-vsscript-api-31",
 //! #           any(feature = "vapoursynth-functions", feature = "gte-vsscript-api-32")))]
 //! # fn foo() -> Result<(), Error> {
 //! use vapoursynth::prelude::*;
 //!
 //! let env = Environment::from_file("test.vpy", EvalFlags::SetWorkingDir)?;
-//! let node = env.get_output(0)?.0; // Without `.0` for VSScript API 3.0
+//! let node = env.get_output(0)?.node;
 //! let frame = node.get_frame(0)?;
 //!
 //! println!("Resolution: {}×{}", frame.width(0), frame.height(0));
@@ -188,15 +187,20 @@ pub mod plugin;
 pub mod plugins;
 pub mod video_info;
 
+#[cfg(feature = "track-allocations")]
+pub mod tracking;
+#[cfg(not(feature = "track-allocations"))]
+mod tracking;
+
 pub mod prelude {
     //! The VapourSynth prelude.
     //!
     //! Contains the types you most likely want to import anyway.
     pub use super::api::{MessageType, API};
     pub use super::component::Component;
-    pub use super::format::{ColorFamily, PresetFormat, SampleType};
+    pub use super::format::{ColorFamily, PresetFormat, SampleRange, SampleType};
     pub use super::frame::{Frame, FrameRef, FrameRefMut};
-    pub use super::map::{Map, OwnedMap, ValueType};
+    pub use super::map::{Map, OwnedMap, ReservedProps, ValueType};
     pub use super::node::{GetFrameError, Node};
     pub use super::plugin::Plugin;
     pub use super::video_info::Property;
@@ -44,6 +44,123 @@ impl<'map, 'elem> Iterator for Keys<'map, 'elem> {
 
 impl<'map, 'elem> ExactSizeIterator for Keys<'map, 'elem> {}
 
+/// An iterator over the keys of a map along with their value types and value counts.
+#[derive(Debug, Clone, Copy)]
+pub struct Iter<'map, 'elem: 'map> {
+    keys: Keys<'map, 'elem>,
+}
+
+impl<'map, 'elem> Iter<'map, 'elem> {
+    #[inline]
+    pub(crate) fn new(map: &'map Map<'elem>) -> Self {
+        Self {
+            keys: Keys::new(map),
+        }
+    }
+}
+
+impl<'map, 'elem> Iterator for Iter<'map, 'elem> {
+    type Item = (&'map str, ValueType, usize);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let map = self.keys.map;
+        let index = self.keys.index;
+        let key = self.keys.next()?;
+
+        // The key comes from the map, so it's valid and present.
+        let key_raw = map.key_raw(index);
+        let (value_type, count) = unsafe {
+            (
+                map.value_type_raw_unchecked(key_raw).unwrap(),
+                map.value_count_raw_unchecked(key_raw).unwrap(),
+            )
+        };
+
+        Some((key, value_type, count))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.keys.size_hint()
+    }
+}
+
+impl<'map, 'elem> ExactSizeIterator for Iter<'map, 'elem> {}
+
+/// The first value associated with a key of a map.
+#[derive(Debug, Clone)]
+pub enum Entry<'map, 'elem: 'map> {
+    Int(i64),
+    Float(f64),
+    Data(&'map [u8]),
+    Node(Node<'elem>),
+    Frame(FrameRef<'elem>),
+    Function(Function<'elem>),
+
+    /// The key holds no values of the given type.
+    Empty(ValueType),
+}
+
+/// An iterator over the keys of a map along with their first values.
+#[derive(Debug, Clone, Copy)]
+pub struct Entries<'map, 'elem: 'map> {
+    keys: Keys<'map, 'elem>,
+}
+
+impl<'map, 'elem> Entries<'map, 'elem> {
+    #[inline]
+    pub(crate) fn new(map: &'map Map<'elem>) -> Self {
+        Self {
+            keys: Keys::new(map),
+        }
+    }
+}
+
+impl<'map, 'elem> Iterator for Entries<'map, 'elem> {
+    type Item = (&'map str, Entry<'map, 'elem>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let map = self.keys.map;
+        let index = self.keys.index;
+        let key = self.keys.next()?;
+
+        // The key comes from the map, so it's valid and present, and the value at index 0 exists
+        // whenever the count is positive.
+        let key_raw = map.key_raw(index);
+        let entry = unsafe {
+            let value_type = map.value_type_raw_unchecked(key_raw).unwrap();
+            if map.value_count_raw_unchecked(key_raw).unwrap() == 0 {
+                Entry::Empty(value_type)
+            } else {
+                match value_type {
+                    ValueType::Int => Entry::Int(map.get_int_raw_unchecked(key_raw, 0).unwrap()),
+                    ValueType::Float => {
+                        Entry::Float(map.get_float_raw_unchecked(key_raw, 0).unwrap())
+                    }
+                    ValueType::Data => Entry::Data(map.get_data_raw_unchecked(key_raw, 0).unwrap()),
+                    ValueType::Node => Entry::Node(map.get_node_raw_unchecked(key_raw, 0).unwrap()),
+                    ValueType::Frame => {
+                        Entry::Frame(map.get_frame_raw_unchecked(key_raw, 0).unwrap())
+                    }
+                    ValueType::Function => {
+                        Entry::Function(map.get_function_raw_unchecked(key_raw, 0).unwrap())
+                    }
+                }
+            }
+        };
+
+        Some((key, entry))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.keys.size_hint()
+    }
+}
+
+impl<'map, 'elem> ExactSizeIterator for Entries<'map, 'elem> {}
+
 /// An iterator over the values associated with a certain key of a map.
 #[derive(Debug, Clone)]
 pub struct ValueIter<'map, 'elem: 'map, T> {
@@ -107,6 +224,7 @@ macro_rules! impl_value_iter {
 }
 
 impl_value_iter!(ValueType::Int, i64, get_int_raw_unchecked);
+impl_value_iter!(ValueType::Int, bool, get_bool_raw_unchecked);
 impl_value_iter!(ValueType::Float, f64, get_float_raw_unchecked);
 impl_value_iter!(ValueType::Data, &'map [u8], get_data_raw_unchecked);
 impl_value_iter!(ValueType::Node, Node<'elem>, get_node_raw_unchecked);
@@ -0,0 +1,28 @@
+/// Creates an `OwnedMap` from key-value pairs.
+///
+/// The first argument is an `API` handle, followed by a semicolon and a comma-separated list of
+/// `key => value` pairs. Every value is stored with `Map::set()`, so it can be of any type
+/// implementing `Value`. The values are borrowed, so nodes and frames can be passed without
+/// cloning them.
+///
+/// Evaluates to a `map::Result<OwnedMap>` holding the first error encountered, if any. This is
+/// mainly intended for building filter arguments for `Plugin::invoke_checked()`, which returns
+/// an error type convertible from `map::Error`, so both can be handled with `?`.
+///
+/// # Example
+/// ```ignore
+/// let args = args! { api; "clip" => node, "width" => 640, "height" => 480 }?;
+/// let rv = resize.invoke_checked("Bicubic", &args)?;
+/// let resized = rv.get_node("clip")?;
+/// ```
+#[macro_export]
+macro_rules! args {
+    ($api:expr; $($key:expr => $value:expr),* $(,)?) => {{
+        let mut map = $crate::map::OwnedMap::new($api);
+        let rv = (|| -> $crate::map::Result<()> {
+            $(map.set($key, &$value)?;)*
+            Ok(())
+        })();
+        rv.map(|()| map)
+    }};
+}
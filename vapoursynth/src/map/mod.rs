@@ -2,23 +2,34 @@
 
 use std::borrow::Cow;
 use std::ffi::{CStr, CString};
+use std::fmt;
 use std::marker::PhantomData;
 use std::ops::{Deref, DerefMut};
 use std::os::raw::c_char;
 use std::ptr::{self, NonNull};
-use std::{mem, result, slice};
+use std::str::Utf8Error;
+use std::{mem, result, slice, str};
 use vapoursynth_sys as ffi;
 
 use crate::api::API;
 use crate::frame::{Frame, FrameRef};
 use crate::function::Function;
 use crate::node::Node;
+use crate::tracking::{self, Resource};
 
 mod errors;
 pub use self::errors::{Error, InvalidKeyError, Result};
 
 mod iterators;
-pub use self::iterators::{Keys, ValueIter};
+pub use self::iterators::{Entries, Entry, Iter, Keys, ValueIter};
+
+mod macros;
+
+#[cfg(feature = "serde")]
+mod serialize;
+
+mod reserved;
+pub use self::reserved::{ColorSpace, ReservedProps, StandardMetadata};
 
 mod value;
 pub use self::value::{Value, ValueType};
@@ -57,6 +68,15 @@ pub struct OwnedMap<'elem> {
     map: Map<'elem>,
 }
 
+/// Determines how `Map::merge()` handles keys present in both maps.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum MergeMode {
+    /// The values in the destination map are replaced with the values from the other map.
+    Replace,
+    /// The values from the other map are appended to the values in the destination map.
+    Append,
+}
+
 unsafe impl<'elem> Send for Map<'elem> {}
 unsafe impl<'elem> Sync for Map<'elem> {}
 
@@ -112,6 +132,7 @@ impl<'elem> Drop for OwnedMap<'elem> {
         unsafe {
             API::get_cached().free_map(&mut self.map);
         }
+        tracking::released(Resource::Map);
     }
 }
 
@@ -131,15 +152,125 @@ impl<'elem> DerefMut for OwnedMap<'elem> {
     }
 }
 
+/// Maximum number of values shown per key by `Map::dump()`.
+const DUMP_MAX_VALUES: usize = 8;
+
+/// Maximum number of bytes shown per data value by `Map::dump()`.
+const DUMP_MAX_DATA: usize = 32;
+
+/// A wrapper displaying the contents of a map, returned from `Map::dump()`.
+#[derive(Debug, Clone, Copy)]
+pub struct Dump<'map, 'elem: 'map> {
+    map: &'map Map<'elem>,
+}
+
+/// Returns the name of the value type as used in `FilterFunction::args()`.
+pub(crate) fn type_name(value_type: ValueType) -> &'static str {
+    match value_type {
+        ValueType::Int => "int",
+        ValueType::Float => "float",
+        ValueType::Data => "data",
+        ValueType::Node => "clip",
+        ValueType::Frame => "frame",
+        ValueType::Function => "func",
+    }
+}
+
+/// Returns a preview of data: a string if it's valid UTF-8 and hex otherwise.
+fn data_preview(data: &[u8]) -> String {
+    if let Ok(x) = str::from_utf8(data) {
+        let end = (0..=x.len().min(DUMP_MAX_DATA))
+            .rev()
+            .find(|&i| x.is_char_boundary(i))
+            .unwrap();
+        let ellipsis = if end < x.len() { "..." } else { "" };
+        format!("{:?}{}", &x[..end], ellipsis)
+    } else {
+        let end = data.len().min(DUMP_MAX_DATA);
+        let hex: String = data[..end].iter().map(|x| format!("{:02x}", x)).collect();
+        let ellipsis = if end < data.len() { "..." } else { "" };
+        format!("0x{}{}", hex, ellipsis)
+    }
+}
+
+impl<'map, 'elem> fmt::Display for Dump<'map, 'elem> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let map = self.map;
+
+        for index in 0..map.key_count() {
+            let key = map.key_raw(index);
+
+            // The key comes from the map, so it's valid and present.
+            let (count, value_type, values) = unsafe {
+                let count = map.value_count_raw_unchecked(key).unwrap();
+                let value_type = map.value_type_raw_unchecked(key).unwrap();
+                let shown = count.min(DUMP_MAX_VALUES);
+                let key = key.to_owned();
+
+                let values: Vec<_> = match value_type {
+                    ValueType::Int => ValueIter::<i64>::new(map, key)
+                        .unwrap()
+                        .take(shown)
+                        .map(|x| x.to_string())
+                        .collect(),
+                    ValueType::Float => ValueIter::<f64>::new(map, key)
+                        .unwrap()
+                        .take(shown)
+                        .map(|x| format!("{:?}", x))
+                        .collect(),
+                    ValueType::Data => ValueIter::<&[u8]>::new(map, key)
+                        .unwrap()
+                        .take(shown)
+                        .map(data_preview)
+                        .collect(),
+                    ValueType::Node => vec!["<node>".to_owned(); shown],
+                    ValueType::Frame => vec!["<frame>".to_owned(); shown],
+                    ValueType::Function => vec!["<function>".to_owned(); shown],
+                };
+
+                (count, value_type, values)
+            };
+
+            if index > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}: {}", key.to_string_lossy(), type_name(value_type))?;
+
+            if count == 1 {
+                write!(f, " = {}", values[0])?;
+            } else {
+                write!(f, "[{}] = [{}", count, values.join(", "))?;
+                if count > values.len() {
+                    write!(f, ", ...")?;
+                }
+                write!(f, "]")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
 impl<'elem> OwnedMap<'elem> {
     /// Creates a new map.
     #[inline]
     pub fn new(api: API) -> Self {
+        tracking::acquired(Resource::Map);
         Self {
             map: unsafe { Map::from_ptr(api.create_map()) },
         }
     }
 
+    /// Creates a new map holding copies of all keys and values of `map`.
+    ///
+    /// Nodes, frames and functions are reference-counted, so only the references are copied.
+    #[inline]
+    pub fn clone_from_map(api: API, map: &Map<'elem>) -> Self {
+        let mut rv = Self::new(api);
+        map.copy_to(&mut rv);
+        rv
+    }
+
     /// Wraps pointer into `OwnedMap`.
     ///
     /// # Safety
@@ -147,6 +278,7 @@ impl<'elem> OwnedMap<'elem> {
     /// map pointer.
     #[inline]
     pub(crate) unsafe fn from_ptr(handle: *mut ffi::VSMap) -> Self {
+        tracking::acquired(Resource::Map);
         Self {
             map: Map::from_ptr(handle),
         }
@@ -183,6 +315,15 @@ impl<'owner, 'elem> MapRefMut<'owner, 'elem> {
     }
 }
 
+/// Replaces a `KeyNotFound` error with `default`.
+#[inline]
+fn or_default<T>(result: Result<T>, default: T) -> Result<T> {
+    match result {
+        Err(Error::KeyNotFound) => Ok(default),
+        x => x,
+    }
+}
+
 /// Turns a `prop_get_something()` error into a `Result`.
 #[inline]
 fn handle_get_prop_error(error: i32) -> Result<()> {
@@ -253,6 +394,26 @@ impl<'elem> Map<'elem> {
         Ok(CString::new(key).unwrap())
     }
 
+    /// Checks if the key is valid and calls `f` with it as a `CStr`.
+    ///
+    /// Unlike `make_raw_key()`, this doesn't allocate for reasonably short keys.
+    #[inline]
+    pub(crate) fn with_raw_key<R, F: FnOnce(&CStr) -> R>(key: &str, f: F) -> Result<R> {
+        const BUF_LEN: usize = 64;
+
+        Map::is_key_valid(key)?;
+
+        if key.len() < BUF_LEN {
+            let mut buf = [0u8; BUF_LEN];
+            buf[..key.len()].copy_from_slice(key.as_bytes());
+            // Valid keys never contain zero bytes.
+            let key = CStr::from_bytes_with_nul(&buf[..=key.len()]).unwrap();
+            Ok(f(key))
+        } else {
+            Ok(f(&CString::new(key).unwrap()))
+        }
+    }
+
     /// Clears the map.
     #[inline]
     pub fn clear(&mut self) {
@@ -263,7 +424,7 @@ impl<'elem> Map<'elem> {
 
     /// Returns the error message contained in the map, if any.
     #[inline]
-    pub fn error(&self) -> Option<Cow<str>> {
+    pub fn error(&self) -> Option<Cow<'_, str>> {
         let error_message = unsafe { API::get_cached().get_error(self) };
         if error_message.is_null() {
             return None;
@@ -273,6 +434,17 @@ impl<'elem> Map<'elem> {
         Some(error_message.to_string_lossy())
     }
 
+    /// Returns the error message contained in the map, if any, and clears the map.
+    ///
+    /// The map is left untouched if it doesn't contain an error. This is useful for converting
+    /// errors returned from `Plugin::invoke()` into other error types.
+    #[inline]
+    pub fn take_error(&mut self) -> Option<String> {
+        let error_message = self.error()?.into_owned();
+        self.clear();
+        Some(error_message)
+    }
+
     /// Adds an error message to a map. The map is cleared first.
     #[inline]
     pub fn set_error(&mut self, error_message: &str) -> Result<()> {
@@ -306,18 +478,51 @@ impl<'elem> Map<'elem> {
     /// Returns a key from a map.
     ///
     /// # Panics
-    /// Panics if `index >= self.key_count()`.
+    /// Panics if `index >= self.key_count()` or if the key isn't valid UTF-8. Keys set through
+    /// this crate are always valid, but maps coming from third-party code might not be; use
+    /// `try_key()` for those.
     #[inline]
     pub fn key(&self, index: usize) -> &str {
-        self.key_raw(index).to_str().unwrap()
+        self.try_key(index).unwrap()
+    }
+
+    /// Returns a key from a map, or an error if the key isn't valid UTF-8.
+    ///
+    /// # Panics
+    /// Panics if `index >= self.key_count()`.
+    #[inline]
+    pub fn try_key(&self, index: usize) -> result::Result<&str, Utf8Error> {
+        self.key_raw(index).to_str()
     }
 
     /// Returns an iterator over all keys in a map.
+    ///
+    /// The iterator panics on keys that aren't valid UTF-8, see `key()`.
     #[inline]
-    pub fn keys(&self) -> Keys {
+    pub fn keys(&self) -> Keys<'_, 'elem> {
         Keys::new(self)
     }
 
+    /// Returns an iterator over all keys in a map along with their value types and the number of
+    /// values associated with them.
+    ///
+    /// The iterator panics on keys that aren't valid UTF-8, see `key()`.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, 'elem> {
+        Iter::new(self)
+    }
+
+    /// Returns an iterator over all keys in a map along with the first value associated with
+    /// each of them.
+    ///
+    /// This is convenient for dumping a whole map, for example for debugging. Use `get_iter()` to
+    /// retrieve the rest of the values of keys holding arrays. The iterator panics on keys that
+    /// aren't valid UTF-8, see `key()`.
+    #[inline]
+    pub fn entries(&self) -> Entries<'_, 'elem> {
+        Entries::new(self)
+    }
+
     /// Returns the number of elements associated with a key in a map.
     ///
     /// # Safety
@@ -340,6 +545,53 @@ impl<'elem> Map<'elem> {
         unsafe { self.value_count_raw_unchecked(&key) }
     }
 
+    /// Returns the number of elements associated with the first of `keys` present in the map.
+    ///
+    /// Returns the index of that key in `keys` along with the count. This is useful for filters
+    /// accepting an argument under several names, for example a legacy and a new one.
+    ///
+    /// Returns `Error::KeyNotFound` if none of the keys are present. Invalid keys result in an
+    /// error even if an earlier key is missing.
+    #[inline]
+    pub fn value_count_any(&self, keys: &[&str]) -> Result<(usize, usize)> {
+        for (index, key) in keys.iter().enumerate() {
+            match self.value_count(key) {
+                Ok(count) => return Ok((index, count)),
+                Err(Error::KeyNotFound) => {}
+                Err(err) => return Err(err),
+            }
+        }
+
+        Err(Error::KeyNotFound)
+    }
+
+    /// Returns `true` if the map contains the given key.
+    ///
+    /// Invalid keys are never present in a map, so `false` is returned for them.
+    ///
+    /// This doesn't allocate unless the key is very long.
+    #[inline]
+    pub fn contains_key(&self, key: &str) -> bool {
+        Map::with_raw_key(key, |key| unsafe {
+            self.value_type_raw_unchecked(key).is_ok()
+        })
+        .unwrap_or(false)
+    }
+
+    /// Returns `true` if the map contains the given key.
+    ///
+    /// This is a faster version of `contains_key()` for callers with a pre-made key. Invalid keys
+    /// are never present in a map, so `false` is returned for them.
+    #[inline]
+    pub fn contains_key_cstr(&self, key: &CStr) -> bool {
+        match key.to_str() {
+            Ok(x) if Map::is_key_valid(x).is_ok() => unsafe {
+                self.value_type_raw_unchecked(key).is_ok()
+            },
+            _ => false,
+        }
+    }
+
     /// Retrieves a value type from a map.
     ///
     /// # Safety
@@ -365,6 +617,55 @@ impl<'elem> Map<'elem> {
         unsafe { self.value_type_raw_unchecked(&key) }
     }
 
+    /// Returns a one-line summary of the keys in the map along with their value types and counts.
+    ///
+    /// The types are named the same way as in `FilterFunction::args()`, and keys holding a number
+    /// of values other than one have the count appended: `clip: clip, planes: int[3]`. The
+    /// values themselves aren't included, so this is cheap even for maps holding large data.
+    ///
+    /// This is useful for diagnostics, for example logging the arguments a filter was called with
+    /// from `FilterFunction::create()`.
+    pub fn summary(&self) -> String {
+        let mut rv = String::new();
+
+        for index in 0..self.key_count() {
+            let key = self.key_raw(index);
+
+            // The key comes from the map, so it's valid and present.
+            let (count, value_type) = unsafe {
+                (
+                    self.value_count_raw_unchecked(key).unwrap(),
+                    self.value_type_raw_unchecked(key).unwrap(),
+                )
+            };
+
+            if index > 0 {
+                rv.push_str(", ");
+            }
+            rv.push_str(&key.to_string_lossy());
+            rv.push_str(": ");
+            rv.push_str(type_name(value_type));
+            if count != 1 {
+                rv.push_str(&format!("[{}]", count));
+            }
+        }
+
+        rv
+    }
+
+    /// Returns a wrapper displaying the full contents of the map, one key per line.
+    ///
+    /// Each line contains the key, the value type and count in the same format as `summary()`,
+    /// and a preview of the values: `planes: int[3] = [0, 1, 2]`. At most 8 values are shown
+    /// per key. Data is shown as a string if it's valid UTF-8 and as hex otherwise, truncated to
+    /// 32 bytes. Nodes, frames and functions are shown as `<node>`, `<frame>` and `<function>`.
+    ///
+    /// This is useful for debugging, for example finding out why a filter got the wrong arguments.
+    #[inline]
+    pub fn dump(&self) -> Dump<'_, 'elem> {
+        Dump { map: self }
+    }
+
     /// Deletes the given key.
     ///
     /// # Safety
@@ -387,6 +688,116 @@ impl<'elem> Map<'elem> {
         unsafe { self.delete_key_raw_unchecked(&key) }
     }
 
+    /// Copies all integer, floating point and data values into `dest`, replacing the values
+    /// already present there. Nodes, frames and functions are skipped.
+    pub(crate) fn copy_plain_values_to(&self, dest: &mut Map<'elem>) {
+        for index in 0..self.key_count() {
+            let key = self.key_raw(index);
+
+            // The key comes from the map, so it's valid and present.
+            unsafe {
+                let value_type = self.value_type_raw_unchecked(key).unwrap();
+                if let ValueType::Node | ValueType::Frame | ValueType::Function = value_type {
+                    continue;
+                }
+
+                let _ = dest.delete_key_raw_unchecked(key);
+                self.append_values_to(key, dest);
+            }
+        }
+    }
+
+    /// Appends all values associated with `key` to `dest`, creating the key there if needed.
+    ///
+    /// # Safety
+    /// The caller must ensure `key` is valid and present in this map, and that `key` either isn't
+    /// present in `dest` or holds values of the same type there.
+    unsafe fn append_values_to(&self, key: &CStr, dest: &mut Map<'elem>) {
+        let count = self.value_count_raw_unchecked(key).unwrap() as i32;
+        let value_type = self.value_type_raw_unchecked(key).unwrap();
+
+        dest.touch_raw_unchecked(key, value_type);
+
+        for i in 0..count {
+            match value_type {
+                ValueType::Int => {
+                    let x = self.get_int_raw_unchecked(key, i).unwrap();
+                    dest.append_int_raw_unchecked(key, x).unwrap();
+                }
+                ValueType::Float => {
+                    let x = self.get_float_raw_unchecked(key, i).unwrap();
+                    dest.append_float_raw_unchecked(key, x).unwrap();
+                }
+                ValueType::Data => {
+                    let x = self.get_data_raw_unchecked(key, i).unwrap();
+                    dest.append_data_raw_unchecked(key, x).unwrap();
+                }
+                ValueType::Node => {
+                    let x = self.get_node_raw_unchecked(key, i).unwrap();
+                    dest.append_node_raw_unchecked(key, &x).unwrap();
+                }
+                ValueType::Frame => {
+                    let x = self.get_frame_raw_unchecked(key, i).unwrap();
+                    dest.append_frame_raw_unchecked(key, &x).unwrap();
+                }
+                ValueType::Function => {
+                    let x = self.get_function_raw_unchecked(key, i).unwrap();
+                    dest.append_function_raw_unchecked(key, &x).unwrap();
+                }
+            }
+        }
+    }
+
+    /// Copies all values into `dest`, replacing the values already present there.
+    pub(crate) fn copy_to(&self, dest: &mut Map<'elem>) {
+        for index in 0..self.key_count() {
+            let key = self.key_raw(index);
+
+            // The key comes from the map, so it's valid and present.
+            unsafe {
+                let _ = dest.delete_key_raw_unchecked(key);
+                self.append_values_to(key, dest);
+            }
+        }
+    }
+
+    /// Copies all keys and values of `other` into this map.
+    ///
+    /// Keys present in both maps are handled according to `mode`. With `MergeMode::Append`, all
+    /// keys present in both maps must hold values of the same type, otherwise
+    /// `Error::WrongValueType` is returned and this map is left untouched.
+    pub fn merge(&mut self, other: &Map<'elem>, mode: MergeMode) -> Result<()> {
+        match mode {
+            MergeMode::Replace => other.copy_to(self),
+            MergeMode::Append => {
+                for index in 0..other.key_count() {
+                    let key = other.key_raw(index);
+
+                    // The key comes from the map, so it's valid and present.
+                    unsafe {
+                        let value_type = other.value_type_raw_unchecked(key).unwrap();
+                        match self.value_type_raw_unchecked(key) {
+                            Ok(x) if x != value_type => return Err(Error::WrongValueType),
+                            _ => {}
+                        }
+                    }
+                }
+
+                for index in 0..other.key_count() {
+                    let key = other.key_raw(index);
+
+                    // The key comes from the map, so it's valid and present, and the types were
+                    // checked above.
+                    unsafe {
+                        other.append_values_to(key, self);
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     /// Touches the key. That is, if the key exists, nothing happens, otherwise a key is created
     /// with no values associated.
     ///
@@ -464,6 +875,15 @@ impl<'elem> Map<'elem> {
         unsafe { self.get_int_raw_unchecked(&key, 0) }
     }
 
+    /// Retrieves an integer from a map, or returns `default` if the key isn't present.
+    ///
+    /// Only a missing key results in `default`. Other errors, like the key holding a value of a
+    /// different type, are still returned.
+    #[inline]
+    pub fn get_int_or(&self, key: &str, default: i64) -> Result<i64> {
+        or_default(self.get_int(key), default)
+    }
+
     /// Retrieves integers from a map.
     #[inline]
     pub fn get_int_iter<'map>(&'map self, key: &str) -> Result<ValueIter<'map, 'elem, i64>> {
@@ -481,6 +901,25 @@ impl<'elem> Map<'elem> {
         unsafe { self.get_int_array_raw_unchecked(&key) }
     }
 
+    /// Retrieves a boolean from a map.
+    ///
+    /// VapourSynth stores booleans as integers: any nonzero value is `true` and zero is `false`.
+    /// This function retrieves the first value associated with the key.
+    #[inline]
+    pub fn get_bool(&self, key: &str) -> Result<bool> {
+        let key = Map::make_raw_key(key)?;
+        unsafe { self.get_bool_raw_unchecked(&key, 0) }
+    }
+
+    /// Retrieves booleans from a map.
+    ///
+    /// See `get_bool()`.
+    #[inline]
+    pub fn get_bool_iter<'map>(&'map self, key: &str) -> Result<ValueIter<'map, 'elem, bool>> {
+        let key = Map::make_raw_key(key)?;
+        unsafe { ValueIter::<bool>::new(self, key) }
+    }
+
     /// Retrieves a floating point number from a map.
     ///
     /// This function retrieves the first value associated with the key.
@@ -490,6 +929,16 @@ impl<'elem> Map<'elem> {
         unsafe { self.get_float_raw_unchecked(&key, 0) }
     }
 
+    /// Retrieves a floating point number from a map, or returns `default` if the key isn't
+    /// present.
+    ///
+    /// Only a missing key results in `default`. Other errors, like the key holding a value of a
+    /// different type, are still returned.
+    #[inline]
+    pub fn get_float_or(&self, key: &str, default: f64) -> Result<f64> {
+        or_default(self.get_float(key), default)
+    }
+
     /// Retrieves an array of floating point numbers from a map.
     ///
     /// This is faster than iterating over a `get_float_iter()`.
@@ -516,6 +965,24 @@ impl<'elem> Map<'elem> {
         unsafe { self.get_data_raw_unchecked(&key, 0) }
     }
 
+    /// Retrieves data from a map, or returns `default` if the key isn't present.
+    ///
+    /// Only a missing key results in `default`. Other errors, like the key holding a value of a
+    /// different type, are still returned.
+    #[inline]
+    pub fn get_data_or<'map>(&'map self, key: &str, default: &'map [u8]) -> Result<&'map [u8]> {
+        or_default(self.get_data(key), default)
+    }
+
+    /// Retrieves data from a map as a UTF-8 string.
+    ///
+    /// This function retrieves the first value associated with the key. Returns
+    /// `Error::InvalidUtf8` if the data isn't valid UTF-8.
+    #[inline]
+    pub fn get_data_str(&self, key: &str) -> Result<&str> {
+        Ok(str::from_utf8(self.get_data(key)?)?)
+    }
+
     /// Retrieves data from a map.
     #[inline]
     pub fn get_data_iter<'map>(
@@ -545,6 +1012,14 @@ impl<'elem> Map<'elem> {
         unsafe { ValueIter::<Node>::new(self, key) }
     }
 
+    /// Retrieves all nodes associated with the key, in order.
+    ///
+    /// This is useful for consuming the output of filters returning several clips at once.
+    #[inline]
+    pub fn get_node_vec(&self, key: &str) -> Result<Vec<Node<'elem>>> {
+        self.get_node_iter(key).map(Iterator::collect)
+    }
+
     /// Retrieves a frame from a map.
     ///
     /// This function retrieves the first value associated with the key.
@@ -596,6 +1071,15 @@ impl<'elem> Map<'elem> {
         Ok(value)
     }
 
+    /// Retrieves a boolean from a map.
+    ///
+    /// # Safety
+    /// The caller must ensure `key` is valid.
+    #[inline]
+    pub(crate) unsafe fn get_bool_raw_unchecked(&self, key: &CStr, index: i32) -> Result<bool> {
+        self.get_int_raw_unchecked(key, index).map(|x| x != 0)
+    }
+
     /// Retrieves an array of integers from a map.
     ///
     /// # Safety
@@ -715,6 +1199,14 @@ impl<'elem> Map<'elem> {
         unsafe { self.append_int_raw_unchecked(&key, x) }
     }
 
+    /// Appends a boolean to a map.
+    ///
+    /// The boolean is stored as an integer, 1 for `true` and 0 for `false`.
+    #[inline]
+    pub fn append_bool(&mut self, key: &str, x: bool) -> Result<()> {
+        self.append_int(key, i64::from(x))
+    }
+
     /// Appends a floating point number to a map.
     #[inline]
     pub fn append_float(&mut self, key: &str, x: f64) -> Result<()> {
@@ -729,6 +1221,12 @@ impl<'elem> Map<'elem> {
         unsafe { self.append_data_raw_unchecked(&key, x) }
     }
 
+    /// Appends a string to a map as data.
+    #[inline]
+    pub fn append_data_str(&mut self, key: &str, x: &str) -> Result<()> {
+        self.append_data(key, x.as_bytes())
+    }
+
     /// Appends a node to a map.
     #[inline]
     pub fn append_node(&mut self, key: &str, x: &Node<'elem>) -> Result<()> {
@@ -860,6 +1358,14 @@ impl<'elem> Map<'elem> {
         Ok(())
     }
 
+    /// Sets a property value to a boolean.
+    ///
+    /// The boolean is stored as an integer, 1 for `true` and 0 for `false`.
+    #[inline]
+    pub fn set_bool(&mut self, key: &str, x: bool) -> Result<()> {
+        self.set_int(key, i64::from(x))
+    }
+
     /// Sets a property value to an integer array.
     ///
     /// This is faster than calling `append_int()` in a loop.
@@ -896,6 +1402,52 @@ impl<'elem> Map<'elem> {
         Ok(())
     }
 
+    /// Sets a property value to an integer array.
+    ///
+    /// Unlike `set_int_array()`, this is available on all API versions. With API 3.1 and above it
+    /// forwards to `set_int_array()`, otherwise the integers are appended one by one.
+    #[inline]
+    pub fn set_int_slice(&mut self, key: &str, x: &[i64]) -> Result<()> {
+        let key = Map::make_raw_key(key)?;
+        unsafe {
+            #[cfg(feature = "gte-vapoursynth-api-31")]
+            self.set_int_array_raw_unchecked(&key, x);
+
+            #[cfg(not(feature = "gte-vapoursynth-api-31"))]
+            {
+                let _ = self.delete_key_raw_unchecked(&key);
+                self.touch_raw_unchecked(&key, ValueType::Int);
+                for &x in x {
+                    self.append_int_raw_unchecked(&key, x)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Sets a property value to a floating point number array.
+    ///
+    /// Unlike `set_float_array()`, this is available on all API versions. With API 3.1 and above
+    /// it forwards to `set_float_array()`, otherwise the numbers are appended one by one.
+    #[inline]
+    pub fn set_float_slice(&mut self, key: &str, x: &[f64]) -> Result<()> {
+        let key = Map::make_raw_key(key)?;
+        unsafe {
+            #[cfg(feature = "gte-vapoursynth-api-31")]
+            self.set_float_array_raw_unchecked(&key, x);
+
+            #[cfg(not(feature = "gte-vapoursynth-api-31"))]
+            {
+                let _ = self.delete_key_raw_unchecked(&key);
+                self.touch_raw_unchecked(&key, ValueType::Float);
+                for &x in x {
+                    self.append_float_raw_unchecked(&key, x)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
     /// Sets a property value to data.
     #[inline]
     pub fn set_data(&mut self, key: &str, x: &[u8]) -> Result<()> {
@@ -906,6 +1458,12 @@ impl<'elem> Map<'elem> {
         Ok(())
     }
 
+    /// Sets a property value to a string stored as data.
+    #[inline]
+    pub fn set_data_str(&mut self, key: &str, x: &str) -> Result<()> {
+        self.set_data(key, x.as_bytes())
+    }
+
     /// Sets a property value to a node.
     #[inline]
     pub fn set_node(&mut self, key: &str, x: &Node<'elem>) -> Result<()> {
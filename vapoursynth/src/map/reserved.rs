@@ -0,0 +1,218 @@
+//! Typed access to the frame properties reserved by VapourSynth.
+
+use std::ffi::CStr;
+
+use crate::map::Map;
+
+/// Converts a nul-terminated reserved key into a `CStr`.
+#[inline]
+fn key(bytes: &'static [u8]) -> &'static CStr {
+    CStr::from_bytes_with_nul(bytes).unwrap()
+}
+
+const DURATION_NUM: &[u8] = b"_DurationNum\0";
+const DURATION_DEN: &[u8] = b"_DurationDen\0";
+const SAR_NUM: &[u8] = b"_SARNum\0";
+const SAR_DEN: &[u8] = b"_SARDen\0";
+const MATRIX: &[u8] = b"_Matrix\0";
+const PRIMARIES: &[u8] = b"_Primaries\0";
+const TRANSFER: &[u8] = b"_Transfer\0";
+const COLOR_RANGE: &[u8] = b"_ColorRange\0";
+const CHROMA_LOCATION: &[u8] = b"_ChromaLocation\0";
+const FIELD_BASED: &[u8] = b"_FieldBased\0";
+const FIELD: &[u8] = b"_Field\0";
+const PICT_TYPE: &[u8] = b"_PictType\0";
+
+/// The color space of a frame, made of the `_Matrix`, `_Primaries`, `_Transfer` and
+/// `_ColorRange` properties.
+///
+/// The values are the raw integers stored by VapourSynth, which follow ITU-T H.265 for the first
+/// three, for example 1 means BT.709. For the color range, 0 means full range and 1 means limited
+/// range. `None` means that the property is absent.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
+pub struct ColorSpace {
+    /// The matrix coefficients (`_Matrix`).
+    pub matrix: Option<i64>,
+
+    /// The color primaries (`_Primaries`).
+    pub primaries: Option<i64>,
+
+    /// The transfer characteristics (`_Transfer`).
+    pub transfer: Option<i64>,
+
+    /// The color range (`_ColorRange`).
+    pub range: Option<i64>,
+}
+
+/// A snapshot of the commonly used reserved frame properties.
+///
+/// The integer values are the raw integers stored by VapourSynth. `None` means that the property
+/// is absent or holds a value of an unexpected type.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
+pub struct StandardMetadata {
+    /// The frame duration in seconds (`_DurationNum` and `_DurationDen`), see
+    /// `ReservedProps::duration()`.
+    pub duration: Option<(i64, i64)>,
+
+    /// The sample aspect ratio (`_SARNum` and `_SARDen`), see
+    /// `ReservedProps::sample_aspect_ratio()`.
+    pub sample_aspect_ratio: Option<(i64, i64)>,
+
+    /// The color space (`_Matrix`, `_Primaries`, `_Transfer` and `_ColorRange`).
+    pub color_space: ColorSpace,
+
+    /// The chroma sample position (`_ChromaLocation`), for example 0 means left.
+    pub chroma_location: Option<i64>,
+
+    /// Whether the frame is interlaced (`_FieldBased`): 0 means progressive, 1 means bottom field
+    /// first and 2 means top field first.
+    pub field_based: Option<i64>,
+
+    /// Which field the frame was made from (`_Field`) if it was separated into fields: 0 means
+    /// the bottom field and 1 means the top field.
+    pub field: Option<i64>,
+
+    /// The picture type (`_PictType`), for example `'I'`, `'P'` or `'B'`.
+    pub pict_type: Option<char>,
+}
+
+/// Typed accessors for the frame properties reserved by VapourSynth.
+///
+/// The getters return `None` when a property is absent or holds a value of an unexpected type, so
+/// clips lacking optional properties don't need any special handling.
+pub trait ReservedProps {
+    /// Returns the frame duration in seconds (`_DurationNum` and `_DurationDen`) as a
+    /// `(numerator, denominator)` pair.
+    ///
+    /// `None` is returned if either property is absent or the denominator is zero.
+    fn duration(&self) -> Option<(i64, i64)>;
+
+    /// Sets the frame duration (`_DurationNum` and `_DurationDen`).
+    ///
+    /// Passing `None` removes both properties.
+    fn set_duration(&mut self, duration: Option<(i64, i64)>);
+
+    /// Returns the sample aspect ratio (`_SARNum` and `_SARDen`) as a `(numerator, denominator)`
+    /// pair.
+    ///
+    /// `None` is returned if either property is absent or the denominator is zero. It usually
+    /// means square pixels.
+    fn sample_aspect_ratio(&self) -> Option<(i64, i64)>;
+
+    /// Sets the sample aspect ratio (`_SARNum` and `_SARDen`).
+    ///
+    /// Passing `None` removes both properties.
+    fn set_sample_aspect_ratio(&mut self, sar: Option<(i64, i64)>);
+
+    /// Returns the color space (`_Matrix`, `_Primaries`, `_Transfer` and `_ColorRange`).
+    fn color_space(&self) -> ColorSpace;
+
+    /// Sets the color space (`_Matrix`, `_Primaries`, `_Transfer` and `_ColorRange`).
+    ///
+    /// Properties corresponding to `None` fields are removed.
+    fn set_color_space(&mut self, color_space: ColorSpace);
+
+    /// Returns the commonly used reserved properties in one go.
+    fn standard_metadata(&self) -> StandardMetadata;
+}
+
+/// Reads a rational stored in two integer properties.
+fn get_rational(map: &Map, num: &'static [u8], den: &'static [u8]) -> Option<(i64, i64)> {
+    let num = unsafe { map.get_int_raw_unchecked(key(num), 0) }.ok()?;
+    let den = unsafe { map.get_int_raw_unchecked(key(den), 0) }.ok()?;
+
+    if den == 0 {
+        None
+    } else {
+        Some((num, den))
+    }
+}
+
+/// Stores a rational in two integer properties, or removes them if `value` is `None`.
+fn set_rational(map: &mut Map, num: &'static [u8], den: &'static [u8], value: Option<(i64, i64)>) {
+    unsafe {
+        if let Some((x, y)) = value {
+            map.set_int_raw_unchecked(key(num), x);
+            map.set_int_raw_unchecked(key(den), y);
+        } else {
+            let _ = map.delete_key_raw_unchecked(key(num));
+            let _ = map.delete_key_raw_unchecked(key(den));
+        }
+    }
+}
+
+/// Reads an integer property.
+fn get_int(map: &Map, name: &'static [u8]) -> Option<i64> {
+    unsafe { map.get_int_raw_unchecked(key(name), 0) }.ok()
+}
+
+/// Reads a single character stored in a data property.
+fn get_char(map: &Map, name: &'static [u8]) -> Option<char> {
+    match unsafe { map.get_data_raw_unchecked(key(name), 0) }.ok()? {
+        &[x] => Some(char::from(x)),
+        _ => None,
+    }
+}
+
+/// Stores an integer property, or removes it if `value` is `None`.
+fn set_int(map: &mut Map, name: &'static [u8], value: Option<i64>) {
+    unsafe {
+        if let Some(x) = value {
+            map.set_int_raw_unchecked(key(name), x);
+        } else {
+            let _ = map.delete_key_raw_unchecked(key(name));
+        }
+    }
+}
+
+impl<'elem> ReservedProps for Map<'elem> {
+    #[inline]
+    fn duration(&self) -> Option<(i64, i64)> {
+        get_rational(self, DURATION_NUM, DURATION_DEN)
+    }
+
+    #[inline]
+    fn set_duration(&mut self, duration: Option<(i64, i64)>) {
+        set_rational(self, DURATION_NUM, DURATION_DEN, duration)
+    }
+
+    #[inline]
+    fn sample_aspect_ratio(&self) -> Option<(i64, i64)> {
+        get_rational(self, SAR_NUM, SAR_DEN)
+    }
+
+    #[inline]
+    fn set_sample_aspect_ratio(&mut self, sar: Option<(i64, i64)>) {
+        set_rational(self, SAR_NUM, SAR_DEN, sar)
+    }
+
+    #[inline]
+    fn color_space(&self) -> ColorSpace {
+        ColorSpace {
+            matrix: get_int(self, MATRIX),
+            primaries: get_int(self, PRIMARIES),
+            transfer: get_int(self, TRANSFER),
+            range: get_int(self, COLOR_RANGE),
+        }
+    }
+
+    #[inline]
+    fn set_color_space(&mut self, color_space: ColorSpace) {
+        set_int(self, MATRIX, color_space.matrix);
+        set_int(self, PRIMARIES, color_space.primaries);
+        set_int(self, TRANSFER, color_space.transfer);
+        set_int(self, COLOR_RANGE, color_space.range);
+    }
+
+    fn standard_metadata(&self) -> StandardMetadata {
+        StandardMetadata {
+            duration: self.duration(),
+            sample_aspect_ratio: self.sample_aspect_ratio(),
+            color_space: self.color_space(),
+            chroma_location: get_int(self, CHROMA_LOCATION),
+            field_based: get_int(self, FIELD_BASED),
+            field: get_int(self, FIELD),
+            pict_type: get_char(self, PICT_TYPE),
+        }
+    }
+}
@@ -0,0 +1,169 @@
+//! Serialization of maps with serde.
+
+use std::fmt;
+use std::marker::PhantomData;
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::de::{self, Deserialize, Deserializer, MapAccess, Visitor};
+use serde::ser::{self, Serialize, SerializeMap, Serializer};
+
+use crate::api::API;
+use crate::map::{Map, OwnedMap, ValueType};
+
+/// The serialized form of the values associated with a key.
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Values {
+    Int(i64),
+    Float(f64),
+    Data(String),
+    IntArray(Vec<i64>),
+    FloatArray(Vec<f64>),
+    DataArray(Vec<String>),
+}
+
+impl Values {
+    /// Reads the values associated with `key`.
+    ///
+    /// Returns an error message for nodes, frames and functions, which can't be serialized.
+    fn from_map(map: &Map, key: &str) -> Result<Self, String> {
+        let count = map.value_count(key).map_err(|e| e.to_string())?;
+        let value_type = map.value_type(key).map_err(|e| e.to_string())?;
+
+        // The key comes from the map and holds values of the right type.
+        Ok(match (value_type, count) {
+            (ValueType::Int, 1) => Values::Int(map.get_int(key).unwrap()),
+            (ValueType::Int, _) => Values::IntArray(map.get_int_iter(key).unwrap().collect()),
+            (ValueType::Float, 1) => Values::Float(map.get_float(key).unwrap()),
+            (ValueType::Float, _) => Values::FloatArray(map.get_float_iter(key).unwrap().collect()),
+            (ValueType::Data, 1) => Values::Data(BASE64.encode(map.get_data(key).unwrap())),
+            (ValueType::Data, _) => Values::DataArray(
+                map.get_data_iter(key)
+                    .unwrap()
+                    .map(|x| BASE64.encode(x))
+                    .collect(),
+            ),
+            (ValueType::Node, _) | (ValueType::Frame, _) | (ValueType::Function, _) => {
+                return Err(format!(
+                    "Key \"{}\" holds nodes, frames or functions, which can't be serialized",
+                    key
+                ));
+            }
+        })
+    }
+
+    /// Stores the values in `map` under `key`, replacing any existing values.
+    fn store_in_map(self, map: &mut Map, key: &str) -> Result<(), String> {
+        fn decode(x: &str) -> Result<Vec<u8>, String> {
+            BASE64.decode(x).map_err(|e| e.to_string())
+        }
+
+        let result = match self {
+            Values::Int(x) => map.set_int(key, x),
+            Values::Float(x) => map.set_float(key, x),
+            Values::Data(x) => map.set_data(key, &decode(&x)?),
+            Values::IntArray(xs) => map
+                .touch(key, ValueType::Int)
+                .and_then(|()| xs.into_iter().try_for_each(|x| map.append_int(key, x))),
+            Values::FloatArray(xs) => map
+                .touch(key, ValueType::Float)
+                .and_then(|()| xs.into_iter().try_for_each(|x| map.append_float(key, x))),
+            Values::DataArray(xs) => {
+                let xs = xs
+                    .iter()
+                    .map(|x| decode(x))
+                    .collect::<Result<Vec<_>, _>>()?;
+                map.touch(key, ValueType::Data)
+                    .and_then(|()| xs.iter().try_for_each(|x| map.append_data(key, x)))
+            }
+        };
+
+        result.map_err(|e| format!("Couldn't store key \"{}\": {}", key, e))
+    }
+}
+
+/// Serializes the map as a map from keys to their values.
+///
+/// Each value is tagged with its type: `int`, `float` or `data` for keys holding a single value,
+/// `int_array`, `float_array` or `data_array` for keys holding any other number of values. Data is
+/// encoded with base64. In JSON, a map looks like
+/// `{"_Matrix": {"int": 1}, "planes": {"int_array": [0, 2]}, "name": {"data": "YXNk"}}`.
+///
+/// Nodes, frames and functions can't be serialized, so maps containing them result in an error,
+/// as do keys which aren't valid UTF-8.
+impl<'elem> Serialize for Map<'elem> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut rv = serializer.serialize_map(Some(self.key_count()))?;
+
+        for index in 0..self.key_count() {
+            let key = self.try_key(index).map_err(ser::Error::custom)?;
+            let values = Values::from_map(self, key).map_err(ser::Error::custom)?;
+            rv.serialize_entry(key, &values)?;
+        }
+
+        rv.end()
+    }
+}
+
+impl<'elem> Serialize for OwnedMap<'elem> {
+    #[inline]
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Map::serialize(self, serializer)
+    }
+}
+
+/// Returns the API, retrieving it if this build is able to.
+#[inline]
+fn api() -> Option<API> {
+    #[cfg(any(
+        feature = "vapoursynth-functions",
+        all(feature = "vsscript-functions", feature = "gte-vsscript-api-32")
+    ))]
+    {
+        API::get()
+    }
+
+    #[cfg(not(any(
+        feature = "vapoursynth-functions",
+        all(feature = "vsscript-functions", feature = "gte-vsscript-api-32")
+    )))]
+    {
+        API::get_cached_opt()
+    }
+}
+
+/// Deserializes a map in the format produced by `Map`'s `Serialize` implementation.
+///
+/// The map is created with the API returned by `API::get()`. Without the linking features, which
+/// is usually the case for plugins, the API that VapourSynth has passed to the plugin is used.
+/// Deserialization fails if no API is available.
+impl<'de, 'elem> Deserialize<'de> for OwnedMap<'elem> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct OwnedMapVisitor<'elem>(PhantomData<&'elem ()>);
+
+        impl<'de, 'elem> Visitor<'de> for OwnedMapVisitor<'elem> {
+            type Value = OwnedMap<'elem>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a map from keys to tagged values")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut access: A) -> Result<Self::Value, A::Error> {
+                let api = api()
+                    .ok_or_else(|| de::Error::custom("The VapourSynth API isn't available"))?;
+                let mut map = OwnedMap::new(api);
+
+                while let Some((key, values)) = access.next_entry::<String, Values>()? {
+                    values
+                        .store_in_map(&mut map, &key)
+                        .map_err(de::Error::custom)?;
+                }
+
+                Ok(map)
+            }
+        }
+
+        deserializer.deserialize_map(OwnedMapVisitor(PhantomData))
+    }
+}
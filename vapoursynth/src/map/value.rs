@@ -51,6 +51,28 @@ impl<'map, 'elem: 'map> Value<'map, 'elem> for i64 {
     }
 }
 
+impl<'map, 'elem: 'map> Value<'map, 'elem> for bool {
+    #[inline]
+    fn get_from_map(map: &Map, key: &str) -> Result<Self> {
+        map.get_bool(key)
+    }
+
+    #[inline]
+    fn get_iter_from_map(map: &'map Map<'elem>, key: &str) -> Result<ValueIter<'map, 'elem, Self>> {
+        map.get_bool_iter(key)
+    }
+
+    #[inline]
+    fn store_in_map(map: &mut Map, key: &str, x: &Self) -> Result<()> {
+        map.set_bool(key, *x)
+    }
+
+    #[inline]
+    fn append_to_map(map: &mut Map, key: &str, x: &Self) -> Result<()> {
+        map.append_bool(key, *x)
+    }
+}
+
 impl<'map, 'elem: 'map> Value<'map, 'elem> for f64 {
     fn get_from_map(map: &Map, key: &str) -> Result<Self> {
         map.get_float(key)
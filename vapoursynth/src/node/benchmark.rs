@@ -0,0 +1,56 @@
+//! Measuring frame generation speed.
+
+use std::ops::Range;
+use std::time::{Duration, Instant};
+
+use crate::node::{GetFrameError, Node};
+
+/// The result of `Node::benchmark()`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BenchResult {
+    /// Number of generated frames.
+    pub frames: usize,
+
+    /// Time it took to generate all frames.
+    pub elapsed: Duration,
+
+    /// Average number of frames generated per second, or 0 if no frames were generated.
+    pub fps: f64,
+}
+
+impl<'core> Node<'core> {
+    /// Measures how fast the frames in `range` are generated.
+    ///
+    /// Up to `requests` frames are requested at a time, see `frames_with_props()`. The frames are
+    /// dropped as soon as they are done, so nothing but generating them is measured. Note that
+    /// frames already in the cache of this node or upstream nodes are returned without being
+    /// generated again, so benchmarking the same range twice in a row measures the cache.
+    ///
+    /// Returns an error if any of the frames couldn't be generated.
+    ///
+    /// # Panics
+    /// Panics if `range.end` is greater than `i32::MAX + 1`.
+    pub fn benchmark(
+        &self,
+        range: Range<usize>,
+        requests: usize,
+    ) -> Result<BenchResult, GetFrameError<'static>> {
+        let frames = range.len();
+
+        let start = Instant::now();
+        for frame in self.ordered_frames(range, requests) {
+            frame?;
+        }
+        let elapsed = start.elapsed();
+
+        Ok(BenchResult {
+            frames,
+            elapsed,
+            fps: if frames == 0 {
+                0.
+            } else {
+                frames as f64 / elapsed.as_secs_f64()
+            },
+        })
+    }
+}
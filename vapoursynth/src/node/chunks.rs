@@ -0,0 +1,71 @@
+//! Splitting clips into chunks.
+
+use std::ops::Range;
+
+use crate::node::{Node, UnknownLength};
+
+/// Splits `0..num_frames` into chunks of at most `chunk_len` frames, ending chunks at the last
+/// boundary within reach.
+fn split(num_frames: usize, chunk_len: usize, boundaries: &[usize]) -> Vec<Range<usize>> {
+    let mut boundaries = boundaries.to_vec();
+    boundaries.sort_unstable();
+
+    let mut chunks = Vec::with_capacity(num_frames / chunk_len + 1);
+    let mut start = 0;
+
+    while start < num_frames {
+        let limit = start.saturating_add(chunk_len).min(num_frames);
+
+        let end = boundaries
+            .iter()
+            .copied()
+            .rev()
+            .find(|&x| x > start && x <= limit)
+            .unwrap_or(limit);
+
+        chunks.push(start..end);
+        start = end;
+    }
+
+    chunks
+}
+
+impl<'core> Node<'core> {
+    /// Splits the clip into contiguous frame ranges of `chunk_len` frames.
+    ///
+    /// The ranges cover the whole clip in order, so they can be processed independently, for
+    /// example encoded in parallel and concatenated. The last range is shorter if the frame count
+    /// isn't a multiple of `chunk_len`.
+    ///
+    /// Returns an error if the clip has unknown length.
+    ///
+    /// # Panics
+    /// Panics if `chunk_len` is zero.
+    #[inline]
+    pub fn chunks(&self, chunk_len: usize) -> Result<Vec<Range<usize>>, UnknownLength> {
+        self.chunks_at_boundaries(chunk_len, &[])
+    }
+
+    /// Splits the clip into contiguous frame ranges of at most `chunk_len` frames, preferring to
+    /// start the ranges at `boundaries`.
+    ///
+    /// `boundaries` are the frames which are good places to start a new range at, for example
+    /// scene changes or keyframes, in any order. Every range ends at the last boundary within
+    /// `chunk_len` frames of its start, or after exactly `chunk_len` frames if there's no such
+    /// boundary. This keeps every range at most `chunk_len` long while aligning the ranges to the
+    /// boundaries where possible. Boundaries outside of the clip are ignored.
+    ///
+    /// Returns an error if the clip has unknown length.
+    ///
+    /// # Panics
+    /// Panics if `chunk_len` is zero.
+    pub fn chunks_at_boundaries(
+        &self,
+        chunk_len: usize,
+        boundaries: &[usize],
+    ) -> Result<Vec<Range<usize>>, UnknownLength> {
+        assert!(chunk_len > 0, "the chunk length must be positive");
+
+        Ok(split(self.known_num_frames()?, chunk_len, boundaries))
+    }
+}
@@ -0,0 +1,36 @@
+//! Detecting clips made of a single image.
+
+use crate::node::{GetFrameError, Node};
+
+impl<'core> Node<'core> {
+    /// Checks whether the frames at `sample_frames` are all identical.
+    ///
+    /// The frames are compared by hashing their format, resolution and pixel data; the frame
+    /// properties are ignored. Only the given frames are checked, so choosing the samples is a
+    /// trade-off between speed and accuracy: for example, the first, the middle and the last
+    /// frame of the clip.
+    ///
+    /// Returns `true` if fewer than two frames are given.
+    ///
+    /// # Panics
+    /// Panics if any frame number is greater than `i32::MAX`.
+    pub fn is_constant_image(
+        &self,
+        sample_frames: &[usize],
+    ) -> Result<bool, GetFrameError<'static>> {
+        let mut expected = None;
+
+        for &n in sample_frames {
+            let frame = self.get_frame(n)?;
+            let hash = frame.content_hash();
+
+            match expected {
+                None => expected = Some(hash),
+                Some(x) if x != hash => return Ok(false),
+                Some(_) => {}
+            }
+        }
+
+        Ok(true)
+    }
+}
@@ -1,8 +1,10 @@
 use std::borrow::Cow;
-use std::error::Error;
+use std::error;
 use std::ffi::CStr;
 use std::fmt;
 
+use thiserror::Error;
+
 /// A container for a `get_frame` error.
 #[derive(Debug)]
 pub struct GetFrameError<'a>(Cow<'a, CStr>);
@@ -14,7 +16,7 @@ impl<'a> fmt::Display for GetFrameError<'a> {
     }
 }
 
-impl<'a> Error for GetFrameError<'a> {
+impl<'a> error::Error for GetFrameError<'a> {
     #[inline]
     fn description(&self) -> &str {
         "VapourSynth error"
@@ -28,9 +30,32 @@ impl<'a> GetFrameError<'a> {
         GetFrameError(message)
     }
 
+    /// Converts this error into one that owns its error message.
+    #[inline]
+    pub fn into_owned(self) -> GetFrameError<'static> {
+        GetFrameError(Cow::Owned(self.0.into_owned()))
+    }
+
     /// Consumes this error, returning its underlying error message.
     #[inline]
     pub fn into_inner(self) -> Cow<'a, CStr> {
         self.0
     }
 }
+
+/// An error indicating that the clip has an unknown number of frames.
+#[derive(Error, Debug, Clone, Copy, Eq, PartialEq)]
+#[error("The clip has unknown length")]
+pub struct UnknownLength;
+
+/// An error waiting for a frame requested with `Node::request_frame()`.
+#[derive(Error, Debug)]
+pub enum WaitError {
+    /// The frame wasn't generated in time.
+    #[error("Timed out waiting for the frame")]
+    Timeout,
+
+    /// The frame generation failed.
+    #[error("Couldn't generate the frame: {0}")]
+    GetFrame(#[from] GetFrameError<'static>),
+}
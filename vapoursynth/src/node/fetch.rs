@@ -0,0 +1,327 @@
+//! Fetching many frames at once.
+
+use std::borrow::Cow;
+use std::collections::VecDeque;
+use std::ffi::CString;
+use std::fmt;
+use std::ops::Range;
+use std::sync::mpsc;
+use std::thread;
+
+use crate::api::API;
+use crate::core::CoreRef;
+use crate::frame::FrameRef;
+use crate::map::{OwnedMap, ReservedProps};
+use crate::node::{GetFrameError, Node, UnknownLength};
+use crate::video_info::Property;
+
+/// Options shared by the operations fetching many frames at once.
+pub struct FetchOptions<'a> {
+    /// The maximum number of frame requests in flight at any given moment.
+    ///
+    /// Values below 1 are treated as 1.
+    pub requests: usize,
+
+    /// A function called after each frame is done, with the number of done frames and the total
+    /// number of frames as arguments.
+    ///
+    /// It's called on the thread which started the operation.
+    pub progress: Option<Box<dyn FnMut(usize, usize) + 'a>>,
+}
+
+impl<'a> Default for FetchOptions<'a> {
+    /// Returns the default options.
+    ///
+    /// The number of requests defaults to the number of hardware threads, which is also the
+    /// default number of VapourSynth worker threads. Use `FetchOptions::for_core()` to match a
+    /// core with a custom thread count instead.
+    #[inline]
+    fn default() -> Self {
+        Self {
+            requests: thread::available_parallelism().map_or(1, |x| x.get()),
+            progress: None,
+        }
+    }
+}
+
+impl<'a> fmt::Debug for FetchOptions<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("FetchOptions")
+            .field("requests", &self.requests)
+            .field("progress", &self.progress.as_ref().map(|_| ".."))
+            .finish()
+    }
+}
+
+impl<'a> FetchOptions<'a> {
+    /// Returns the default options with the number of requests equal to the number of worker
+    /// threads of `core`, like vspipe does.
+    #[inline]
+    pub fn for_core(core: CoreRef) -> Self {
+        Self {
+            requests: core.info().num_threads,
+            ..Default::default()
+        }
+    }
+}
+
+/// Collects the results of asynchronous frame requests in the order of frame numbers.
+///
+/// `completions` yields `(n, result)` pairs in any order, typically the order in which the
+/// `get_frame_async()` callbacks fired. Exactly `range.len()` items are taken from it, so it's fine
+/// to pass an endless iterator such as `mpsc::Receiver::iter()`. The returned vector holds the
+/// result for frame `range.start + i` at index `i`.
+///
+/// # Panics
+/// Panics if `completions` ends early or yields a frame number which is outside of `range` or was
+/// already yielded.
+///
+/// # Example
+/// ```no_run
+/// # use std::sync::mpsc;
+/// # use vapoursynth::node::{collect_ordered, Node};
+/// # fn example(node: &Node) {
+/// let (tx, rx) = mpsc::channel();
+/// for n in 0..10 {
+///     let tx = tx.clone();
+///     node.get_frame_async(n, move |frame, n, _| {
+///         let _ = tx.send((n, frame.map(|_| ()).map_err(|e| e.into_owned())));
+///     });
+/// }
+///
+/// let results = collect_ordered(0..10, rx.iter());
+/// # }
+/// ```
+pub fn collect_ordered<T, I>(range: Range<usize>, completions: I) -> Vec<T>
+where
+    I: IntoIterator<Item = (usize, T)>,
+{
+    let start = range.start;
+    let total = range.len();
+    let mut results: Vec<Option<T>> = range.clone().map(|_| None).collect();
+
+    let mut completions = completions.into_iter();
+    for _ in 0..total {
+        let (n, result) = completions
+            .next()
+            .expect("the completions ended before all frames were done");
+        assert!(range.contains(&n), "frame {} is outside of the range", n);
+
+        let slot = &mut results[n - start];
+        assert!(slot.is_none(), "frame {} was completed twice", n);
+        *slot = Some(result);
+    }
+
+    results.into_iter().map(Option::unwrap).collect()
+}
+
+/// The result of a frame request.
+type FrameResult<'core> = Result<FrameRef<'core>, GetFrameError<'static>>;
+
+/// An iterator over frames in ascending order, keeping several requests in flight.
+pub(crate) struct OrderedFrames<'node, 'core> {
+    node: &'node Node<'core>,
+    to_request: Range<usize>,
+    next: usize,
+    end: usize,
+    requests: usize,
+    // Results for the frames starting from `next`, `None` for frames which aren't done yet.
+    done: VecDeque<Option<FrameResult<'core>>>,
+    tx: mpsc::Sender<(usize, FrameResult<'core>)>,
+    rx: mpsc::Receiver<(usize, FrameResult<'core>)>,
+}
+
+impl<'node, 'core> OrderedFrames<'node, 'core> {
+    /// Requests frames until there are `requests` frames in flight or buffered.
+    fn fill_window(&mut self) {
+        while self.to_request.start < self.next + self.requests {
+            let n = match self.to_request.next() {
+                Some(n) => n,
+                None => break,
+            };
+
+            let tx = self.tx.clone();
+            self.node.get_frame_async(n, move |frame, n, _| {
+                // The receiver might be gone if the iterator was dropped early.
+                let _ = tx.send((n, frame.map_err(GetFrameError::into_owned)));
+            });
+            self.done.push_back(None);
+        }
+    }
+}
+
+impl<'node, 'core> Iterator for OrderedFrames<'node, 'core> {
+    type Item = FrameResult<'core>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next == self.end {
+            return None;
+        }
+
+        self.fill_window();
+
+        while self.done[0].is_none() {
+            let (n, frame) = self.rx.recv().unwrap();
+            self.done[n - self.next] = Some(frame);
+        }
+
+        self.next += 1;
+        self.done.pop_front().unwrap()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.end - self.next;
+        (len, Some(len))
+    }
+}
+
+impl<'node, 'core> ExactSizeIterator for OrderedFrames<'node, 'core> {}
+
+impl<'core> Node<'core> {
+    /// Returns an iterator over every frame of the clip, in ascending order.
+    ///
+    /// Unlike `frames()`, up to `requests` frames are requested ahead of the one being returned,
+    /// so that they're generated in parallel. Values below 1 are treated as 1. vspipe uses the
+    /// number of worker threads of the core, `core.info().num_threads`, which is also what
+    /// `FetchOptions::for_core()` picks. Dropping the iterator early doesn't cancel the requests
+    /// in flight, but their results are discarded.
+    ///
+    /// Returns an error if the clip has unknown length.
+    #[inline]
+    pub fn frames_prefetched(
+        &self,
+        requests: usize,
+    ) -> Result<
+        impl ExactSizeIterator<Item = Result<FrameRef<'core>, GetFrameError<'static>>> + '_,
+        UnknownLength,
+    > {
+        Ok(self.ordered_frames(0..self.known_num_frames()?, requests))
+    }
+
+    /// Returns an iterator over the frames in `range` along with owned copies of their
+    /// properties, in ascending order.
+    ///
+    /// Up to `requests` frames are requested ahead of the one being returned, so that they're
+    /// generated in parallel. Values below 1 are treated as 1. Dropping the iterator early doesn't
+    /// cancel the requests in flight, but their results are discarded.
+    ///
+    /// # Panics
+    /// Panics if `range.end` is greater than `i32::MAX + 1`.
+    pub fn frames_with_props(
+        &self,
+        range: Range<usize>,
+        requests: usize,
+    ) -> impl Iterator<Item = Result<(FrameRef<'core>, OwnedMap<'core>), GetFrameError<'static>>> + '_
+    {
+        self.ordered_frames(range, requests).map(|frame| {
+            frame.map(|frame| {
+                // The node exists, so the API has been retrieved.
+                let mut props = OwnedMap::new(unsafe { API::get_cached() });
+                frame.props().copy_to(&mut props);
+                (frame, props)
+            })
+        })
+    }
+
+    /// Returns an iterator over the frames in `range`, in ascending order.
+    ///
+    /// See `frames_with_props()`.
+    pub(crate) fn ordered_frames(
+        &self,
+        range: Range<usize>,
+        requests: usize,
+    ) -> OrderedFrames<'_, 'core> {
+        let (tx, rx) = mpsc::channel();
+
+        OrderedFrames {
+            node: self,
+            next: range.start,
+            end: range.end.max(range.start),
+            to_request: range,
+            requests: requests.max(1),
+            done: VecDeque::new(),
+            tx,
+            rx,
+        }
+    }
+
+    /// Requests all frames in `range` and blocks until every one of them is done.
+    ///
+    /// The frames are requested with `get_frame_async()`, keeping up to `options.requests`
+    /// requests in flight. The results are returned in the order of frame numbers.
+    ///
+    /// # Panics
+    /// Panics if `range.end` is greater than `i32::MAX + 1`.
+    pub fn get_all_frames_async(
+        &self,
+        range: Range<usize>,
+        mut options: FetchOptions,
+    ) -> Vec<Result<FrameRef<'core>, GetFrameError<'static>>> {
+        let total = range.len();
+
+        let (tx, rx) = mpsc::channel();
+        let mut request = |n| {
+            let tx = tx.clone();
+            self.get_frame_async(n, move |frame, n, _| {
+                // The receiver outlives all callbacks.
+                let _ = tx.send((n, frame.map_err(GetFrameError::into_owned)));
+            });
+        };
+
+        let mut to_request = range.clone();
+        for n in to_request.by_ref().take(options.requests.max(1)) {
+            request(n);
+        }
+
+        let completions = rx.iter().zip(1..).map(|(completion, done)| {
+            if let Some(n) = to_request.next() {
+                request(n);
+            }
+
+            if let Some(progress) = options.progress.as_mut() {
+                progress(done, total);
+            }
+
+            completion
+        });
+
+        collect_ordered(range, completions)
+    }
+
+    /// Returns the durations of the frames in `range` as `(numerator, denominator)` pairs, in
+    /// seconds.
+    ///
+    /// The durations are read from the `_DurationNum` and `_DurationDen` frame properties. Frames
+    /// lacking them fall back to the duration derived from the clip framerate. If the framerate is
+    /// variable as well, an error is returned, as is the case when a frame couldn't be generated.
+    ///
+    /// # Panics
+    /// Panics if `range.end` is greater than `i32::MAX + 1`.
+    pub fn durations(
+        &self,
+        range: Range<usize>,
+    ) -> Result<Vec<(i64, i64)>, GetFrameError<'static>> {
+        let fallback = match self.info().framerate {
+            Property::Variable => None,
+            Property::Constant(framerate) => {
+                Some((framerate.denominator as i64, framerate.numerator as i64))
+            }
+        };
+
+        let start = range.start;
+        self.get_all_frames_async(range, Default::default())
+            .into_iter()
+            .enumerate()
+            .map(|(i, frame)| {
+                frame?.props().duration().or(fallback).ok_or_else(|| {
+                    let message = format!(
+                        "Frame {} has no duration and the clip framerate is variable",
+                        start + i
+                    );
+                    GetFrameError::new(Cow::Owned(CString::new(message).unwrap()))
+                })
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,83 @@
+//! Packed frame layouts.
+
+use crate::format::Format;
+use crate::node::Node;
+use crate::video_info::{Property, Resolution};
+
+/// Describes where a plane goes in a packed frame buffer.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct PlaneLayout {
+    /// Width of the plane, in pixels.
+    pub width: usize,
+
+    /// Height of the plane, in pixels.
+    pub height: usize,
+
+    /// Size of the plane without any row padding, in bytes.
+    pub size: usize,
+
+    /// Offset of the plane from the start of the buffer, in bytes.
+    pub offset: usize,
+}
+
+/// Describes a frame buffer with all planes stored one after another, without any padding.
+///
+/// This is the layout used by vspipe and most video file formats.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct FrameLayout {
+    /// Layouts of the individual planes, in order.
+    pub planes: Vec<PlaneLayout>,
+
+    /// Total size of the buffer, in bytes.
+    pub size: usize,
+}
+
+impl FrameLayout {
+    /// Computes the packed layout of frames with the given format and resolution.
+    pub fn new(format: Format, resolution: Resolution) -> Self {
+        let mut planes = Vec::with_capacity(format.plane_count());
+        let mut offset = 0;
+
+        for plane in 0..format.plane_count() {
+            let (width, height) = if plane == 0 {
+                (resolution.width, resolution.height)
+            } else {
+                (
+                    resolution.width >> format.sub_sampling_w(),
+                    resolution.height >> format.sub_sampling_h(),
+                )
+            };
+
+            let size = width * height * usize::from(format.bytes_per_sample());
+            planes.push(PlaneLayout {
+                width,
+                height,
+                size,
+                offset,
+            });
+            offset += size;
+        }
+
+        Self {
+            planes,
+            size: offset,
+        }
+    }
+}
+
+impl<'core> Node<'core> {
+    /// Returns the packed layout of this node's frames.
+    ///
+    /// Returns `None` if the format or the resolution is variable.
+    #[inline]
+    pub fn frame_layout(&self) -> Option<FrameLayout> {
+        let info = self.info();
+
+        match (info.format, info.resolution) {
+            (Property::Constant(format), Property::Constant(resolution)) => {
+                Some(FrameLayout::new(format, resolution))
+            }
+            _ => None,
+        }
+    }
+}
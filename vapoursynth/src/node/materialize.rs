@@ -0,0 +1,92 @@
+//! Rendering clips into memory.
+
+use std::ffi::CStr;
+use std::ops::Range;
+
+use anyhow::{ensure, Error};
+
+use crate::api::API;
+use crate::core::CoreRef;
+use crate::frame::FrameRef;
+use crate::node::{Flags, Node};
+use crate::plugins::{ffi, Filter, FrameContext};
+use crate::video_info::VideoInfo;
+
+/// A source filter serving frames from memory.
+struct Materialized<'core> {
+    info: VideoInfo<'core>,
+    frames: Vec<FrameRef<'core>>,
+}
+
+impl<'core> Filter<'core> for Materialized<'core> {
+    #[inline]
+    fn video_info(&self, _api: API, _core: CoreRef<'core>) -> Vec<VideoInfo<'core>> {
+        vec![self.info]
+    }
+
+    #[inline]
+    fn get_frame_initial(
+        &self,
+        _api: API,
+        _core: CoreRef<'core>,
+        _context: FrameContext,
+        n: usize,
+    ) -> Result<Option<FrameRef<'core>>, Error> {
+        Ok(Some(self.frames[n].clone()))
+    }
+
+    #[inline]
+    fn get_frame(
+        &self,
+        _api: API,
+        _core: CoreRef<'core>,
+        _context: FrameContext,
+        _n: usize,
+    ) -> Result<FrameRef<'core>, Error> {
+        unreachable!()
+    }
+}
+
+impl<'core> Node<'core> {
+    /// Renders the frames in `range` and returns a new node serving them from memory.
+    ///
+    /// The new node is detached from the filter graph of this node: requesting its frames never
+    /// calls into the upstream filters. Frame `n` of the new node is frame `range.start + n` of
+    /// this node.
+    ///
+    /// All rendered frames are kept alive for as long as the new node exists, so the memory cost
+    /// is the size of every frame in `range`. The frames count towards the framebuffer memory
+    /// usage of the core, but unlike cached frames, they can't be reclaimed when memory runs low.
+    ///
+    /// Returns an error if `range` is empty or any of the frames couldn't be generated.
+    ///
+    /// # Panics
+    /// Panics if `range.end` is greater than `i32::MAX + 1`.
+    pub fn materialize(
+        &self,
+        core: CoreRef<'core>,
+        range: Range<usize>,
+    ) -> Result<Node<'core>, Error> {
+        ensure!(!range.is_empty(), "The frame range is empty");
+
+        let mut info = self.info();
+
+        // useless for some API versions, required for others
+        #[allow(clippy::useless_conversion)]
+        {
+            info.num_frames = range.len().into();
+        }
+        info.flags = Flags::NO_CACHE;
+
+        let frames = self
+            .get_all_frames_async(range, Default::default())
+            .into_iter()
+            .collect::<Result<_, _>>()?;
+
+        let name = CStr::from_bytes_with_nul(b"Materialize\0").unwrap();
+        let filter = Box::new(Materialized { info, frames });
+
+        // The node was created through the API, so it's cached.
+        unsafe { ffi::create_filter_node(core, name, Flags::NO_CACHE, filter) }
+    }
+}
@@ -3,6 +3,7 @@
 use std::borrow::Cow;
 use std::ffi::{CStr, CString};
 use std::marker::PhantomData;
+use std::ops::Range;
 use std::os::raw::{c_char, c_void};
 use std::process;
 use std::ptr::NonNull;
@@ -10,13 +11,43 @@ use std::{mem, panic};
 use vapoursynth_sys as ffi;
 
 use crate::api::API;
+use crate::format::Format;
 use crate::frame::FrameRef;
 use crate::plugins::FrameContext;
 use crate::prelude::Property;
+use crate::tracking::{self, Resource};
 use crate::video_info::VideoInfo;
 
+mod benchmark;
+pub use self::benchmark::BenchResult;
+
+mod chunks;
+
+mod constant;
+
 mod errors;
-pub use self::errors::GetFrameError;
+pub use self::errors::{GetFrameError, UnknownLength, WaitError};
+
+mod fetch;
+pub use self::fetch::{collect_ordered, FetchOptions};
+
+mod layout;
+pub use self::layout::{FrameLayout, PlaneLayout};
+
+mod materialize;
+
+mod parallel;
+
+mod planar;
+
+mod props;
+
+mod request;
+pub use self::request::{FrameFuture, FrameRequest};
+
+mod temporal;
+
+mod y4m;
 
 bitflags! {
     /// Node flags.
@@ -45,6 +76,9 @@ impl From<ffi::VSNodeFlags> for Flags {
 }
 
 /// A reference to a node in the constructed filter graph.
+///
+/// Every `Node` owns exactly one VapourSynth node reference: cloning a `Node` clones the
+/// reference and dropping it frees the reference.
 #[derive(Debug)]
 pub struct Node<'core> {
     handle: NonNull<ffi::VSNodeRef>,
@@ -60,6 +94,7 @@ impl<'core> Drop for Node<'core> {
         unsafe {
             API::get_cached().free_node(self.handle.as_ptr());
         }
+        tracking::released(Resource::Node);
     }
 }
 
@@ -67,6 +102,7 @@ impl<'core> Clone for Node<'core> {
     #[inline]
     fn clone(&self) -> Self {
         let handle = unsafe { API::get_cached().clone_node(self.handle.as_ptr()) };
+        tracking::acquired(Resource::Node);
         Self {
             handle: unsafe { NonNull::new_unchecked(handle) },
             _owner: PhantomData,
@@ -74,6 +110,29 @@ impl<'core> Clone for Node<'core> {
     }
 }
 
+/// An iterator over the frames of a node, returned from `Node::frames()`.
+#[derive(Debug, Clone)]
+pub struct FrameIter<'node, 'core> {
+    node: &'node Node<'core>,
+    range: Range<usize>,
+}
+
+impl<'node, 'core> Iterator for FrameIter<'node, 'core> {
+    type Item = Result<FrameRef<'core>, GetFrameError<'static>>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.range.next().map(|n| self.node.get_frame(n))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.range.size_hint()
+    }
+}
+
+impl<'node, 'core> ExactSizeIterator for FrameIter<'node, 'core> {}
+
 impl<'core> Node<'core> {
     /// Wraps `handle` in a `Node`.
     ///
@@ -81,6 +140,7 @@ impl<'core> Node<'core> {
     /// The caller must ensure `handle` and the lifetime is valid and API is cached.
     #[inline]
     pub(crate) unsafe fn from_ptr(handle: *mut ffi::VSNodeRef) -> Self {
+        tracking::acquired(Resource::Node);
         Self {
             handle: NonNull::new_unchecked(handle),
             _owner: PhantomData,
@@ -104,14 +164,102 @@ impl<'core> Node<'core> {
         }
     }
 
+    /// Returns the format of this node's clip, or `None` if the format is variable.
+    #[inline]
+    pub fn format(&self) -> Option<Format<'core>> {
+        match self.info().format {
+            Property::Variable => None,
+            Property::Constant(format) => Some(format),
+        }
+    }
+
+    /// Returns the number of planes of this node's clip, or `None` if the format is variable.
+    #[inline]
+    pub fn plane_count(&self) -> Option<usize> {
+        self.format().map(|format| format.plane_count())
+    }
+
+    /// Returns `true` if this is an instance of the built-in Cache filter.
+    ///
+    /// This is a shortcut for `info().is_cache_node()`.
+    #[inline]
+    pub fn is_cache_node(&self) -> bool {
+        self.info().is_cache_node()
+    }
+
+    /// Returns `true` if this node's filter prefers linear frame access, like source filters do.
+    ///
+    /// This is a shortcut for `info().wants_linear_access()`.
+    #[cfg(feature = "gte-vapoursynth-api-33")]
+    #[inline]
+    pub fn wants_linear_access(&self) -> bool {
+        self.info().wants_linear_access()
+    }
+
     /// Generates a frame directly.
     ///
     /// The `'error` lifetime is unbounded because this function always returns owned data.
     ///
+    /// Error messages longer than 32 KiB are truncated, use `get_frame_with_error_capacity()` to
+    /// receive longer messages.
+    ///
     /// # Panics
     /// Panics is `n` is greater than `i32::MAX`.
+    #[inline]
     pub fn get_frame<'error>(&self, n: usize) -> Result<FrameRef<'core>, GetFrameError<'error>> {
+        // Kinda arbitrary. Same value as used in vsvfw.
+        const ERROR_BUF_CAPACITY: usize = 32 * 1024;
+
+        self.get_frame_with_error_capacity(n, ERROR_BUF_CAPACITY)
+    }
+
+    /// Returns an iterator generating every frame of the clip in order with `get_frame()`.
+    ///
+    /// The frames are generated one at a time, when the iterator is advanced. See
+    /// `frames_with_props()` for generating several frames in parallel.
+    ///
+    /// Returns an error if the clip has unknown length. This can't happen starting from
+    /// VapourSynth API 3.2, where the frame count is always known.
+    #[inline]
+    pub fn frames(&self) -> Result<FrameIter<'_, 'core>, UnknownLength> {
+        Ok(FrameIter {
+            node: self,
+            range: 0..self.known_num_frames()?,
+        })
+    }
+
+    /// Returns the number of frames, or an error if the clip has unknown length.
+    #[inline]
+    pub(crate) fn known_num_frames(&self) -> Result<usize, UnknownLength> {
+        #[cfg(feature = "gte-vapoursynth-api-32")]
+        {
+            Ok(self.info().num_frames)
+        }
+
+        #[cfg(not(feature = "gte-vapoursynth-api-32"))]
+        match self.info().num_frames {
+            Property::Constant(x) => Ok(x),
+            Property::Variable => Err(UnknownLength),
+        }
+    }
+
+    /// Generates a frame directly, receiving error messages into a buffer of `capacity` bytes.
+    ///
+    /// The buffer is allocated for every call, and error messages which don't fit into it,
+    /// including the terminating nul byte, are truncated.
+    ///
+    /// The `'error` lifetime is unbounded because this function always returns owned data.
+    ///
+    /// # Panics
+    /// Panics is `n` is greater than `i32::MAX`, if `capacity` is zero or if it's greater than
+    /// `i32::MAX`.
+    pub fn get_frame_with_error_capacity<'error>(
+        &self,
+        n: usize,
+        capacity: usize,
+    ) -> Result<FrameRef<'core>, GetFrameError<'error>> {
         assert!(n <= i32::MAX as usize);
+        assert!(capacity > 0);
 
         let vi = &self.info();
 
@@ -130,10 +278,7 @@ impl<'core> Node<'core> {
             return Err(GetFrameError::new(Cow::Owned(err_cstring)));
         }
 
-        // Kinda arbitrary. Same value as used in vsvfw.
-        const ERROR_BUF_CAPACITY: usize = 32 * 1024;
-
-        let mut err_buf = vec![0; ERROR_BUF_CAPACITY];
+        let mut err_buf = vec![0; capacity];
         let mut err_buf = err_buf.into_boxed_slice();
 
         let handle =
@@ -151,7 +296,8 @@ impl<'core> Node<'core> {
     /// Requests the generation of a frame. When the frame is ready, a user-provided function is
     /// called.
     ///
-    /// If multiple frames were requested, they can be returned in any order.
+    /// If multiple frames were requested, they can be returned in any order. Use
+    /// `collect_ordered()` to put the results back in the order of frame numbers.
     ///
     /// The callback arguments are:
     ///
@@ -159,6 +305,17 @@ impl<'core> Node<'core> {
     /// - the frame number (equal to `n`),
     /// - the node that generated the frame (the same as `self`).
     ///
+    /// The callback is called exactly once, usually on one of the VapourSynth worker threads,
+    /// though it may also be the thread calling this function. Callbacks of different requests
+    /// can run at the same time on different threads, so any state they share must be
+    /// synchronized. The `Send` bound makes the compiler enforce this: shared state has to be
+    /// captured through something like `Arc<Mutex<_>>` or an `mpsc::Sender`, the latter being the
+    /// simplest way of handing the results over to another thread.
+    ///
+    /// The callback blocks the thread it runs on, so it should return quickly. In particular,
+    /// waiting for other frames from within the callback, for example with `get_frame()`, can
+    /// deadlock if all worker threads end up waiting.
+    ///
     /// If the callback panics, the process is aborted.
     ///
     /// # Panics
@@ -208,6 +365,9 @@ impl<'core> Node<'core> {
             let user_data = Box::from_raw(user_data as *mut CallbackData<'static>);
 
             let closure = panic::AssertUnwindSafe(move || {
+                // The callback owns both the frame and the node reference: they are wrapped here
+                // and freed when the user callback drops them, so nothing leaks even if the
+                // callback ignores its arguments.
                 let frame = if frame.is_null() {
                     debug_assert!(!error_msg.is_null());
                     let error_msg = Cow::Borrowed(CStr::from_ptr(error_msg));
@@ -250,6 +410,7 @@ impl<'core> Node<'core> {
 
         // It'll be dropped by the callback.
         mem::forget(new_node);
+        tracking::released(Resource::Node);
     }
 
     /// Requests a frame from a node and returns immediately.
@@ -0,0 +1,108 @@
+//! Processing frames in parallel.
+
+use std::ops::Range;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{mpsc, Mutex};
+use std::thread;
+
+use anyhow::{Context, Error};
+
+use crate::frame::{Frame, FrameRef};
+use crate::node::{collect_ordered, GetFrameError, Node};
+
+impl<'core> Node<'core> {
+    /// Calls `f` on every frame in `range` in parallel and returns the results in the order of
+    /// frame numbers.
+    ///
+    /// `f` receives the frame number and the frame. It runs on `requests` threads spawned for the
+    /// duration of this call rather than on the VapourSynth worker threads, so it's fine for it
+    /// to take a while. Values of `requests` below 1 are treated as 1.
+    ///
+    /// At most `requests` frames are requested or being processed at any given moment, which
+    /// bounds the memory use regardless of the length of `range`.
+    ///
+    /// Returns the first error by frame number if any frame couldn't be generated or `f` failed
+    /// on it. The remaining frames are still processed.
+    ///
+    /// # Panics
+    /// Panics if `range.end` is greater than `i32::MAX + 1` or if `f` panics.
+    pub fn map_frames<T, F>(
+        &self,
+        range: Range<usize>,
+        requests: usize,
+        f: F,
+    ) -> Result<Vec<T>, Error>
+    where
+        T: Send,
+        F: Fn(usize, &Frame<'core>) -> Result<T, Error> + Sync,
+    {
+        let requests = requests.max(1);
+        let f = &f;
+
+        let (frame_tx, frame_rx) =
+            mpsc::channel::<(usize, Result<FrameRef<'core>, GetFrameError<'static>>)>();
+        let (result_tx, result_rx) = mpsc::channel();
+        let frame_rx = Mutex::new(frame_rx);
+        let frame_rx = &frame_rx;
+
+        let results = thread::scope(|scope| {
+            for _ in 0..requests.min(range.len()) {
+                let result_tx = result_tx.clone();
+
+                scope.spawn(move || loop {
+                    // The lock is released before processing the frame.
+                    let (n, frame) = match frame_rx.lock().unwrap().recv() {
+                        Ok(x) => x,
+                        // All requests are done.
+                        Err(_) => break,
+                    };
+
+                    // Panics are propagated once all frames are done, otherwise the missing
+                    // result would make the caller wait forever.
+                    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                        frame
+                            .map_err(Error::from)
+                            .and_then(|frame| f(n, &frame))
+                            .with_context(|| format!("Couldn't process frame {}", n))
+                    }));
+
+                    // The receiver outlives the workers.
+                    let _ = result_tx.send((n, result));
+                });
+            }
+
+            // The workers exit once all senders are dropped.
+            let frame_tx = frame_tx;
+            drop(result_tx);
+
+            let request = |n| {
+                let frame_tx = frame_tx.clone();
+                self.get_frame_async(n, move |frame, n, _| {
+                    // The receiver outlives all callbacks.
+                    let _ = frame_tx.send((n, frame.map_err(GetFrameError::into_owned)));
+                });
+            };
+
+            let mut to_request = range.clone();
+            for n in to_request.by_ref().take(requests) {
+                request(n);
+            }
+
+            // Request the next frame once a frame is fully processed.
+            let completions = result_rx.iter().inspect(|_| {
+                if let Some(n) = to_request.next() {
+                    request(n);
+                }
+            });
+
+            let results = collect_ordered(range, completions);
+            drop(frame_tx);
+            results
+        });
+
+        results
+            .into_iter()
+            .map(|result| result.unwrap_or_else(|payload| panic::resume_unwind(payload)))
+            .collect()
+    }
+}
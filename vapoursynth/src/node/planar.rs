@@ -0,0 +1,55 @@
+//! Converting packed compat clips to planar formats.
+
+use anyhow::{anyhow, bail, Error};
+
+use crate::api::API;
+use crate::core::CoreRef;
+use crate::format::PresetFormat;
+use crate::map::OwnedMap;
+use crate::node::Node;
+use crate::video_info::Property;
+
+impl<'core> Node<'core> {
+    /// Returns this clip converted to a native planar format.
+    ///
+    /// The packed `CompatBGR32` and `CompatYUY2` formats exist for AviSynth compatibility and
+    /// can't be processed by native filters. They are converted to `RGB24` and `YUV422P8`
+    /// respectively, which hold the same samples. Clips which are already planar are returned as
+    /// is.
+    ///
+    /// The conversion uses `resize.Point`, which doesn't touch the sample values. Returns an error
+    /// if the clip has varying format, the resize plugin isn't available or VapourSynth rejects
+    /// the conversion.
+    pub fn to_planar(&self, core: CoreRef<'core>) -> Result<Node<'core>, Error> {
+        let format = match self.info().format {
+            Property::Constant(x) => x,
+            Property::Variable => bail!("Cannot convert clips with varying format"),
+        };
+
+        let target = if format.id() == PresetFormat::CompatBGR32.into() {
+            PresetFormat::RGB24
+        } else if format.id() == PresetFormat::CompatYUY2.into() {
+            PresetFormat::YUV422P8
+        } else {
+            return Ok(self.clone());
+        };
+
+        let resize = core
+            .get_plugin_by_id("com.vapoursynth.resize")?
+            .ok_or_else(|| anyhow!("The resize plugin isn't loaded"))?;
+
+        // The node exists, so the API has been retrieved.
+        let api = unsafe { API::get_cached() };
+
+        let mut args = OwnedMap::new(api);
+        args.set_node("clip", self)?;
+        args.set_int("format", target as i64)?;
+
+        let rv = resize.invoke("Point", &args)?;
+        if let Some(error) = rv.error() {
+            bail!("{}", error);
+        }
+
+        Ok(rv.get_node("clip")?)
+    }
+}
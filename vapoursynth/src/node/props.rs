@@ -0,0 +1,134 @@
+//! Overriding and modifying frame properties of whole clips.
+
+use std::ffi::CStr;
+
+use anyhow::{anyhow, bail, Error};
+
+use crate::api::API;
+use crate::core::CoreRef;
+use crate::frame::{FrameRef, FrameRefMut};
+use crate::map::{Map, OwnedMap};
+use crate::node::{Flags, Node};
+use crate::plugins::{ffi, Filter, FrameContext, RequestPattern};
+use crate::video_info::VideoInfo;
+
+/// A filter passing frames through with their properties modified by a function.
+struct PropMap<'core, F> {
+    source: Node<'core>,
+    f: F,
+}
+
+impl<'core, F> Filter<'core> for PropMap<'core, F>
+where
+    F: Fn(usize, &mut Map<'core>) + Send + Sync,
+{
+    #[inline]
+    fn video_info(&self, _api: API, _core: CoreRef<'core>) -> Vec<VideoInfo<'core>> {
+        vec![self.source.info()]
+    }
+
+    #[inline]
+    fn get_frame_initial(
+        &self,
+        _api: API,
+        _core: CoreRef<'core>,
+        context: FrameContext,
+        n: usize,
+    ) -> Result<Option<FrameRef<'core>>, Error> {
+        self.source.request_frame_filter(context, n);
+        Ok(None)
+    }
+
+    fn get_frame(
+        &self,
+        _api: API,
+        core: CoreRef<'core>,
+        context: FrameContext,
+        n: usize,
+    ) -> Result<FrameRef<'core>, Error> {
+        let frame = self
+            .source
+            .get_frame_filter(context, n)
+            .ok_or_else(|| anyhow!("Couldn't get the source frame"))?;
+
+        // The plane data is copy-on-write and only the properties are written to, so the pixels
+        // are never copied.
+        let mut frame = FrameRefMut::copy_of(core, &frame);
+        (self.f)(n, &mut frame.props_mut());
+        Ok(frame.into())
+    }
+
+    #[inline]
+    fn dependencies(&self) -> Vec<(Node<'core>, RequestPattern)> {
+        vec![(self.source.clone(), RequestPattern::StrictSpatial)]
+    }
+}
+
+impl<'core> Node<'core> {
+    /// Returns a new node whose frames carry the given integer properties.
+    ///
+    /// The pixel data is left untouched, so this is cheap. It's useful for fixing mistagged
+    /// sources, for example setting `_ColorRange` or `_Matrix`. Existing properties with the same
+    /// names are overwritten.
+    ///
+    /// This is implemented by chaining `std.SetFrameProp` calls, one per property. Returns an
+    /// error if the std plugin or the function isn't available or if VapourSynth rejects the
+    /// arguments.
+    pub fn set_frame_props(
+        &self,
+        core: CoreRef<'core>,
+        props: &[(&str, i64)],
+    ) -> Result<Node<'core>, Error> {
+        let std = core
+            .get_plugin_by_id("com.vapoursynth.std")?
+            .ok_or_else(|| anyhow!("The std plugin isn't loaded"))?;
+
+        // The node exists, so the API has been retrieved.
+        let api = unsafe { API::get_cached() };
+
+        let mut node = self.clone();
+        for &(prop, value) in props {
+            let mut args = OwnedMap::new(api);
+            args.set_node("clip", &node)?;
+            args.set_data("prop", prop.as_bytes())?;
+            args.set_int("intval", value)?;
+
+            let rv = std.invoke("SetFrameProp", &args)?;
+            if let Some(error) = rv.error() {
+                bail!("{}", error);
+            }
+
+            node = rv.get_node("clip")?;
+        }
+
+        Ok(node)
+    }
+
+    /// Returns a new node whose frames have their properties modified by `f`.
+    ///
+    /// For every requested frame number `n`, `f` receives `n` and the properties of frame `n`,
+    /// which it can change in any way. The pixel data is passed through without being copied, so
+    /// this is a cheap way of tweaking metadata, for example fixing `_ChromaLocation`, without
+    /// writing a whole filter.
+    ///
+    /// `f` is called from the VapourSynth worker threads, possibly for several frames at once.
+    pub fn map_props<F>(&self, core: CoreRef<'core>, f: F) -> Result<Node<'core>, Error>
+    where
+        F: Fn(usize, &mut Map<'core>) + Send + Sync + 'core,
+    {
+        let filter = Box::new(PropMap {
+            source: self.clone(),
+            f,
+        });
+
+        // The node exists, so the API has been retrieved.
+        unsafe {
+            ffi::create_filter_node(
+                core,
+                CStr::from_bytes_with_nul(b"PropMap\0").unwrap(),
+                Flags::NO_CACHE,
+                filter,
+            )
+        }
+    }
+}
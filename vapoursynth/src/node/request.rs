@@ -0,0 +1,143 @@
+//! Handles for asynchronous frame requests.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::time::Duration;
+
+use crate::frame::FrameRef;
+use crate::node::{GetFrameError, Node, WaitError};
+
+/// A handle to a frame requested with `Node::request_frame()`.
+///
+/// Dropping the handle doesn't cancel the request: VapourSynth has no way of doing that. The
+/// frame is still generated, but it's released right away instead of being kept around.
+#[derive(Debug)]
+pub struct FrameRequest<'core> {
+    n: usize,
+    rx: Receiver<Result<FrameRef<'core>, GetFrameError<'static>>>,
+}
+
+impl<'core> FrameRequest<'core> {
+    /// Returns the number of the requested frame.
+    #[inline]
+    pub fn frame_number(&self) -> usize {
+        self.n
+    }
+
+    /// Blocks until the frame is generated.
+    #[inline]
+    pub fn wait(self) -> Result<FrameRef<'core>, GetFrameError<'static>> {
+        // The callback always sends the result before dropping the sender.
+        self.rx.recv().unwrap()
+    }
+
+    /// Blocks until the frame is generated or `timeout` passes, whichever comes first.
+    ///
+    /// On timeout the handle is consumed and the frame is released as soon as it's generated,
+    /// see the type-level docs. Request the frame again to retry.
+    #[inline]
+    pub fn wait_timeout(self, timeout: Duration) -> Result<FrameRef<'core>, WaitError> {
+        match self.rx.recv_timeout(timeout) {
+            Ok(result) => result.map_err(WaitError::from),
+            Err(RecvTimeoutError::Timeout) => Err(WaitError::Timeout),
+            Err(RecvTimeoutError::Disconnected) => unreachable!(),
+        }
+    }
+}
+
+/// The state shared between a `FrameFuture` and its `get_frame_async()` callback.
+#[derive(Debug)]
+struct Shared<'core> {
+    result: Option<Result<FrameRef<'core>, GetFrameError<'static>>>,
+    waker: Option<Waker>,
+}
+
+/// A future resolving to a frame requested with `Node::get_frame_future()`.
+///
+/// Dropping the future doesn't cancel the request: VapourSynth has no way of doing that. The
+/// frame is still generated, but it's released right away instead of being kept around.
+#[derive(Debug)]
+pub struct FrameFuture<'core> {
+    shared: Arc<Mutex<Shared<'core>>>,
+    done: bool,
+}
+
+impl<'core> Future for FrameFuture<'core> {
+    type Output = Result<FrameRef<'core>, GetFrameError<'static>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        assert!(!this.done, "the future was polled after it resolved");
+
+        let mut shared = this.shared.lock().unwrap();
+        match shared.result.take() {
+            Some(result) => {
+                this.done = true;
+                Poll::Ready(result)
+            }
+            None => {
+                shared.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl<'core> Node<'core> {
+    /// Requests the generation of a frame, returning a handle which can be used to wait for it.
+    ///
+    /// This is a blocking-friendly alternative to `get_frame_async()`.
+    ///
+    /// # Panics
+    /// Panics is `n` is greater than `i32::MAX`.
+    pub fn request_frame(&self, n: usize) -> FrameRequest<'core> {
+        let (tx, rx) = mpsc::sync_channel(1);
+
+        self.get_frame_async(n, move |frame, _, _| {
+            // The receiver is gone if the handle was dropped, in which case the frame is dropped
+            // right here.
+            let _ = tx.send(frame.map_err(GetFrameError::into_owned));
+        });
+
+        FrameRequest { n, rx }
+    }
+
+    /// Requests the generation of a frame, returning a future resolving to it.
+    ///
+    /// This is an `async`-friendly alternative to `get_frame_async()`, which doesn't depend on
+    /// any particular runtime. The frame is requested right away rather than on the first poll.
+    /// The waker is called from one of the VapourSynth worker threads.
+    ///
+    /// The future must be polled again after being woken up to resolve. Polling it again after it
+    /// resolved panics.
+    ///
+    /// # Panics
+    /// Panics is `n` is greater than `i32::MAX`.
+    pub fn get_frame_future(&self, n: usize) -> FrameFuture<'core> {
+        let shared = Arc::new(Mutex::new(Shared {
+            result: None,
+            waker: None,
+        }));
+
+        let callback_shared = shared.clone();
+        self.get_frame_async(n, move |frame, _, _| {
+            let waker = {
+                let mut shared = callback_shared.lock().unwrap();
+                shared.result = Some(frame.map_err(GetFrameError::into_owned));
+                shared.waker.take()
+            };
+
+            if let Some(waker) = waker {
+                waker.wake();
+            }
+        });
+
+        FrameFuture {
+            shared,
+            done: false,
+        }
+    }
+}
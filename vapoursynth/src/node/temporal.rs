@@ -0,0 +1,104 @@
+//! Per-pixel statistics across frames.
+
+use anyhow::{bail, ensure, Error};
+#[cfg(feature = "f16-pixel-type")]
+use half::f16;
+
+use crate::component::Sample;
+use crate::core::CoreRef;
+use crate::format::SampleType;
+use crate::frame::{FrameRef, FrameRefMut};
+use crate::node::Node;
+#[cfg(not(feature = "gte-vapoursynth-api-32"))]
+use crate::video_info::Property;
+
+/// Writes the per-pixel mean of `plane` of `frames` into `dest`.
+fn mean_plane<T: Sample>(dest: &mut FrameRefMut, frames: &[FrameRef], plane: usize) {
+    let count = frames.len() as f64;
+    let mut sums = vec![0f64; dest.width(plane)];
+
+    for row in 0..dest.height(plane) {
+        sums.iter_mut().for_each(|x| *x = 0.);
+
+        for frame in frames {
+            for (sum, &x) in sums.iter_mut().zip(frame.plane_row::<T>(plane, row)) {
+                *sum += x.to_f64();
+            }
+        }
+
+        for (x, sum) in dest.plane_row_mut::<T>(plane, row).iter_mut().zip(&sums) {
+            *x = T::from_f64(sum / count);
+        }
+    }
+}
+
+impl<'core> Node<'core> {
+    /// Computes the per-pixel mean of `plane` over the frames `center - radius..=center + radius`.
+    ///
+    /// The window is clamped to the bounds of the clip, so near the start and the end of the clip
+    /// fewer frames are averaged. Integer samples are rounded to the nearest value.
+    ///
+    /// The returned frame is a copy of frame `center`, including its properties, with `plane`
+    /// replaced by the mean. The other planes are left as is.
+    ///
+    /// Returns an error if `center` is out of bounds, the clip has unknown length, `plane` is out
+    /// of bounds, any of the frames couldn't be generated or doesn't match the format and the
+    /// resolution of frame `center`.
+    ///
+    /// # Panics
+    /// Panics if the window end is greater than `i32::MAX + 1`.
+    pub fn temporal_mean(
+        &self,
+        core: CoreRef<'core>,
+        center: usize,
+        radius: usize,
+        plane: usize,
+    ) -> Result<FrameRefMut<'core>, Error> {
+        #[cfg(feature = "gte-vapoursynth-api-32")]
+        let num_frames = self.info().num_frames;
+        #[cfg(not(feature = "gte-vapoursynth-api-32"))]
+        let num_frames = match self.info().num_frames {
+            Property::Constant(x) => x,
+            Property::Variable => bail!("The clip has unknown length"),
+        };
+
+        ensure!(center < num_frames, "The center frame is out of bounds");
+
+        let start = center.saturating_sub(radius);
+        let end = center
+            .saturating_add(radius)
+            .saturating_add(1)
+            .min(num_frames);
+
+        let frames = self
+            .get_all_frames_async(start..end, Default::default())
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()?;
+        let center_frame = &frames[center - start];
+
+        let format = center_frame.format();
+        ensure!(plane < format.plane_count(), "The plane is out of bounds");
+
+        let resolution = center_frame.resolution(plane);
+        for frame in &frames {
+            ensure!(
+                frame.format() == format && frame.resolution(plane) == resolution,
+                "The frames in the window have different formats or resolutions"
+            );
+        }
+
+        let mut dest = FrameRefMut::copy_of(core, center_frame);
+
+        match (format.sample_type(), format.bytes_per_sample()) {
+            (SampleType::Integer, 1) => mean_plane::<u8>(&mut dest, &frames, plane),
+            (SampleType::Integer, 2) => mean_plane::<u16>(&mut dest, &frames, plane),
+            (SampleType::Integer, 4) => mean_plane::<u32>(&mut dest, &frames, plane),
+            #[cfg(feature = "f16-pixel-type")]
+            (SampleType::Float, 2) => mean_plane::<f16>(&mut dest, &frames, plane),
+            (SampleType::Float, 4) => mean_plane::<f32>(&mut dest, &frames, plane),
+            _ => bail!("Unsupported sample type"),
+        }
+
+        Ok(dest)
+    }
+}
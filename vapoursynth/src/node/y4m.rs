@@ -0,0 +1,122 @@
+//! Writing clips in the YUV4MPEG2 format.
+
+use std::io::Write;
+use std::ops::Range;
+
+use anyhow::{bail, Context, Error};
+
+use crate::format::{ColorFamily, Format, SampleType};
+use crate::frame::Frame;
+use crate::node::Node;
+use crate::video_info::{Property, VideoInfo};
+
+/// Returns the y4m colorspace identifier of the format, e.g. `420p10`.
+fn colorspace(format: Format) -> Result<String, Error> {
+    let subsampling = match format.color_family() {
+        ColorFamily::Gray => {
+            return Ok(if format.bits_per_sample() > 8 {
+                format!("mono{}", format.bits_per_sample())
+            } else {
+                "mono".to_owned()
+            });
+        }
+        ColorFamily::YUV => match (format.sub_sampling_w(), format.sub_sampling_h()) {
+            (1, 1) => "420",
+            (1, 0) => "422",
+            (0, 0) => "444",
+            (2, 2) => "410",
+            (2, 0) => "411",
+            (0, 1) => "440",
+            _ => bail!("No y4m identifier exists for the current format"),
+        },
+        _ => bail!("No y4m identifier exists for the current format"),
+    };
+
+    Ok(match (format.sample_type(), format.bits_per_sample()) {
+        (SampleType::Integer, 8) => subsampling.to_owned(),
+        (SampleType::Integer, bits) => format!("{}p{}", subsampling, bits),
+        (SampleType::Float, 16) => format!("{}ph", subsampling),
+        (SampleType::Float, 32) => format!("{}ps", subsampling),
+        _ => bail!("No y4m identifier exists for the current format"),
+    })
+}
+
+/// Writes the y4m stream header for a clip of `num_frames` frames.
+fn write_header(writer: &mut dyn Write, info: &VideoInfo, num_frames: usize) -> Result<(), Error> {
+    let format = match info.format {
+        Property::Constant(x) => x,
+        Property::Variable => bail!("Cannot output clips with varying format"),
+    };
+    let resolution = match info.resolution {
+        Property::Constant(x) => x,
+        Property::Variable => bail!("Cannot output clips with varying dimensions"),
+    };
+    let framerate = match info.framerate {
+        Property::Constant(x) => x,
+        Property::Variable => bail!("Cannot output clips with varying framerate"),
+    };
+
+    writeln!(
+        writer,
+        "YUV4MPEG2 C{} W{} H{} F{}:{} Ip A0:0 XLENGTH={}",
+        colorspace(format)?,
+        resolution.width,
+        resolution.height,
+        framerate.numerator,
+        framerate.denominator,
+        num_frames,
+    )?;
+
+    Ok(())
+}
+
+/// Writes a y4m frame, including the frame header.
+fn write_frame(writer: &mut dyn Write, frame: &Frame) -> Result<(), Error> {
+    writeln!(writer, "FRAME")?;
+
+    for plane in 0..frame.format().plane_count() {
+        if let Ok(data) = frame.data(plane) {
+            writer.write_all(data)?;
+        } else {
+            for row in 0..frame.height(plane) {
+                writer.write_all(frame.data_row(plane, row))?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+impl<'core> Node<'core> {
+    /// Writes the frames in `range` to `writer` in the YUV4MPEG2 format.
+    ///
+    /// The stream header is written first, followed by every frame in order. Up to `requests`
+    /// frames are requested ahead of the one being written, see `frames_with_props()`.
+    ///
+    /// Gray and YUV formats are supported. Bit depths above 8 and floating point formats use the
+    /// same colorspace suffixes as vspipe: `p10`, `p16` and so on for integer samples, `ph` and
+    /// `ps` for half and single precision floating point samples, and `mono10`, `mono16` and so on
+    /// for gray formats. y4m has no RGB colorspaces, so RGB clips result in an error.
+    ///
+    /// Returns an error if the clip has varying format, resolution or framerate, if any frame
+    /// couldn't be generated or if writing fails. The frames written up to that point are left in
+    /// `writer`.
+    ///
+    /// # Panics
+    /// Panics if `range.end` is greater than `i32::MAX + 1`.
+    pub fn write_y4m(
+        &self,
+        writer: &mut dyn Write,
+        range: Range<usize>,
+        requests: usize,
+    ) -> Result<(), Error> {
+        write_header(writer, &self.info(), range.len()).context("Couldn't write the y4m header")?;
+
+        for (n, frame) in range.clone().zip(self.ordered_frames(range, requests)) {
+            let frame = frame.with_context(|| format!("Couldn't get frame {}", n))?;
+            write_frame(writer, &frame).with_context(|| format!("Couldn't write frame {}", n))?;
+        }
+
+        Ok(())
+    }
+}
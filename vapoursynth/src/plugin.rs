@@ -6,9 +6,31 @@ use std::ops::Deref;
 use std::ptr::NonNull;
 use vapoursynth_sys as ffi;
 
+use thiserror::Error;
+
 use crate::api::API;
-use crate::map::{Map, OwnedMap};
-use crate::plugins::{self, FilterFunction};
+use crate::map::{self, Map, OwnedMap};
+use crate::plugins::{self, FilterFunction, InvalidArgsError};
+
+/// The error type for `Plugin::register_function()`.
+#[derive(Error, Debug)]
+pub enum RegisterFunctionError {
+    #[error("Couldn't convert to a CString")]
+    CStringConversion(#[from] NulError),
+    #[error("The args string is invalid")]
+    InvalidArgs(#[from] InvalidArgsError),
+}
+
+/// The error type for `Plugin::invoke_checked()`.
+#[derive(Error, Debug)]
+pub enum InvokeError {
+    #[error("Couldn't convert to a CString")]
+    CStringConversion(#[from] NulError),
+    #[error("Couldn't build the arguments")]
+    Args(#[from] map::Error),
+    #[error("The filter returned an error: {}", _0)]
+    Filter(String),
+}
 
 /// A VapourSynth plugin.
 #[derive(Debug, Clone, Copy)]
@@ -85,12 +107,37 @@ impl<'core> Plugin<'core> {
         })
     }
 
+    /// Invokes a filter and checks the result for an error.
+    ///
+    /// This is `invoke()` followed by `Map::error()`: if the filter sets an error, it's returned
+    /// as `InvokeError::Filter`. `InvokeError` converts from `map::Error`, so together with the
+    /// `args!` macro a filter call takes two `?`s and a single error type.
+    #[inline]
+    pub fn invoke_checked(
+        &self,
+        name: &str,
+        args: &Map<'core>,
+    ) -> Result<OwnedMap<'core>, InvokeError> {
+        let rv = self.invoke(name, args)?;
+
+        if let Some(error) = rv.error() {
+            return Err(InvokeError::Filter(error.into_owned()));
+        }
+
+        Ok(rv)
+    }
+
     /// Registers a filter function to be exported by a non-readonly plugin.
+    ///
+    /// The args string is validated with `plugins::normalize_args()` first.
     #[inline]
-    pub fn register_function<F: FilterFunction>(&self, filter_function: F) -> Result<(), NulError> {
+    pub fn register_function<F: FilterFunction>(
+        &self,
+        filter_function: F,
+    ) -> Result<(), RegisterFunctionError> {
         // TODO: this is almost the same code as plugins::ffi::call_register_function().
         let name_cstring = CString::new(filter_function.name())?;
-        let args_cstring = CString::new(filter_function.args())?;
+        let args_cstring = CString::new(plugins::normalize_args(filter_function.args())?)?;
 
         let data = Box::new(plugins::ffi::FilterFunctionData::<F> {
             filter_function,
@@ -0,0 +1,277 @@
+//! Filter argument string validation.
+
+use std::collections::HashSet;
+
+use crate::map::{type_name, ValueType};
+use crate::plugins::InvalidArgsError;
+
+/// A filter function parameter, as declared in the argument string.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct ParamInfo {
+    /// The parameter name.
+    pub name: String,
+
+    /// The type of the parameter values.
+    pub value_type: ValueType,
+
+    /// Whether the parameter is an array.
+    pub array: bool,
+
+    /// Whether the parameter is optional (`opt`).
+    pub optional: bool,
+
+    /// Whether the array is allowed to be empty (`empty`).
+    pub empty: bool,
+}
+
+/// A builder for filter argument strings.
+///
+/// This is useful for filter functions whose parameters are only known at runtime. Parameters are
+/// added in order with `arg()` or `param()`, and `array()`, `optional()` and `empty()` modify the
+/// most recently added one:
+///
+/// ```
+/// # use vapoursynth::map::ValueType;
+/// # use vapoursynth::plugins::ArgSpec;
+/// let args = ArgSpec::new()
+///     .arg("clip", ValueType::Node)
+///     .arg("planes", ValueType::Int)
+///     .array()
+///     .optional()
+///     .build()
+///     .unwrap();
+/// assert_eq!(args, "clip:clip;planes:int[]:opt;");
+/// ```
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash)]
+pub struct ArgSpec {
+    params: Vec<ParamInfo>,
+}
+
+impl ArgSpec {
+    /// Creates an empty argument specification.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a required non-array parameter.
+    #[inline]
+    pub fn arg(self, name: &str, value_type: ValueType) -> Self {
+        self.param(ParamInfo {
+            name: name.to_owned(),
+            value_type,
+            array: false,
+            optional: false,
+            empty: false,
+        })
+    }
+
+    /// Adds a parameter.
+    #[inline]
+    pub fn param(mut self, param: ParamInfo) -> Self {
+        self.params.push(param);
+        self
+    }
+
+    /// Makes the last added parameter an array.
+    ///
+    /// # Panics
+    /// Panics if no parameters were added.
+    #[inline]
+    pub fn array(mut self) -> Self {
+        self.last_param().array = true;
+        self
+    }
+
+    /// Makes the last added parameter optional.
+    ///
+    /// # Panics
+    /// Panics if no parameters were added.
+    #[inline]
+    pub fn optional(mut self) -> Self {
+        self.last_param().optional = true;
+        self
+    }
+
+    /// Allows the last added parameter to be an empty array.
+    ///
+    /// # Panics
+    /// Panics if no parameters were added.
+    #[inline]
+    pub fn empty(mut self) -> Self {
+        self.last_param().empty = true;
+        self
+    }
+
+    /// Returns the parameters added so far, in order.
+    #[inline]
+    pub fn params(&self) -> &[ParamInfo] {
+        &self.params
+    }
+
+    /// Builds the argument string.
+    ///
+    /// The string is in the normalized form returned by `normalize_args()`. Returns an error if any
+    /// parameter has an invalid name, a name is used more than once or a non-array parameter
+    /// allows empty arrays.
+    pub fn build(&self) -> Result<String, InvalidArgsError> {
+        let mut rv = String::new();
+
+        for (index, param) in self.params.iter().enumerate() {
+            // Check the name separately so that separators in it can't change the string layout.
+            if !is_name_valid(&param.name) {
+                return Err(InvalidArgsError::InvalidName {
+                    index,
+                    name: param.name.clone(),
+                });
+            }
+
+            rv.push_str(&param.name);
+            rv.push(':');
+            rv.push_str(type_name(param.value_type));
+            if param.array {
+                rv.push_str("[]");
+            }
+            if param.optional {
+                rv.push_str(":opt");
+            }
+            if param.empty {
+                rv.push_str(":empty");
+            }
+            rv.push(';');
+        }
+
+        normalize_args(&rv)
+    }
+
+    #[inline]
+    fn last_param(&mut self) -> &mut ParamInfo {
+        self.params
+            .last_mut()
+            .expect("no parameters were added to the ArgSpec")
+    }
+}
+
+/// Returns the value type corresponding to an argument type name.
+fn value_type(type_name: &str) -> Option<ValueType> {
+    match type_name {
+        "int" => Some(ValueType::Int),
+        "float" => Some(ValueType::Float),
+        "data" => Some(ValueType::Data),
+        "clip" => Some(ValueType::Node),
+        "frame" => Some(ValueType::Frame),
+        "func" => Some(ValueType::Function),
+        _ => None,
+    }
+}
+
+/// Checks if an argument or filter name matches `^[a-zA-Z][a-zA-Z0-9_]*$`.
+fn is_name_valid(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() => {}
+        _ => return false,
+    }
+
+    chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Validates a filter argument string and returns it in the normalized form.
+///
+/// See `FilterFunction::args()` for the format. The normalized form has every argument terminated
+/// with a semicolon and no empty segments. VapourSynth doesn't report errors in argument strings
+/// gracefully, so this is called on every string before it's handed over to VapourSynth.
+pub fn normalize_args(args: &str) -> Result<String, InvalidArgsError> {
+    let mut rv = String::with_capacity(args.len() + 1);
+
+    for (arg, _) in parse(args)? {
+        rv.push_str(arg);
+        rv.push(';');
+    }
+
+    Ok(rv)
+}
+
+/// Validates a filter argument string and returns the declared parameters, in order.
+///
+/// See `FilterFunction::args()` for the format.
+pub fn parse_args(args: &str) -> Result<Vec<ParamInfo>, InvalidArgsError> {
+    Ok(parse(args)?.into_iter().map(|(_, param)| param).collect())
+}
+
+/// Validates a filter argument string and returns every argument along with its parameter.
+fn parse(args: &str) -> Result<Vec<(&str, ParamInfo)>, InvalidArgsError> {
+    let mut rv = Vec::new();
+    let mut names = HashSet::new();
+
+    for (index, arg) in args.split(';').filter(|x| !x.is_empty()).enumerate() {
+        let mut fields = arg.split(':');
+
+        let name = fields.next().unwrap();
+        if !is_name_valid(name) {
+            return Err(InvalidArgsError::InvalidName {
+                index,
+                name: name.to_owned(),
+            });
+        }
+        if !names.insert(name) {
+            return Err(InvalidArgsError::DuplicateName {
+                name: name.to_owned(),
+            });
+        }
+
+        let type_name = fields.next().ok_or_else(|| InvalidArgsError::MissingType {
+            name: name.to_owned(),
+        })?;
+        let (base_type, is_array) = match type_name.strip_suffix("[]") {
+            Some(base_type) => (base_type, true),
+            None => (type_name, false),
+        };
+        let value_type = value_type(base_type).ok_or_else(|| InvalidArgsError::UnknownType {
+            name: name.to_owned(),
+            type_name: type_name.to_owned(),
+        })?;
+
+        let mut opt = false;
+        let mut empty = false;
+        for flag in fields {
+            let seen = match flag {
+                "opt" => &mut opt,
+                "empty" => &mut empty,
+                _ => {
+                    return Err(InvalidArgsError::UnknownFlag {
+                        name: name.to_owned(),
+                        flag: flag.to_owned(),
+                    })
+                }
+            };
+
+            if *seen {
+                return Err(InvalidArgsError::DuplicateFlag {
+                    name: name.to_owned(),
+                    flag: flag.to_owned(),
+                });
+            }
+            *seen = true;
+        }
+
+        if empty && !is_array {
+            return Err(InvalidArgsError::EmptyFlagOnNonArray {
+                name: name.to_owned(),
+            });
+        }
+
+        rv.push((
+            arg,
+            ParamInfo {
+                name: name.to_owned(),
+                value_type,
+                array: is_array,
+                optional: opt,
+                empty,
+            },
+        ));
+    }
+
+    Ok(rv)
+}
@@ -0,0 +1,22 @@
+use thiserror::Error;
+
+/// An error indicating a filter argument string is invalid.
+///
+/// See `FilterFunction::args()` for the argument string format.
+#[derive(Error, Debug, Clone, Eq, PartialEq)]
+pub enum InvalidArgsError {
+    #[error("Argument {} has an invalid name {:?}", index, name)]
+    InvalidName { index: usize, name: String },
+    #[error("Argument {:?} has no type", name)]
+    MissingType { name: String },
+    #[error("Argument {:?} has an unknown type {:?}", name, type_name)]
+    UnknownType { name: String, type_name: String },
+    #[error("Argument {:?} has an unknown flag {:?}", name, flag)]
+    UnknownFlag { name: String, flag: String },
+    #[error("Argument {:?} has the {:?} flag more than once", name, flag)]
+    DuplicateFlag { name: String, flag: String },
+    #[error("Argument {:?} isn't an array but has the \"empty\" flag", name)]
+    EmptyFlagOnNonArray { name: String },
+    #[error("Argument {:?} is declared more than once", name)]
+    DuplicateName { name: String },
+}
@@ -1,5 +1,5 @@
 //! Internal stuff for plugin FFI handling.
-use std::ffi::CString;
+use std::ffi::{CStr, CString};
 use std::fmt::Write;
 use std::ops::{Deref, DerefMut};
 use std::os::raw::c_void;
@@ -13,8 +13,11 @@ use thiserror::Error;
 use crate::api::API;
 use crate::core::CoreRef;
 use crate::frame::FrameRef;
-use crate::map::{Map, MapRef, MapRefMut};
-use crate::plugins::{Filter, FilterFunction, FrameContext, Metadata};
+use crate::map::{Map, MapRef, MapRefMut, OwnedMap};
+use crate::node::{Flags, Node};
+use crate::plugin::RegisterFunctionError;
+use crate::plugins::{normalize_args, Filter, FilterFunction, FrameContext, Metadata};
+use crate::tracking::{self, Resource};
 use crate::video_info::VideoInfo;
 
 /// Container for the internal filter function data.
@@ -109,12 +112,13 @@ unsafe extern "system" fn get_frame(
         let n = n as usize;
 
         let rv = match activation_reason {
-            x if x == ffi::VSActivationReason::arInitial as _ => {
+            x if x == ffi::VSActivationReason::arInitial as i32 => {
                 match filter.get_frame_initial(api, core, context, n) {
                     Ok(Some(frame)) => {
                         let ptr = frame.deref().deref() as *const _;
                         // The ownership is transferred to the caller.
                         mem::forget(frame);
+                        tracking::released(Resource::Frame);
                         ptr
                     }
                     Ok(None) => ptr::null(),
@@ -132,12 +136,13 @@ unsafe extern "system" fn get_frame(
                     }
                 }
             }
-            x if x == ffi::VSActivationReason::arAllFramesReady as _ => {
+            x if x == ffi::VSActivationReason::arAllFramesReady as i32 => {
                 match filter.get_frame(api, core, context, n) {
                     Ok(frame) => {
                         let ptr = frame.deref().deref() as *const _;
                         // The ownership is transferred to the caller.
                         mem::forget(frame);
+                        tracking::released(Resource::Frame);
                         ptr
                     }
                     Err(err) => {
@@ -163,6 +168,17 @@ unsafe extern "system" fn get_frame(
     }
 }
 
+/// Returns the mode the filter should be created in.
+#[inline]
+fn filter_mode(filter: &dyn Filter) -> ffi::VSFilterMode {
+    // Serial filters rely on never being called from more than one thread at a time.
+    if filter.is_serial() {
+        ffi::VSFilterMode::fmSerial
+    } else {
+        ffi::VSFilterMode::fmParallel
+    }
+}
+
 /// Creates a new instance of the filter.
 pub(crate) unsafe extern "system" fn create<F: FilterFunction>(
     in_: *const ffi::VSMap,
@@ -172,7 +188,7 @@ pub(crate) unsafe extern "system" fn create<F: FilterFunction>(
     api: *const ffi::VSAPI,
 ) {
     let closure = move || {
-        API::set(api);
+        API::set(api, core);
 
         let args = MapRef::from_ptr(in_);
         let mut out = MapRefMut::from_ptr(out);
@@ -200,6 +216,8 @@ pub(crate) unsafe extern "system" fn create<F: FilterFunction>(
         };
 
         if let Some(filter) = filter {
+            let filter_mode = filter_mode(&**filter);
+
             API::get_cached().create_filter(
                 in_,
                 out.deref_mut().deref_mut(),
@@ -207,7 +225,7 @@ pub(crate) unsafe extern "system" fn create<F: FilterFunction>(
                 init,
                 get_frame,
                 Some(free),
-                ffi::VSFilterMode::fmParallel,
+                filter_mode,
                 ffi::VSNodeFlags(0),
                 Box::into_raw(filter) as *mut _,
                 core.ptr(),
@@ -223,6 +241,41 @@ pub(crate) unsafe extern "system" fn create<F: FilterFunction>(
     }
 }
 
+/// Creates a node backed by `filter` outside of a plugin.
+///
+/// # Safety
+/// The caller must ensure the API is cached.
+pub(crate) unsafe fn create_filter_node<'core>(
+    core: CoreRef<'core>,
+    name: &CStr,
+    flags: Flags,
+    filter: Box<dyn Filter<'core> + 'core>,
+) -> Result<Node<'core>, anyhow::Error> {
+    let api = API::get_cached();
+    let in_ = OwnedMap::new(api);
+    let mut out = OwnedMap::new(api);
+    let filter_mode = filter_mode(&*filter);
+
+    api.create_filter(
+        in_.deref().deref(),
+        out.deref_mut().deref_mut(),
+        name.as_ptr(),
+        init,
+        get_frame,
+        Some(free),
+        filter_mode,
+        ffi::VSNodeFlags(flags.bits()),
+        Box::into_raw(Box::new(filter)) as *mut _,
+        core.ptr(),
+    );
+
+    if let Some(error) = out.error() {
+        anyhow::bail!("{}", error);
+    }
+
+    Ok(out.get_node("clip")?)
+}
+
 /// Registers the plugin.
 ///
 /// This function is for internal use only.
@@ -254,8 +307,41 @@ pub unsafe fn call_config_func(
     );
 }
 
+/// Reports an error which prevented a filter from being registered.
+///
+/// VapourSynth doesn't pass its API to the plugin initialization function, so the error is sent
+/// through the VapourSynth log only if the API can be retrieved some other way and supports
+/// logging. Otherwise the error is only returned from `call_register_func()`.
+fn report_registration_error(name: &str, error: &RegisterFunctionError) {
+    #[cfg(all(
+        feature = "gte-vapoursynth-api-34",
+        any(
+            feature = "vapoursynth-functions",
+            all(feature = "vsscript-functions", feature = "gte-vsscript-api-32")
+        )
+    ))]
+    if let Some(api) = API::get() {
+        let message = match error {
+            RegisterFunctionError::CStringConversion(_) => {
+                format!(
+                    "Couldn't register filter {:?}: the name contains a null byte",
+                    name
+                )
+            }
+            RegisterFunctionError::InvalidArgs(err) => format!(
+                "Couldn't register filter {}: invalid args string: {}",
+                name, err
+            ),
+        };
+        let _ = api.log(crate::api::MessageType::Critical, &message);
+    }
+}
+
 /// Registers the filter `F`.
 ///
+/// If the name or the args string of the filter function is invalid, the filter isn't
+/// registered and the error is returned.
+///
 /// This function is for internal use only.
 ///
 /// # Safety
@@ -265,13 +351,23 @@ pub unsafe fn call_register_func<F: FilterFunction>(
     register_func: *const c_void,
     plugin: *mut c_void,
     filter_function: F,
-) {
+) -> Result<(), RegisterFunctionError> {
     let register_func = *(&register_func as *const _ as *const ffi::VSRegisterFunction);
 
-    let name_cstring = CString::new(filter_function.name())
-        .expect("Couldn't convert the filter name to a CString");
-    let args_cstring = CString::new(filter_function.args())
-        .expect("Couldn't convert the filter args to a CString");
+    let cstrings = CString::new(filter_function.name())
+        .map_err(RegisterFunctionError::from)
+        .and_then(|name| {
+            // The normalized string consists of validated names and types only.
+            let args = CString::new(normalize_args(filter_function.args())?).unwrap();
+            Ok((name, args))
+        });
+    let (name_cstring, args_cstring) = match cstrings {
+        Ok(x) => x,
+        Err(err) => {
+            report_registration_error(filter_function.name(), &err);
+            return Err(err);
+        }
+    };
 
     let data = Box::new(FilterFunctionData {
         filter_function,
@@ -285,6 +381,8 @@ pub unsafe fn call_register_func<F: FilterFunction>(
         Box::into_raw(data) as _,
         plugin as *mut ffi::VSPlugin,
     );
+
+    Ok(())
 }
 
 /// Exports a VapourSynth plugin from this library.
@@ -327,7 +425,8 @@ macro_rules! export_vapoursynth_plugin {
             let closure = move || {
                 call_config_func(config_func, plugin, $metadata);
 
-                $(call_register_func(register_func, plugin, $filter);)*
+                // Invalid filter functions are skipped, the rest of the plugin still loads.
+                $(let _ = call_register_func(register_func, plugin, $filter);)*
             };
 
             if panic::catch_unwind(closure).is_err() {
@@ -1,8 +1,10 @@
 use std::marker::PhantomData;
-use std::ptr::NonNull;
+use std::ptr::{self, NonNull};
 use vapoursynth_sys as ffi;
 
 use crate::api::API;
+use crate::core::CoreRef;
+use crate::node::Node;
 
 /// A frame context used in filters.
 #[derive(Debug, Clone, Copy)]
@@ -31,10 +33,60 @@ impl<'a> FrameContext<'a> {
     }
 
     /// Returns the index of the node from which the frame is being requested.
+    ///
+    /// A filter whose `video_info()` returns more than one entry has one output node per entry,
+    /// all backed by the same filter instance. This index tells them apart.
     #[inline]
     pub fn output_index(self) -> usize {
         let index = unsafe { API::get_cached().get_output_index(self.handle.as_ptr()) };
         debug_assert!(index >= 0);
         index as _
     }
+
+    /// Returns the node and the number of the requested frame which completed last.
+    ///
+    /// When a filter is activated because one of its requested frames became ready, this is the
+    /// frame that triggered the activation. In `get_frame()`, after all requested frames are
+    /// ready, it's the one which completed last. Returns `None` if none of the requested frames
+    /// have completed yet, for example in `get_frame_initial()`.
+    ///
+    /// `core` is only used to tie the returned node to the lifetime of the core.
+    pub fn completed_frame<'core>(self, _core: CoreRef<'core>) -> Option<(Node<'core>, usize)> {
+        let mut node = ptr::null_mut();
+        let mut n = 0;
+        unsafe {
+            API::get_cached().query_completed_frame(&mut node, &mut n, self.handle.as_ptr());
+        }
+
+        if node.is_null() {
+            return None;
+        }
+
+        debug_assert!(n >= 0);
+
+        // The returned reference is borrowed from the context, so clone it.
+        let node = unsafe { Node::from_ptr(API::get_cached().clone_node(node)) };
+        Some((node, n as usize))
+    }
+
+    /// Releases frame `n` of `node` early.
+    ///
+    /// Tells the core that a frame requested with `Node::request_frame_filter()` in this context
+    /// won't be retrieved with `Node::get_frame_filter()` after all, so the memory it takes up
+    /// can be reclaimed right away instead of when the current frame is done. This is useful for
+    /// filters which request many large frames but end up needing only some of them.
+    ///
+    /// Must only be called for frames already requested in this context, from `get_frame()`.
+    ///
+    /// # Panics
+    /// Panics is `n` is greater than `i32::MAX`.
+    #[inline]
+    pub fn release_frame_early(self, node: &Node, n: usize) {
+        assert!(n <= i32::MAX as usize);
+        let n = n as i32;
+
+        unsafe {
+            API::get_cached().release_frame_early(node.ptr(), n, self.handle.as_ptr());
+        }
+    }
 }
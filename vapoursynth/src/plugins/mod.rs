@@ -10,9 +10,18 @@ use crate::map::{self, Map, Value, ValueIter};
 use crate::node::Node;
 use crate::video_info::VideoInfo;
 
+mod args;
+pub use self::args::{normalize_args, parse_args, ArgSpec, ParamInfo};
+
+mod errors;
+pub use self::errors::InvalidArgsError;
+
 mod frame_context;
 pub use self::frame_context::FrameContext;
 
+mod serial;
+pub use self::serial::SerialFilter;
+
 pub mod ffi;
 
 /// Plugin metadata.
@@ -42,6 +51,23 @@ pub struct Metadata {
     pub read_only: bool,
 }
 
+/// The way a filter requests frames from one of its input nodes.
+///
+/// This corresponds to `VSRequestPattern` from VapourSynth API 4 and is used in
+/// `Filter::dependencies()`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum RequestPattern {
+    /// Any frames may be requested, and the same frame may be requested more than once.
+    General,
+
+    /// Each frame is requested at most once, so it doesn't need to be kept in the cache.
+    NoFrameReuse,
+
+    /// Only frame `n` is requested when generating frame `n`, and the input node has the same
+    /// length as the output node.
+    StrictSpatial,
+}
+
 /// A filter function interface.
 ///
 /// See the `make_filter_function!` macro that generates types implementing this automatically.
@@ -73,8 +99,21 @@ pub trait FilterFunction: Send + Sync {
     ///
     /// The following example declares the arguments "blah", "moo", and "asdf":
     /// `blah:clip;moo:int[]:opt;asdf:float:opt;`
+    ///
+    /// The string is checked with `normalize_args()` when the filter function is registered. If
+    /// it's invalid, the filter function isn't registered. The error is reported through the
+    /// VapourSynth log when it can be reached.
     fn args(&self) -> &str;
 
+    /// Returns the parameters declared in the argument string.
+    ///
+    /// This is meant for tools inspecting plugins, for example to generate a user interface for
+    /// the filter. The default implementation parses `args()` with `parse_args()`.
+    #[inline]
+    fn parameters(&self) -> Result<Vec<ParamInfo>, InvalidArgsError> {
+        parse_args(self.args())
+    }
+
     /// The callback for this filter function.
     ///
     /// In most cases this is where you should create a new instance of the filter and return it.
@@ -83,7 +122,8 @@ pub trait FilterFunction: Send + Sync {
     ///
     /// `args` contains the filter arguments, as specified by the argument string from
     /// `FilterFunction::args()`. Their presence and types are validated by VapourSynth so it's
-    /// safe to `unwrap()`.
+    /// safe to `unwrap()`. When debugging argument parsing, `args.summary()` gives a cheap
+    /// overview of the arguments the filter was called with.
     ///
     /// In this function you should take all input nodes for your filter and store them somewhere
     /// so that you can request their frames in `get_frame_initial()`.
@@ -98,9 +138,27 @@ pub trait FilterFunction: Send + Sync {
 }
 
 /// A filter interface.
-// TODO: perhaps it's possible to figure something out about Send + Sync with specialization? Since
-// there are Node flags which say that the filter will be called strictly by one thread, in which
-// case Sync shouldn't be required.
+///
+/// The frame functions can be called from several threads at once, so the filter must be `Sync`.
+/// See `SerialFilter` for filters that aren't `Sync`.
+///
+/// # Sharing state
+/// Per-instance data is simply stored in the filter's fields. Immutable data shared between
+/// filter instances, such as configuration or lookup tables computed once, can be stored in an
+/// `Arc` field: `Arc<T>` is `Send + Sync` whenever `T` is, and it's dropped together with the
+/// last filter instance referencing it. The `FilterFunction` can hold the original `Arc` and hand
+/// out clones in `create()`.
+///
+/// ```ignore
+/// struct Config {
+///     lut: [u8; 256],
+/// }
+///
+/// struct MyFilter<'core> {
+///     source: Node<'core>,
+///     config: Arc<Config>,
+/// }
+/// ```
 pub trait Filter<'core>: Send + Sync {
     /// Returns the parameters of this filter's output node.
     ///
@@ -140,6 +198,30 @@ pub trait Filter<'core>: Send + Sync {
         context: FrameContext,
         n: usize,
     ) -> Result<FrameRef<'core>, Error>;
+
+    /// Returns the input nodes of this filter along with the way their frames are requested.
+    ///
+    /// VapourSynth API 4 lets filters declare their dependencies explicitly so that the core can
+    /// make better caching decisions. The currently supported API versions have no such
+    /// mechanism, so the returned dependencies are purely advisory: they are never passed to the
+    /// core and don't affect how the filter is run. Frames still have to be requested in
+    /// `get_frame_initial()` as usual.
+    ///
+    /// Declaring the dependencies now lets the filter take advantage of them once API 4 support is
+    /// added. The default implementation returns no dependencies.
+    #[inline]
+    fn dependencies(&self) -> Vec<(Node<'core>, RequestPattern)> {
+        Vec::new()
+    }
+
+    /// Returns whether this filter must be created in the serial mode.
+    ///
+    /// This is used for filters created with `SerialFilter::into_filter()`.
+    #[doc(hidden)]
+    #[inline]
+    fn is_serial(&self) -> bool {
+        false
+    }
 }
 
 /// An internal trait representing a filter argument type.
@@ -171,6 +253,13 @@ impl<'map, 'elem: 'map> FilterArgument<'map, 'elem> for i64 {
     }
 }
 
+impl<'map, 'elem: 'map> FilterArgument<'map, 'elem> for bool {
+    #[inline]
+    fn type_name() -> &'static str {
+        "int"
+    }
+}
+
 impl<'map, 'elem: 'map> FilterArgument<'map, 'elem> for f64 {
     #[inline]
     fn type_name() -> &'static str {
@@ -308,8 +397,9 @@ mod private {
     pub trait Sealed {}
 
     impl Sealed for i64 {}
+    impl Sealed for bool {}
     impl Sealed for f64 {}
-    impl<'map> Sealed for &'map [u8] {}
+    impl Sealed for &[u8] {}
     impl<'elem> Sealed for Node<'elem> {}
     impl<'elem> Sealed for FrameRef<'elem> {}
     impl<'elem> Sealed for Function<'elem> {}
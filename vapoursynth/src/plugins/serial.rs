@@ -0,0 +1,113 @@
+//! Filters which are run in the serial mode.
+
+use std::sync::Mutex;
+
+use anyhow::Error;
+
+use crate::api::API;
+use crate::core::CoreRef;
+use crate::frame::FrameRef;
+use crate::node::Node;
+use crate::plugins::{Filter, FrameContext, RequestPattern};
+use crate::video_info::VideoInfo;
+
+/// A filter interface for filters with mutable state.
+///
+/// Unlike `Filter`, this trait doesn't require `Sync` and the frame functions receive `&mut self`.
+/// This is made possible by running the filter in the serial mode, where VapourSynth never calls
+/// into the filter from more than one thread at a time and processes only one of its frames at a
+/// time, which comes at a cost of parallelism.
+///
+/// Return `SerialFilter::into_filter()` from `FilterFunction::create()` to create the filter.
+pub trait SerialFilter<'core>: Send {
+    /// Returns the parameters of this filter's output node.
+    ///
+    /// The returned vector should contain one entry for each node output index.
+    fn video_info(&self, api: API, core: CoreRef<'core>) -> Vec<VideoInfo<'core>>;
+
+    /// Requests the necessary frames from downstream nodes.
+    ///
+    /// See `Filter::get_frame_initial()`.
+    fn get_frame_initial(
+        &mut self,
+        api: API,
+        core: CoreRef<'core>,
+        context: FrameContext,
+        n: usize,
+    ) -> Result<Option<FrameRef<'core>>, Error>;
+
+    /// Returns the requested frame.
+    ///
+    /// See `Filter::get_frame()`.
+    fn get_frame(
+        &mut self,
+        api: API,
+        core: CoreRef<'core>,
+        context: FrameContext,
+        n: usize,
+    ) -> Result<FrameRef<'core>, Error>;
+
+    /// Returns the input nodes of this filter along with the way their frames are requested.
+    ///
+    /// See `Filter::dependencies()`.
+    #[inline]
+    fn dependencies(&self) -> Vec<(Node<'core>, RequestPattern)> {
+        Vec::new()
+    }
+
+    /// Boxes the filter so that it can be returned from `FilterFunction::create()`.
+    #[inline]
+    fn into_filter(self) -> Box<dyn Filter<'core> + 'core>
+    where
+        Self: Sized + 'core,
+    {
+        Box::new(SerialFilterAdapter(Mutex::new(self)))
+    }
+}
+
+/// Adapts a `SerialFilter` to the `Filter` interface.
+///
+/// The filter is created in the serial mode, so the lock is never contended.
+struct SerialFilterAdapter<F>(Mutex<F>);
+
+impl<'core, F: SerialFilter<'core>> Filter<'core> for SerialFilterAdapter<F> {
+    #[inline]
+    fn video_info(&self, api: API, core: CoreRef<'core>) -> Vec<VideoInfo<'core>> {
+        self.0.lock().unwrap().video_info(api, core)
+    }
+
+    #[inline]
+    fn get_frame_initial(
+        &self,
+        api: API,
+        core: CoreRef<'core>,
+        context: FrameContext,
+        n: usize,
+    ) -> Result<Option<FrameRef<'core>>, Error> {
+        self.0
+            .lock()
+            .unwrap()
+            .get_frame_initial(api, core, context, n)
+    }
+
+    #[inline]
+    fn get_frame(
+        &self,
+        api: API,
+        core: CoreRef<'core>,
+        context: FrameContext,
+        n: usize,
+    ) -> Result<FrameRef<'core>, Error> {
+        self.0.lock().unwrap().get_frame(api, core, context, n)
+    }
+
+    #[inline]
+    fn dependencies(&self) -> Vec<(Node<'core>, RequestPattern)> {
+        self.0.lock().unwrap().dependencies()
+    }
+
+    #[inline]
+    fn is_serial(&self) -> bool {
+        true
+    }
+}
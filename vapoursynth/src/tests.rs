@@ -43,6 +43,7 @@ mod need_api_and_vsscript {
         let format = frame.format();
         assert_eq!(format.name(), "RGB24");
         assert_eq!(format.plane_count(), 3);
+        assert_eq!(frame.format_id(), PresetFormat::RGB24.into());
 
         for plane in 0..format.plane_count() {
             let resolution = frame.resolution(plane);
@@ -66,16 +67,17 @@ mod need_api_and_vsscript {
     }
 
     fn green_test(env: &vsscript::Environment) {
-        #[cfg(feature = "gte-vsscript-api-31")]
-        let (node, alpha_node) = {
+        let vsscript::Output {
+            node,
+            alpha: alpha_node,
+        } = {
             let output = env.get_output(0);
             assert!(output.is_ok());
             output.unwrap()
         };
-        #[cfg(not(feature = "gte-vsscript-api-31"))]
-        let (node, alpha_node) = (env.get_output(0).unwrap(), None::<Node>);
 
         assert!(alpha_node.is_none());
+        assert_eq!(env.has_alpha(0).ok(), Some(false));
 
         let info = node.info();
 
@@ -132,14 +134,14 @@ mod need_api_and_vsscript {
             vsscript::Environment::from_file("test-vpy/variable.vpy", vsscript::EvalFlags::Nothing)
                 .unwrap();
 
-        #[cfg(feature = "gte-vsscript-api-31")]
-        let (node, alpha_node) = {
+        let vsscript::Output {
+            node,
+            alpha: alpha_node,
+        } = {
             let output = env.get_output(0);
             assert!(output.is_ok());
             output.unwrap()
         };
-        #[cfg(not(feature = "gte-vsscript-api-31"))]
-        let (node, alpha_node) = (env.get_output(0).unwrap(), None::<Node>);
 
         assert!(alpha_node.is_none());
 
@@ -148,6 +150,9 @@ mod need_api_and_vsscript {
         assert_eq!(info.format, Property::Variable);
         assert_eq!(info.framerate, Property::Variable);
         assert_eq!(info.resolution, Property::Variable);
+        assert_eq!(node.frame_layout(), None);
+        assert!(node.format().is_none());
+        assert_eq!(node.plane_count(), None);
 
         #[cfg(feature = "gte-vapoursynth-api-32")]
         assert_eq!(info.num_frames, 200);
@@ -211,6 +216,44 @@ mod need_api_and_vsscript {
         env_video_var_test(&env);
     }
 
+    // Doesn't depend on the VSScript API version.
+    #[test]
+    fn output() {
+        let env =
+            vsscript::Environment::from_file("test-vpy/green.vpy", vsscript::EvalFlags::Nothing)
+                .unwrap();
+
+        let output: vsscript::Output = env.get_output(0).unwrap();
+        assert!(output.alpha.is_none());
+        green_frame_test(&output.node.get_frame(0).unwrap());
+
+        assert!(matches!(env.get_output(1), Err(vsscript::Error::NoOutput)));
+    }
+
+    #[test]
+    fn outputs() {
+        let env =
+            vsscript::Environment::from_file("test-vpy/green.vpy", vsscript::EvalFlags::Nothing)
+                .unwrap();
+
+        let outputs = env.get_outputs().unwrap();
+        assert_eq!(outputs.len(), 1);
+        assert_eq!(outputs[0].0, 0);
+        green_frame_test(&outputs[0].1.node.get_frame(0).unwrap());
+        assert_eq!(env.output_indices().unwrap(), vec![0]);
+
+        let env = vsscript::Environment::from_file(
+            "test-vpy/pixel-formats.vpy",
+            vsscript::EvalFlags::Nothing,
+        )
+        .unwrap();
+        assert_eq!(env.output_indices().unwrap(), (0..6).collect::<Vec<_>>());
+
+        // Gaps between the indices don't stop the search.
+        env.clear_output(2).unwrap();
+        assert_eq!(env.output_indices().unwrap(), vec![0, 1, 3, 4, 5]);
+    }
+
     #[test]
     #[cfg(feature = "gte-vsscript-api-31")]
     fn alpha() {
@@ -218,15 +261,18 @@ mod need_api_and_vsscript {
             vsscript::Environment::from_file("test-vpy/alpha.vpy", vsscript::EvalFlags::Nothing)
                 .unwrap();
 
-        let (_, alpha_node) = {
+        let alpha_node = {
             let output = env.get_output(0);
             assert!(output.is_ok());
-            output.unwrap()
+            output.unwrap().alpha
         };
 
         assert!(alpha_node.is_some());
         let alpha_node = alpha_node.unwrap();
 
+        assert_eq!(env.has_alpha(0).ok(), Some(true));
+        assert!(env.has_alpha(1).is_err());
+
         let info = alpha_node.info();
 
         if let Property::Constant(format) = info.format {
@@ -283,16 +329,13 @@ mod need_api_and_vsscript {
         bits_per_sample: u8,
         color: [T; 3],
     ) {
-        #[cfg(feature = "gte-vsscript-api-31")]
-        let node = env.get_output(index).unwrap().0;
-        #[cfg(not(feature = "gte-vsscript-api-31"))]
-        let node = env.get_output(index).unwrap();
+        let node = env.get_output(index).unwrap().node;
 
         let frame = node.get_frame(0).unwrap();
         let format = frame.format();
 
         assert_eq!(format.bits_per_sample(), bits_per_sample);
-        let bytes_per_sample = ((bits_per_sample + 7) / 8).next_power_of_two();
+        let bytes_per_sample = bits_per_sample.div_ceil(8).next_power_of_two();
         assert_eq!(format.bytes_per_sample(), bytes_per_sample);
 
         for plane_num in 0..3 {
@@ -354,10 +397,7 @@ mod need_api_and_vsscript {
         )
         .unwrap();
 
-        #[cfg(feature = "gte-vsscript-api-31")]
-        let node = env.get_output(0).unwrap().0;
-        #[cfg(not(feature = "gte-vsscript-api-31"))]
-        let node = env.get_output(0).unwrap();
+        let node = env.get_output(0).unwrap().node;
 
         let frame = node.get_frame(0).unwrap();
         let _ = frame.plane_row::<u8>(0, 0); // Should be u16.
@@ -369,10 +409,7 @@ mod need_api_and_vsscript {
             vsscript::Environment::from_file("test-vpy/gradient.vpy", vsscript::EvalFlags::Nothing)
                 .unwrap();
 
-        #[cfg(feature = "gte-vsscript-api-31")]
-        let node = env.get_output(0).unwrap().0;
-        #[cfg(not(feature = "gte-vsscript-api-31"))]
-        let node = env.get_output(0).unwrap();
+        let node = env.get_output(0).unwrap().node;
 
         let frame = node.get_frame(0).unwrap();
         for plane in 0..3 {
@@ -395,6 +432,250 @@ mod need_api_and_vsscript {
         }
     }
 
+    #[test]
+    fn plane_to_vec2d() {
+        let env =
+            vsscript::Environment::from_file("test-vpy/gradient.vpy", vsscript::EvalFlags::Nothing)
+                .unwrap();
+        let node = env.get_output(0).unwrap().node;
+        let frame = node.get_frame(0).unwrap();
+
+        let red = frame.plane_to_vec2d::<u8>(0);
+        assert_eq!(red.len(), 16);
+        assert!(red.iter().all(|row| row.len() == 16));
+        assert_eq!(red[3][5], 48);
+
+        let green = frame.plane_to_vec2d::<u8>(1);
+        assert_eq!(green[3][5], 80);
+    }
+
+    #[test]
+    fn flip() {
+        let env =
+            vsscript::Environment::from_file("test-vpy/gradient.vpy", vsscript::EvalFlags::Nothing)
+                .unwrap();
+        let core = env.get_core().unwrap();
+        let node = env.get_output(0).unwrap().node;
+        let frame = node.get_frame(0).unwrap();
+
+        let mut dst = FrameRefMut::copy_of(core, &frame);
+        assert_eq!(frame.flip_vertical_into(&mut dst), Ok(()));
+        for row in 0..16 {
+            // Red increases downwards, green to the right.
+            assert_eq!(dst.plane_row::<u8>(0, row), &[(15 - row as u8) * 16; 16]);
+            assert_eq!(dst.plane_row::<u8>(1, row), frame.plane_row::<u8>(1, row));
+        }
+
+        assert_eq!(frame.flip_horizontal_into(&mut dst), Ok(()));
+        for row in 0..16 {
+            assert_eq!(dst.plane_row::<u8>(0, row), frame.plane_row::<u8>(0, row));
+            let mut reversed = frame.plane_row::<u8>(1, row).to_vec();
+            reversed.reverse();
+            assert_eq!(dst.plane_row::<u8>(1, row), &reversed[..]);
+        }
+
+        let gray8 = core.get_format(PresetFormat::Gray8.into()).unwrap();
+        let resolution = Resolution {
+            width: 16,
+            height: 16,
+        };
+        let mut gray = core
+            .new_frame_with_data(gray8, resolution, &[&[0u8; 256][..]], None)
+            .unwrap();
+        assert_eq!(
+            frame.flip_vertical_into(&mut gray),
+            Err(frame::MismatchedFrames)
+        );
+    }
+
+    #[test]
+    fn plane_region() {
+        let env =
+            vsscript::Environment::from_file("test-vpy/gradient.vpy", vsscript::EvalFlags::Nothing)
+                .unwrap();
+        let node = env.get_output(0).unwrap().node;
+        let frame = node.get_frame(0).unwrap();
+
+        let region = frame.plane_region::<u8>(0, 2, 3, 4, 4).unwrap();
+        let expected: Vec<u8> = (3..7).flat_map(|row| [row * 16; 4]).collect();
+        assert_eq!(region, expected);
+
+        let region = frame.plane_region::<u8>(1, 2, 3, 4, 4).unwrap();
+        let expected: Vec<u8> = (0..4).flat_map(|_| (2..6).map(|col| col * 16)).collect();
+        assert_eq!(region, expected);
+
+        assert_eq!(frame.plane_region::<u8>(0, 12, 0, 4, 16).unwrap().len(), 64);
+        assert_eq!(
+            frame.plane_region::<u8>(0, 13, 0, 4, 4),
+            Err(frame::RegionOutOfBounds)
+        );
+        assert_eq!(
+            frame.plane_region::<u8>(0, 0, usize::MAX, 1, 2),
+            Err(frame::RegionOutOfBounds)
+        );
+    }
+
+    #[test]
+    fn benchmark() {
+        let env =
+            vsscript::Environment::from_file("test-vpy/green.vpy", vsscript::EvalFlags::Nothing)
+                .unwrap();
+        let node = env.get_output(0).unwrap().node;
+
+        let result = node.benchmark(0..10, 4).unwrap();
+        assert_eq!(result.frames, 10);
+        assert!(result.elapsed > std::time::Duration::ZERO);
+        assert!(result.fps > 0.);
+
+        let result = node.benchmark(5..5, 4).unwrap();
+        assert_eq!(result.frames, 0);
+        assert_eq!(result.fps, 0.);
+    }
+
+    #[test]
+    fn set_frame_props() {
+        let env =
+            vsscript::Environment::from_file("test-vpy/green.vpy", vsscript::EvalFlags::Nothing)
+                .unwrap();
+        let core = env.get_core().unwrap();
+        let node = env.get_output(0).unwrap().node;
+
+        let node = node
+            .set_frame_props(core, &[("_ColorRange", 0), ("Answer", 42)])
+            .unwrap();
+        let frame = node.get_frame(0).unwrap();
+        assert_eq!(frame.props().get_int("_ColorRange"), Ok(0));
+        assert_eq!(frame.props().get_int("Answer"), Ok(42));
+        green_frame_test(&frame);
+    }
+
+    #[test]
+    fn map_props() {
+        let env =
+            vsscript::Environment::from_file("test-vpy/green.vpy", vsscript::EvalFlags::Nothing)
+                .unwrap();
+        let core = env.get_core().unwrap();
+        let green = env.get_output(0).unwrap().node;
+
+        let node = green
+            .map_props(core, |n, props| {
+                props.set_int("_Matrix", 1).unwrap();
+                props.set_int("Frame", n as i64).unwrap();
+            })
+            .unwrap();
+
+        let frames = node.get_all_frames_async(0..100, node::FetchOptions::for_core(core));
+        for (n, frame) in frames.into_iter().enumerate() {
+            let frame = frame.unwrap();
+            assert_eq!(frame.props().get_int("_Matrix"), Ok(1));
+            assert_eq!(frame.props().get_int("Frame"), Ok(n as i64));
+            assert_eq!(frame.props().duration(), Some((1, 60)));
+        }
+
+        // The pixel data is shared with the source frame.
+        let source = green.materialize(core, 0..1).unwrap();
+        let node = source.map_props(core, |_, props| props.clear()).unwrap();
+        let frame = node.get_frame(0).unwrap();
+        assert_eq!(frame.props().key_count(), 0);
+        green_frame_test(&frame);
+        for plane in 0..3 {
+            assert_eq!(
+                frame.data_ptr(plane),
+                source.get_frame(0).unwrap().data_ptr(plane)
+            );
+        }
+    }
+
+    #[cfg(feature = "gte-vapoursynth-api-36")]
+    #[test]
+    fn from_file_with_callback() {
+        let messages = std::sync::Mutex::new(Vec::new());
+
+        let env = vsscript::Environment::from_file_with_callback(
+            "test-vpy/green.vpy",
+            vsscript::EvalFlags::Nothing,
+            |message| messages.lock().unwrap().push(message.to_owned()),
+        )
+        .unwrap();
+        green_frame_test(&env.get_output(0).unwrap().node.get_frame(0).unwrap());
+
+        // The handler is removed after the evaluation.
+        let count = messages.lock().unwrap().len();
+        let api = API::get().unwrap();
+        assert_eq!(api.log(MessageType::Debug, "after evaluation"), Ok(()));
+        assert_eq!(messages.lock().unwrap().len(), count);
+
+        assert!(vsscript::Environment::from_file_with_callback(
+            "test-vpy/missing.vpy",
+            vsscript::EvalFlags::Nothing,
+            |_| {}
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn map_frames() {
+        let env =
+            vsscript::Environment::from_file("test-vpy/green.vpy", vsscript::EvalFlags::Nothing)
+                .unwrap();
+        let node = env.get_output(0).unwrap().node;
+
+        let means = node
+            .map_frames(0..10, 3, |_, frame| {
+                let sum: u64 = frame
+                    .plane_rows::<u8>(1)
+                    .flatten()
+                    .map(|&x| u64::from(x))
+                    .sum();
+                Ok(sum as f64 / (frame.width(1) * frame.height(1)) as f64)
+            })
+            .unwrap();
+        assert_eq!(means, [255.; 10]);
+
+        let numbers = node.map_frames(5..10, 0, |n, _| Ok(n)).unwrap();
+        assert_eq!(numbers, [5, 6, 7, 8, 9]);
+
+        let err = node
+            .map_frames(0..10, 4, |n, _| {
+                if n % 4 == 3 {
+                    anyhow::bail!("odd frame");
+                }
+                Ok(n)
+            })
+            .unwrap_err();
+        assert!(err.to_string().contains("frame 3"));
+
+        assert!(node.map_frames(0..0, 4, |n, _| Ok(n)).unwrap().is_empty());
+    }
+
+    #[test]
+    #[cfg(all(feature = "gte-vsscript-api-31", not(feature = "gte-vsscript-api-32")))]
+    fn concurrent_evaluation() {
+        // Make sure the library version has been checked.
+        drop(vsscript::Environment::new().unwrap());
+
+        let version = unsafe { vapoursynth_sys::vsscript_getApiVersion() };
+        if (version >> 16, version & 0xFFFF) < (3, 2) {
+            assert!(vsscript::ffi_calls_serialized());
+            return;
+        }
+        assert!(!vsscript::ffi_calls_serialized());
+
+        // With the mutex held here, the evaluation can only finish if it doesn't take the mutex.
+        let _lock = vsscript::FFI_CALL_MUTEX.lock().unwrap();
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            let rv = vsscript::Environment::from_script(include_str!("../test-vpy/green.vpy"));
+            tx.send(rv.is_ok()).unwrap();
+        });
+
+        assert_eq!(
+            rx.recv_timeout(std::time::Duration::from_secs(10)),
+            Ok(true)
+        );
+    }
+
     #[test]
     fn clear_output() {
         let env =
@@ -417,10 +698,10 @@ mod need_api_and_vsscript {
         let env =
             vsscript::Environment::from_script(include_str!("../test-vpy/green.vpy")).unwrap();
 
-        #[cfg(feature = "gte-vsscript-api-31")]
-        let (node, alpha_node) = env.get_output(0).unwrap();
-        #[cfg(not(feature = "gte-vsscript-api-31"))]
-        let (node, alpha_node) = (env.get_output(0).unwrap(), None::<Node>);
+        let vsscript::Output {
+            node,
+            alpha: alpha_node,
+        } = env.get_output(0).unwrap();
 
         assert!(alpha_node.is_none());
 
@@ -446,20 +727,74 @@ mod need_api_and_vsscript {
         assert!(env.get_variable("video", &mut map).is_ok());
     }
 
+    #[test]
+    fn vsscript_typed_variables() {
+        let env =
+            vsscript::Environment::from_script(include_str!("../test-vpy/green.vpy")).unwrap();
+        let node = env.get_output(0).unwrap().node;
+
+        assert!(env.set_variable_int("Int", 42).is_ok());
+        assert!(env.set_variable_float("Float", 0.5).is_ok());
+        assert!(env.set_variable_data("Data", b"green").is_ok());
+        assert!(env.set_variable_node("Clip", &node).is_ok());
+        assert!(matches!(
+            env.set_variable_int("", 1),
+            Err(vsscript::Error::InvalidVariableName(_))
+        ));
+
+        let mut map = OwnedMap::new(API::get().unwrap());
+        assert!(env.get_variable("Int", &mut map).is_ok());
+        assert_eq!(map.get_int("Int"), Ok(42));
+        assert!(env.get_variable("Float", &mut map).is_ok());
+        assert_eq!(map.get_float("Float"), Ok(0.5));
+        assert!(env.get_variable("Data", &mut map).is_ok());
+        assert_eq!(map.get_data("Data"), Ok(&b"green"[..]));
+        assert!(env.get_variable("Clip", &mut map).is_ok());
+        green_frame_test(&map.get_node("Clip").unwrap().get_frame(0).unwrap());
+    }
+
+    #[test]
+    fn vsscript_variables_from_props() {
+        let env =
+            vsscript::Environment::from_script(include_str!("../test-vpy/green.vpy")).unwrap();
+        let core = env.get_core().unwrap();
+
+        let node = env.get_output(0).unwrap().node;
+
+        let mut frame = FrameRefMut::copy_of(core, &node.get_frame(0).unwrap());
+        {
+            let mut props = frame.props_mut();
+            props.set_float("Gamma", 2.2).unwrap();
+            props.append_data("Label", b"green").unwrap();
+            props.set_node("Clip", &node).unwrap();
+        }
+
+        assert!(env.set_variables_from_props(&frame).is_ok());
+
+        let mut map = OwnedMap::new(API::get().unwrap());
+        assert!(env.get_variable("_DurationDen", &mut map).is_ok());
+        assert_eq!(map.get_int("_DurationDen"), Ok(60));
+        assert!(env.get_variable("Gamma", &mut map).is_ok());
+        assert_eq!(map.get_float("Gamma"), Ok(2.2));
+        assert!(env.get_variable("Label", &mut map).is_ok());
+        assert_eq!(map.get_data("Label"), Ok(&b"green"[..]));
+        assert!(env.get_variable("Clip", &mut map).is_err());
+    }
+
     #[test]
     fn get_frame_async() {
         let env =
             vsscript::Environment::from_file("test-vpy/green.vpy", vsscript::EvalFlags::Nothing)
                 .unwrap();
 
-        #[cfg(feature = "gte-vsscript-api-31")]
-        let (node, alpha_node) = {
+        let vsscript::Output {
+            node,
+            alpha: alpha_node,
+        } = {
             let output = env.get_output(0);
             assert!(output.is_ok());
             output.unwrap()
         };
-        #[cfg(not(feature = "gte-vsscript-api-31"))]
-        let (node, alpha_node) = (env.get_output(0).unwrap(), None::<Node>);
 
         assert!(alpha_node.is_none());
 
@@ -502,14 +837,14 @@ mod need_api_and_vsscript {
             vsscript::Environment::from_file("test-vpy/green.vpy", vsscript::EvalFlags::Nothing)
                 .unwrap();
 
-        #[cfg(feature = "gte-vsscript-api-31")]
-        let (node, alpha_node) = {
+        let vsscript::Output {
+            node,
+            alpha: alpha_node,
+        } = {
             let output = env.get_output(0);
             assert!(output.is_ok());
             output.unwrap()
         };
-        #[cfg(not(feature = "gte-vsscript-api-31"))]
-        let (node, alpha_node) = (env.get_output(0).unwrap(), None::<Node>);
 
         assert!(alpha_node.is_none());
 
@@ -534,321 +869,1536 @@ mod need_api_and_vsscript {
     }
 
     #[test]
-    fn core() {
+    fn get_all_frames_async() {
         let env =
             vsscript::Environment::from_file("test-vpy/green.vpy", vsscript::EvalFlags::Nothing)
                 .unwrap();
 
-        let core = env.get_core();
-        assert!(core.is_ok());
-        let core = core.unwrap();
+        let node = env.get_output(0).unwrap().node;
 
-        let yuv420p8 = core.get_format(PresetFormat::YUV420P8.into());
-        assert!(yuv420p8.is_some());
-        let yuv420p8 = yuv420p8.unwrap();
+        let frames = node.get_all_frames_async(0..10, Default::default());
+        assert_eq!(frames.len(), 10);
+        for frame in frames {
+            assert!(frame.is_ok());
+            green_frame_test(&frame.unwrap());
+        }
 
-        assert_eq!(yuv420p8.id(), PresetFormat::YUV420P8.into());
-        assert_eq!(yuv420p8.name(), "YUV420P8");
-        assert_eq!(yuv420p8.plane_count(), 3);
-        assert_eq!(yuv420p8.color_family(), ColorFamily::YUV);
-        assert_eq!(yuv420p8.sample_type(), SampleType::Integer);
-        assert_eq!(yuv420p8.bits_per_sample(), 8);
-        assert_eq!(yuv420p8.bytes_per_sample(), 1);
-        assert_eq!(yuv420p8.sub_sampling_w(), 1);
-        assert_eq!(yuv420p8.sub_sampling_h(), 1);
+        // The clip only has 100 frames, so the last request fails.
+        let frames = node.get_all_frames_async(98..101, Default::default());
+        assert_eq!(frames.len(), 3);
+        assert!(frames[0].is_ok());
+        assert!(frames[1].is_ok());
+        assert!(frames[2].is_err());
 
-        let yuv422p8 = core.get_format(PresetFormat::YUV422P8.into()).unwrap();
-        assert_eq!(yuv422p8.sub_sampling_w(), 1);
-        assert_eq!(yuv422p8.sub_sampling_h(), 0);
+        assert!(node
+            .get_all_frames_async(5..5, Default::default())
+            .is_empty());
 
-        #[cfg(feature = "gte-vapoursynth-api-36")]
-        {
-            assert_eq!(core.set_max_cache_size(1337), 1337);
-            assert_eq!(core.set_thread_count(3), 3);
-            assert_eq!(core.info().max_framebuffer_size, 1337);
-            assert_eq!(core.info().num_threads, 3);
+        // The frames of this clip change resolution at frame 100, which makes the order visible.
+        let env =
+            vsscript::Environment::from_file("test-vpy/variable.vpy", vsscript::EvalFlags::Nothing)
+                .unwrap();
+
+        let node = env.get_output(0).unwrap().node;
+
+        let frames = node.get_all_frames_async(90..110, Default::default());
+        for (i, frame) in frames.into_iter().enumerate() {
+            let width = if i < 10 { 1920 } else { 1280 };
+            assert_eq!(frame.unwrap().width(0), width);
         }
     }
 
     #[test]
-    fn plugins() {
+    fn frames_with_props() {
         let env =
             vsscript::Environment::from_file("test-vpy/green.vpy", vsscript::EvalFlags::Nothing)
                 .unwrap();
 
-        fn bind<'a, T: ?Sized, U: ?Sized>(_: &'a T, x: &'a U) -> &'a U {
-            x
-        }
-
-        let core = env.get_core().unwrap();
-        let plugins = core.plugins();
-        let ids: Vec<_> = plugins
-            .keys()
-            .filter_map(|key| unsafe {
-                bind(
-                    key,
-                    CStr::from_ptr(plugins.get_data(key).unwrap().as_ptr() as _),
-                )
-                .to_str()
-                .ok()
-            })
-            .filter_map(|id| id.split(';').nth(1))
-            .collect();
-        assert!(ids.contains(&"com.vapoursynth.std"));
-        assert!(ids.contains(&"com.vapoursynth.resize"));
-
-        let std = core.get_plugin_by_id("com.vapoursynth.std");
-        assert!(std.is_ok());
-        let std = std.unwrap();
-        assert!(std.is_some());
-        let std = std.unwrap();
+        let node = env.get_output(0).unwrap().node;
 
-        let functions = std.functions();
-        assert!(functions
-            .keys()
-            .filter_map(|key| unsafe {
-                bind(
-                    key,
-                    CStr::from_ptr(functions.get_data(key).unwrap().as_ptr() as _),
-                )
-                .to_str()
-                .ok()
-                .map(|value| (key, value))
-            })
-            .filter_map(|(key, value)| value.split(';').next().map(|name| (key, name)))
-            .any(|x| x == ("CropRel", "CropRel")));
+        let items: Vec<_> = node.frames_with_props(0..100, 8).collect();
+        assert_eq!(items.len(), 100);
+        for item in items {
+            let (frame, props) = item.unwrap();
+            green_frame_test(&frame);
+            assert_eq!(props.get_int("_DurationNum"), Ok(1));
+            assert_eq!(props.get_int("_DurationDen"), Ok(60));
+        }
 
-        #[cfg(feature = "gte-vsscript-api-31")]
-        let (node, _) = env.get_output(0).unwrap();
-        #[cfg(not(feature = "gte-vsscript-api-31"))]
-        let (node, _) = (env.get_output(0).unwrap(), None::<Node>);
+        // Dropping the iterator early is fine.
+        assert_eq!(node.frames_with_props(0..100, 8).take(3).count(), 3);
 
-        let mut args = OwnedMap::new(API::get().unwrap());
-        args.set_node("clip", &node);
-        args.set_int("left", 100);
+        let mut iter = node.frames_with_props(98..101, 0);
+        assert!(iter.next().unwrap().is_ok());
+        assert!(iter.next().unwrap().is_ok());
+        assert!(iter.next().unwrap().is_err());
+        assert!(iter.next().is_none());
+    }
 
-        let rv = std.invoke("CropRel", &args).unwrap();
-        assert!(rv.error().is_none());
+    #[test]
+    fn temporal_mean() {
+        let env =
+            vsscript::Environment::from_file("test-vpy/green.vpy", vsscript::EvalFlags::Nothing)
+                .unwrap();
+        let core = env.get_core().unwrap();
 
-        let node = rv.get_node("clip");
-        assert!(node.is_ok());
-        let node = node.unwrap();
+        let node = env.get_output(0).unwrap().node;
 
-        let frame = node.get_frame(0).unwrap();
-        assert_eq!(
-            frame.resolution(0),
-            Resolution {
-                width: 1820,
-                height: 1080,
+        // The clip is constant, so the mean matches the input, including at the clip bounds.
+        for &(center, radius) in &[(0, 2), (50, 3), (99, 5), (10, 0)] {
+            for plane in 0..3 {
+                let frame = node.temporal_mean(core, center, radius, plane).unwrap();
+                green_frame_test(&frame);
             }
-        );
+        }
+
+        assert!(node.temporal_mean(core, 100, 1, 0).is_err());
+        assert!(node.temporal_mean(core, 0, 1, 3).is_err());
     }
 
     #[test]
-    fn functions() {
+    fn invoke_checked() {
         let env =
             vsscript::Environment::from_file("test-vpy/green.vpy", vsscript::EvalFlags::Nothing)
                 .unwrap();
-
         let core = env.get_core().unwrap();
         let api = API::get().unwrap();
 
-        let function = Function::new(api, core, |_api, _core, in_, out| {
-            assert_eq!(in_.get_int("hello").unwrap(), 1337);
-            out.set_int("there", 42).unwrap();
-        });
-
-        let mut in_ = OwnedMap::new(api);
-        let mut out = OwnedMap::new(api);
-        in_.set_int("hello", 1337).unwrap();
+        let green = env.get_output(0).unwrap().node;
 
-        function.call(&in_, &mut out);
+        let std = core
+            .get_plugin_by_id("com.vapoursynth.std")
+            .unwrap()
+            .unwrap();
 
-        assert!(out.error().is_none());
-        assert_eq!(out.get_int("there").unwrap(), 42);
+        let args = args! {
+            api;
+            "clip" => green,
+            "format" => PresetFormat::Gray8 as i64,
+            "width" => 640,
+        }
+        .unwrap();
+        let rv = std.invoke_checked("BlankClip", &args).unwrap();
+        let frame = rv.get_node("clip").unwrap().get_frame(0).unwrap();
+        assert_eq!(frame.format_id(), PresetFormat::Gray8.into());
+        assert_eq!(frame.resolution(0).width, 640);
+        assert_eq!(frame.resolution(0).height, 1080);
+
+        let args = args! { api; "clip" => green, "nonexistent" => 1 }.unwrap();
+        assert!(matches!(
+            std.invoke_checked("BlankClip", &args),
+            Err(plugin::InvokeError::Filter(_))
+        ));
+
+        assert!(matches!(
+            args! { api; "clip" => green, "" => 1 },
+            Err(map::Error::InvalidKey(_))
+        ));
     }
-}
 
-// We need either VSScript API 3.2 or the VapourSynth functions.
-#[cfg(any(
-    feature = "vapoursynth-functions",
-    all(feature = "vsscript-functions", feature = "gte-vsscript-api-32")
-))]
-mod need_api {
-    use std::ffi::CString;
-    use std::sync::mpsc::{channel, Sender};
-    use std::sync::Mutex;
+    #[test]
+    fn frames() {
+        let env =
+            vsscript::Environment::from_file("test-vpy/green.vpy", vsscript::EvalFlags::Nothing)
+                .unwrap();
+        let node = env.get_output(0).unwrap().node;
 
-    use super::*;
-    use prelude::*;
+        let frames = node.frames().unwrap();
+        assert_eq!(frames.len(), 100);
+
+        let mut count = 0;
+        for frame in frames {
+            green_frame_test(&frame.unwrap());
+            count += 1;
+        }
+        assert_eq!(count, 100);
+
+        let mut frames = node.frames().unwrap();
+        assert!(frames.nth(99).is_some());
+        assert_eq!(frames.len(), 0);
+        assert!(frames.next().is_none());
+    }
 
     #[test]
-    fn maps() {
+    fn frames_prefetched() {
+        let env =
+            vsscript::Environment::from_file("test-vpy/green.vpy", vsscript::EvalFlags::Nothing)
+                .unwrap();
+        let core = env.get_core().unwrap();
+        let node = env.get_output(0).unwrap().node;
+
+        let frames = node.frames_prefetched(core.info().num_threads).unwrap();
+        assert_eq!(frames.len(), 100);
+
+        let mut count = 0;
+        for frame in frames {
+            green_frame_test(&frame.unwrap());
+            count += 1;
+        }
+        assert_eq!(count, 100);
+
+        let mut frames = node.frames_prefetched(0).unwrap();
+        assert!(frames.next().unwrap().is_ok());
+        assert_eq!(frames.len(), 99);
+        assert!(frames.all(|frame| frame.is_ok()));
+    }
+
+    #[test]
+    fn chunks() {
+        let env =
+            vsscript::Environment::from_file("test-vpy/green.vpy", vsscript::EvalFlags::Nothing)
+                .unwrap();
+        let node = env.get_output(0).unwrap().node;
+
+        assert_eq!(node.chunks(30).unwrap(), [0..30, 30..60, 60..90, 90..100]);
+        assert_eq!(node.chunks(100).unwrap(), vec![0..100]);
+        assert_eq!(node.chunks(1000).unwrap(), vec![0..100]);
+
+        assert_eq!(
+            node.chunks_at_boundaries(30, &[95, 10, 25, 70, 0, 200])
+                .unwrap(),
+            [0..25, 25..55, 55..70, 70..95, 95..100]
+        );
+    }
+
+    #[test]
+    fn node_vec() {
+        let env =
+            vsscript::Environment::from_file("test-vpy/green.vpy", vsscript::EvalFlags::Nothing)
+                .unwrap();
+        let core = env.get_core().unwrap();
+
+        let green = env.get_output(0).unwrap().node;
+
+        let std = core
+            .get_plugin_by_id("com.vapoursynth.std")
+            .unwrap()
+            .unwrap();
+        let mut args = OwnedMap::new(API::get().unwrap());
+        args.set_node("clip", &green).unwrap();
+        args.append_float("color", 255.0).unwrap();
+        args.append_float("color", 0.0).unwrap();
+        args.append_float("color", 0.0).unwrap();
+        let red = std.invoke("BlankClip", &args).unwrap();
+        let red = red.get_node("clip").unwrap();
+
         let mut map = OwnedMap::new(API::get().unwrap());
+        map.append_node("clip", &red).unwrap();
+        map.append_node("clip", &green).unwrap();
+        map.append_node("clip", &red).unwrap();
 
-        assert_eq!(map.key_count(), 0);
+        let nodes = map.get_node_vec("clip").unwrap();
+        assert_eq!(nodes.len(), 3);
+
+        let first_pixel = |node: &Node| node.get_frame(0).unwrap().plane_row::<u8>(0, 0)[0];
+        assert_eq!(first_pixel(&nodes[0]), 255);
+        assert_eq!(first_pixel(&nodes[1]), 0);
+        assert_eq!(first_pixel(&nodes[2]), 255);
 
-        assert_eq!(map.touch("test_frame", ValueType::Frame), Ok(()));
-        assert_eq!(map.value_type("test_frame"), Ok(ValueType::Frame));
-        assert_eq!(map.value_count("test_frame"), Ok(0));
         assert_eq!(
-            map.append_int("test_frame", 42),
-            Err(map::Error::WrongValueType)
+            map.get_node_vec("missing").err(),
+            Some(map::Error::KeyNotFound)
         );
+    }
+
+    #[test]
+    fn durations() {
+        let env =
+            vsscript::Environment::from_file("test-vpy/green.vpy", vsscript::EvalFlags::Nothing)
+                .unwrap();
+
+        let node = env.get_output(0).unwrap().node;
+
+        assert_eq!(node.durations(0..10).unwrap(), vec![(1, 60); 10]);
+        assert!(node.durations(95..101).is_err());
+    }
+
+    #[test]
+    fn fetch_options() {
+        let env =
+            vsscript::Environment::from_file("test-vpy/variable.vpy", vsscript::EvalFlags::Nothing)
+                .unwrap();
+        let core = env.get_core().unwrap();
+
+        let node = env.get_output(0).unwrap().node;
+
         assert_eq!(
-            map.append("test_frame", &42),
-            Err(map::Error::WrongValueType)
+            node::FetchOptions::for_core(core).requests,
+            core.info().num_threads
         );
 
-        assert_eq!(map.set_int("i", 42), Ok(()));
-        assert_eq!(map.get_int("i"), Ok(42));
-        assert_eq!(map.append_int("i", 43), Ok(()));
-        assert_eq!(map.get_int("i"), Ok(42));
-        {
-            let iter = map.get_int_iter("i");
-            assert!(iter.is_ok());
-            let mut iter = iter.unwrap();
-            assert_eq!(iter.next(), Some(42));
-            assert_eq!(iter.next(), Some(43));
-            assert_eq!(iter.next(), None);
-        }
+        let mut reported = Vec::new();
+        let options = node::FetchOptions {
+            requests: 1,
+            progress: Some(Box::new(|done, total| reported.push((done, total)))),
+        };
 
-        assert_eq!(map.set("i", &42), Ok(()));
-        assert_eq!(map.get("i"), Ok(42));
-        assert_eq!(map.append("i", &43), Ok(()));
-        assert_eq!(map.get("i"), Ok(42));
-        {
-            let iter = map.get_iter::<i64>("i");
-            assert!(iter.is_ok());
-            let mut iter = iter.unwrap();
-            assert_eq!(iter.next(), Some(42));
-            assert_eq!(iter.next(), Some(43));
-            assert_eq!(iter.next(), None);
+        let frames = node.get_all_frames_async(90..110, options);
+        for (i, frame) in frames.into_iter().enumerate() {
+            let width = if i < 10 { 1920 } else { 1280 };
+            assert_eq!(frame.unwrap().width(0), width);
         }
 
-        #[cfg(feature = "gte-vapoursynth-api-31")]
-        {
-            assert_eq!(map.get_int_array("i"), Ok(&[42, 43][..]));
+        assert_eq!(reported, (1..=20).map(|x| (x, 20)).collect::<Vec<_>>());
+    }
 
-            assert_eq!(map.set_int_array("ia", &[10, 20, 30]), Ok(()));
-            assert_eq!(map.get_int_array("ia"), Ok(&[10, 20, 30][..]));
-        }
+    #[test]
+    fn node_clones() {
+        let env =
+            vsscript::Environment::from_file("test-vpy/green.vpy", vsscript::EvalFlags::Nothing)
+                .unwrap();
 
-        assert_eq!(map.set_float("f", 42f64), Ok(()));
-        assert_eq!(map.get_float("f"), Ok(42f64));
-        assert_eq!(map.append_float("f", 43f64), Ok(()));
-        assert_eq!(map.get_float("f"), Ok(42f64));
-        {
-            let iter = map.get_float_iter("f");
-            assert!(iter.is_ok());
-            let mut iter = iter.unwrap();
-            assert_eq!(iter.next(), Some(42f64));
-            assert_eq!(iter.next(), Some(43f64));
-            assert_eq!(iter.next(), None);
+        let node = env.get_output(0).unwrap().node;
+
+        let resolution = node.info().resolution;
+        let (tx, rx) = channel();
+
+        // Every iteration creates two extra node references: the temporary clone, which is
+        // dropped before the frame is ready, and the one handed to the callback. Both must be
+        // released, otherwise the core complains about leaked filter instances when it's freed.
+        for i in 0..1000 {
+            let tx = tx.clone();
+
+            node.clone()
+                .get_frame_async(i % 100, move |frame, n, node| {
+                    assert!(frame.is_ok());
+                    assert_eq!(n, i % 100);
+                    assert_eq!(node.info().resolution, resolution);
+
+                    assert_eq!(tx.send(()), Ok(()));
+                });
         }
 
-        assert_eq!(map.set("f", &42f64), Ok(()));
-        assert_eq!(map.get("f"), Ok(42f64));
-        assert_eq!(map.append("f", &43f64), Ok(()));
-        assert_eq!(map.get("f"), Ok(42f64));
-        {
-            let iter = map.get_iter::<f64>("f");
-            assert!(iter.is_ok());
-            let mut iter = iter.unwrap();
-            assert_eq!(iter.next(), Some(42f64));
-            assert_eq!(iter.next(), Some(43f64));
-            assert_eq!(iter.next(), None);
+        for _ in 0..1000 {
+            assert_eq!(rx.recv(), Ok(()));
         }
+    }
 
-        #[cfg(feature = "gte-vapoursynth-api-31")]
-        {
-            assert_eq!(map.get_float_array("f"), Ok(&[42f64, 43f64][..]));
+    #[test]
+    fn frame_refs_are_freed() {
+        let env =
+            vsscript::Environment::from_file("test-vpy/green.vpy", vsscript::EvalFlags::Nothing)
+                .unwrap();
+        let core = env.get_core().unwrap();
 
-            assert_eq!(map.set_float_array("fa", &[10f64, 20f64, 30f64]), Ok(()));
-            assert_eq!(map.get_float_array("fa"), Ok(&[10f64, 20f64, 30f64][..]));
+        let node = env.get_output(0).unwrap().node;
+
+        // Each frame is about 6 MB, so leaking even a fraction of them would blow way past the
+        // framebuffer cache limit.
+        const ITERATIONS: usize = 20_000;
+
+        for i in 0..ITERATIONS {
+            let frame = node.get_frame(i % 100).unwrap();
+            let _clone = frame.clone();
         }
 
-        assert_eq!(map.set_data("d", &[1, 2, 3]), Ok(()));
-        assert_eq!(map.get_data("d"), Ok(&[1, 2, 3][..]));
-        assert_eq!(map.append_data("d", &[4, 5, 6]), Ok(()));
-        assert_eq!(map.get_data("d"), Ok(&[1, 2, 3][..]));
-        {
-            let iter = map.get_data_iter("d");
-            assert!(iter.is_ok());
-            let mut iter = iter.unwrap();
-            assert_eq!(iter.next(), Some(&[1, 2, 3][..]));
-            assert_eq!(iter.next(), Some(&[4, 5, 6][..]));
-            assert_eq!(iter.next(), None);
+        // Error paths must not leak either.
+        for _ in 0..ITERATIONS / 10 {
+            assert!(node.get_frame(100).is_err());
         }
 
-        assert_eq!(map.set("d", &&[1, 2, 3][..]), Ok(()));
-        assert_eq!(map.get("d"), Ok(&[1, 2, 3][..]));
-        assert_eq!(map.append("d", &&[4, 5, 6][..]), Ok(()));
-        assert_eq!(map.get("d"), Ok(&[1, 2, 3][..]));
+        let (tx, rx) = channel();
+        for i in 0..ITERATIONS {
+            let tx = tx.clone();
+
+            // The frame is dropped right away without being looked at.
+            node.get_frame_async(i % 101, move |_, _, _| {
+                assert_eq!(tx.send(()), Ok(()));
+            });
+        }
+        for _ in 0..ITERATIONS {
+            assert_eq!(rx.recv(), Ok(()));
+        }
+
+        let info = core.info();
+        assert!(info.used_framebuffer_size <= info.max_framebuffer_size);
+    }
+
+    #[test]
+    fn node_flags() {
+        let env =
+            vsscript::Environment::from_file("test-vpy/green.vpy", vsscript::EvalFlags::Nothing)
+                .unwrap();
+
+        let node = env.get_output(0).unwrap().node;
+
+        assert_eq!(node.format().map(|f| f.name()), Some("RGB24"));
+        assert_eq!(node.plane_count(), Some(3));
+        assert_eq!(node.get_frame(0).unwrap().plane_count(), 3);
+        assert_eq!(node.frame_layout().map(|l| l.size), Some(1920 * 1080 * 3));
+
+        // Filters are automatically followed by a Cache instance.
+        assert!(node.is_cache_node());
+        #[cfg(feature = "gte-vapoursynth-api-33")]
+        assert!(!node.wants_linear_access());
+
+        let mut info = node.info();
+        info.flags = node::Flags::empty();
+        assert!(!info.is_cache_node());
+
+        #[cfg(feature = "gte-vapoursynth-api-33")]
         {
-            let iter = map.get_iter::<&[u8]>("d");
-            assert!(iter.is_ok());
-            let mut iter = iter.unwrap();
-            assert_eq!(iter.next(), Some(&[1, 2, 3][..]));
-            assert_eq!(iter.next(), Some(&[4, 5, 6][..]));
-            assert_eq!(iter.next(), None);
+            info.flags = node::Flags::MAKE_LINEAR;
+            assert!(info.wants_linear_access());
+            assert!(!info.is_cache_node());
         }
+    }
 
-        // TODO: node, frame and function method tests when we can make them.
+    #[test]
+    fn reserved_props() {
+        let env =
+            vsscript::Environment::from_file("test-vpy/green.vpy", vsscript::EvalFlags::Nothing)
+                .unwrap();
+        let core = env.get_core().unwrap();
 
-        assert_eq!(map.delete_key("test_frame"), Ok(()));
-        assert_eq!(map.delete_key("test_frame"), Err(map::Error::KeyNotFound));
+        let node = env.get_output(0).unwrap().node;
 
-        assert_eq!(map.error(), None);
-        assert_eq!(map.set_error("hello there"), Ok(()));
+        let frame = node.get_frame(0).unwrap();
+        assert_eq!(frame.props().sample_aspect_ratio(), None);
+        assert_eq!(frame.props().duration(), Some((1, 60)));
+
+        let mut frame = FrameRefMut::copy_of(core, &frame);
+        frame.props_mut().set_sample_aspect_ratio(Some((40, 33)));
+        assert_eq!(frame.props().get_int("_SARNum"), Ok(40));
+        assert_eq!(frame.props().get_int("_SARDen"), Ok(33));
+        assert_eq!(frame.props().sample_aspect_ratio(), Some((40, 33)));
+
+        frame.props_mut().set_sample_aspect_ratio(None);
+        assert_eq!(frame.props().sample_aspect_ratio(), None);
+        assert!(!frame.props().contains_key("_SARNum"));
+
+        frame.props_mut().set_duration(Some((1001, 24000)));
+        assert_eq!(frame.props().duration(), Some((1001, 24000)));
+        frame.props_mut().set_duration(None);
+        assert_eq!(frame.props().duration(), None);
+
+        let bt709 = map::ColorSpace {
+            matrix: Some(1),
+            primaries: Some(1),
+            transfer: Some(1),
+            range: Some(1),
+        };
+        frame.props_mut().set_color_space(bt709);
+        assert_eq!(frame.props().get_int("_Matrix"), Ok(1));
+        assert_eq!(frame.props().get_int("_ColorRange"), Ok(1));
+        assert_eq!(frame.props().color_space(), bt709);
+
+        let partial = map::ColorSpace {
+            matrix: Some(9),
+            ..Default::default()
+        };
+        frame.props_mut().set_color_space(partial);
+        assert_eq!(frame.props().color_space(), partial);
+        assert!(!frame.props().contains_key("_Primaries"));
+    }
+
+    #[test]
+    fn standard_metadata() {
+        let env =
+            vsscript::Environment::from_file("test-vpy/green.vpy", vsscript::EvalFlags::Nothing)
+                .unwrap();
+        let core = env.get_core().unwrap();
+
+        let node = env.get_output(0).unwrap().node;
+
+        let frame = node.get_frame(0).unwrap();
         assert_eq!(
-            map.error().as_ref().map(|x| x.as_ref()),
-            Some("hello there")
+            frame.standard_metadata(),
+            map::StandardMetadata {
+                duration: Some((1, 60)),
+                ..Default::default()
+            }
         );
+
+        let mut frame = FrameRefMut::copy_of(core, &frame);
+        frame.props_mut().set_int("_FieldBased", 2).unwrap();
+        frame.props_mut().set_int("_ChromaLocation", 0).unwrap();
+        frame.props_mut().set_data("_PictType", b"I").unwrap();
+
+        let metadata = frame.standard_metadata();
+        assert_eq!(metadata.field_based, Some(2));
+        assert_eq!(metadata.chroma_location, Some(0));
+        assert_eq!(metadata.pict_type, Some('I'));
+        assert_eq!(metadata.field, None);
+        assert_eq!(metadata.sample_aspect_ratio, None);
+        assert_eq!(metadata.color_space, map::ColorSpace::default());
     }
 
-    // This test is commented out because it currently deadlocks due to
-    // https://github.com/vapoursynth/vapoursynth/issues/507
-    //
-    // Uncomment when R48 comes out (that's where this is fixed).
-    //
-    // #[cfg(feature = "gte-vapoursynth-api-34")]
-    // #[test]
-    // fn message_handler() {
-    //     let api = API::get().unwrap();
-    //     let (tx, rx) = channel();
-    //
-    //     // Hopefully no one logs anything here and breaks the test.
-    //     api.set_message_handler(move |message_type, message| {
-    //         assert_eq!(tx.send((message_type, message.to_owned())), Ok(()));
-    //     });
-    //
-    //     assert_eq!(
-    //         api.log(MessageType::Warning, "test warning message"),
-    //         Ok(())
-    //     );
-    //     assert_eq!(
-    //         rx.recv(),
-    //         Ok((
-    //             MessageType::Warning,
-    //             CString::new("test warning message").unwrap()
-    //         ))
-    //     );
-    //
-    //     assert_eq!(api.log(MessageType::Debug, "test debug message"), Ok(()));
-    //     assert_eq!(
-    //         rx.recv(),
-    //         Ok((
-    //             MessageType::Debug,
-    //             CString::new("test debug message").unwrap()
-    //         ))
-    //     );
-    //
-    //     assert_eq!(
-    //         api.log(MessageType::Critical, "test critical message"),
-    //         Ok(())
-    //     );
-    //     assert_eq!(
+    #[test]
+    fn core() {
+        let env =
+            vsscript::Environment::from_file("test-vpy/green.vpy", vsscript::EvalFlags::Nothing)
+                .unwrap();
+
+        let core = env.get_core();
+        assert!(core.is_ok());
+        let core = core.unwrap();
+
+        let yuv420p8 = core.get_format(PresetFormat::YUV420P8.into());
+        assert!(yuv420p8.is_some());
+        let yuv420p8 = yuv420p8.unwrap();
+
+        assert_eq!(yuv420p8.id(), PresetFormat::YUV420P8.into());
+        assert_eq!(yuv420p8.name(), "YUV420P8");
+        assert_eq!(yuv420p8.plane_count(), 3);
+        assert_eq!(yuv420p8.color_family(), ColorFamily::YUV);
+        assert_eq!(yuv420p8.sample_type(), SampleType::Integer);
+        assert_eq!(yuv420p8.bits_per_sample(), 8);
+        assert_eq!(yuv420p8.bytes_per_sample(), 1);
+        assert_eq!(yuv420p8.sub_sampling_w(), 1);
+        assert_eq!(yuv420p8.sub_sampling_h(), 1);
+
+        let yuv422p8 = core.get_format(PresetFormat::YUV422P8.into()).unwrap();
+        assert_eq!(yuv422p8.sub_sampling_w(), 1);
+        assert_eq!(yuv422p8.sub_sampling_h(), 0);
+
+        assert!(yuv420p8.is_yuv());
+        assert!(!yuv420p8.is_rgb());
+        assert!(!yuv420p8.is_gray());
+        assert!(yuv420p8.has_chroma());
+        assert!(yuv420p8.is_subsampled());
+        assert!(yuv422p8.is_subsampled());
+
+        let rgb24 = core.get_format(PresetFormat::RGB24.into()).unwrap();
+        assert!(rgb24.is_rgb());
+        assert!(!rgb24.is_yuv());
+        assert!(!rgb24.has_chroma());
+        assert!(!rgb24.is_subsampled());
+
+        let gray8 = core.get_format(PresetFormat::Gray8.into()).unwrap();
+        assert!(gray8.is_gray());
+        assert!(!gray8.has_chroma());
+        assert!(!gray8.is_subsampled());
+
+        #[cfg(feature = "gte-vapoursynth-api-36")]
+        {
+            assert_eq!(core.set_max_cache_size(1337), 1337);
+            assert_eq!(core.set_max_cache_size(0), 1337);
+            assert_eq!(core.set_thread_count(3), 3);
+            assert_eq!(core.info().max_framebuffer_size, 1337);
+            assert_eq!(core.info().num_threads, 3);
+        }
+    }
+
+    #[test]
+    fn get_frame_error_capacity() {
+        let env =
+            vsscript::Environment::from_file("test-vpy/green.vpy", vsscript::EvalFlags::Nothing)
+                .unwrap();
+        let core = env.get_core().unwrap();
+
+        let green = env.get_output(0).unwrap().node;
+
+        let message = format!("{}END", "x".repeat(64 * 1024));
+        let node = core
+            .frame_eval(&[green], {
+                let message = message.clone();
+                move |_, _| Err(anyhow::anyhow!("{}", message))
+            })
+            .unwrap();
+
+        let err = node.get_frame(0).unwrap_err().to_string();
+        assert!(err.len() < 32 * 1024);
+        assert!(!err.ends_with("END"));
+
+        let err = node
+            .get_frame_with_error_capacity(1, 128 * 1024)
+            .unwrap_err()
+            .to_string();
+        assert!(err.ends_with(&message));
+    }
+
+    #[test]
+    fn materialize() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let env =
+            vsscript::Environment::from_file("test-vpy/green.vpy", vsscript::EvalFlags::Nothing)
+                .unwrap();
+        let core = env.get_core().unwrap();
+
+        let green = env.get_output(0).unwrap().node;
+
+        let resolution = green.info().resolution;
+
+        // Count how many times the upstream graph generates a frame.
+        let generated = Arc::new(AtomicUsize::new(0));
+        let counting = core
+            .frame_eval(&[green], {
+                let generated = generated.clone();
+                move |_, frames| {
+                    generated.fetch_add(1, Ordering::SeqCst);
+                    Ok(frames[0].clone())
+                }
+            })
+            .unwrap();
+
+        let node = counting.materialize(core, 10..60).unwrap();
+        assert_eq!(generated.load(Ordering::SeqCst), 50);
+
+        let info = node.info();
+        #[cfg(feature = "gte-vapoursynth-api-32")]
+        assert_eq!(info.num_frames, 50);
+        #[cfg(not(feature = "gte-vapoursynth-api-32"))]
+        assert_eq!(info.num_frames, Property::Constant(50));
+        assert_eq!(info.resolution, resolution);
+
+        for _ in 0..2 {
+            for n in 0..50 {
+                green_frame_test(&node.get_frame(n).unwrap());
+            }
+        }
+        assert!(node.get_frame(50).is_err());
+        assert_eq!(generated.load(Ordering::SeqCst), 50);
+
+        assert!(counting.materialize(core, 0..0).is_err());
+        assert!(counting.materialize(core, 90..110).is_err());
+    }
+
+    #[test]
+    fn shared_filter_state() {
+        use std::sync::Arc;
+
+        use crate::core::CoreRef;
+        use anyhow::anyhow;
+        use plugins::{ffi, Filter, FrameContext};
+        use video_info::VideoInfo;
+
+        struct Config {
+            offset: i64,
+        }
+
+        struct Stamp<'core> {
+            source: Node<'core>,
+            config: Arc<Config>,
+        }
+
+        impl<'core> Filter<'core> for Stamp<'core> {
+            fn video_info(&self, _api: API, _core: CoreRef<'core>) -> Vec<VideoInfo<'core>> {
+                vec![self.source.info()]
+            }
+
+            fn get_frame_initial(
+                &self,
+                _api: API,
+                _core: CoreRef<'core>,
+                context: FrameContext,
+                n: usize,
+            ) -> Result<Option<FrameRef<'core>>, anyhow::Error> {
+                self.source.request_frame_filter(context, n);
+                Ok(None)
+            }
+
+            fn get_frame(
+                &self,
+                _api: API,
+                core: CoreRef<'core>,
+                context: FrameContext,
+                n: usize,
+            ) -> Result<FrameRef<'core>, anyhow::Error> {
+                let frame = self
+                    .source
+                    .get_frame_filter(context, n)
+                    .ok_or_else(|| anyhow!("Couldn't get the source frame"))?;
+
+                let mut frame = FrameRefMut::copy_of(core, &frame);
+                frame
+                    .props_mut()
+                    .set_int("Stamp", n as i64 + self.config.offset)?;
+                Ok(frame.into())
+            }
+        }
+
+        let env =
+            vsscript::Environment::from_file("test-vpy/green.vpy", vsscript::EvalFlags::Nothing)
+                .unwrap();
+        let core = env.get_core().unwrap();
+
+        let green = env.get_output(0).unwrap().node;
+
+        let config = Arc::new(Config { offset: 1000 });
+        let make_stamp = |source| {
+            let filter = Box::new(Stamp {
+                source,
+                config: config.clone(),
+            });
+            unsafe {
+                ffi::create_filter_node(
+                    core,
+                    CStr::from_bytes_with_nul(b"Stamp\0").unwrap(),
+                    node::Flags::empty(),
+                    filter,
+                )
+            }
+            .unwrap()
+        };
+
+        let node = make_stamp(make_stamp(green));
+        assert_eq!(Arc::strong_count(&config), 3);
+
+        // Both instances read the config from many threads at once.
+        let frames = node.get_all_frames_async(0..100, node::FetchOptions::for_core(core));
+        for (n, frame) in frames.into_iter().enumerate() {
+            let frame = frame.unwrap();
+            assert_eq!(frame.props().get_int("Stamp"), Ok(n as i64 + 1000));
+        }
+
+        drop(node);
+        assert_eq!(Arc::strong_count(&config), 1);
+    }
+
+    #[test]
+    fn release_frame_early() {
+        use crate::core::CoreRef;
+        use anyhow::anyhow;
+        use plugins::{ffi, Filter, FrameContext};
+        use video_info::VideoInfo;
+
+        // Requests two frames but only ever uses the first one.
+        struct Speculative<'core> {
+            source: Node<'core>,
+        }
+
+        impl<'core> Filter<'core> for Speculative<'core> {
+            fn video_info(&self, _api: API, _core: CoreRef<'core>) -> Vec<VideoInfo<'core>> {
+                vec![self.source.info()]
+            }
+
+            fn get_frame_initial(
+                &self,
+                _api: API,
+                _core: CoreRef<'core>,
+                context: FrameContext,
+                n: usize,
+            ) -> Result<Option<FrameRef<'core>>, anyhow::Error> {
+                self.source.request_frame_filter(context, n);
+                self.source.request_frame_filter(context, (n + 1).min(99));
+                Ok(None)
+            }
+
+            fn get_frame(
+                &self,
+                _api: API,
+                _core: CoreRef<'core>,
+                context: FrameContext,
+                n: usize,
+            ) -> Result<FrameRef<'core>, anyhow::Error> {
+                if n < 99 {
+                    context.release_frame_early(&self.source, n + 1);
+                }
+
+                self.source
+                    .get_frame_filter(context, n)
+                    .ok_or_else(|| anyhow!("Couldn't get the source frame"))
+            }
+        }
+
+        let env =
+            vsscript::Environment::from_file("test-vpy/green.vpy", vsscript::EvalFlags::Nothing)
+                .unwrap();
+        let core = env.get_core().unwrap();
+
+        let green = env.get_output(0).unwrap().node;
+
+        let filter = Box::new(Speculative { source: green });
+        let node = unsafe {
+            ffi::create_filter_node(
+                core,
+                CStr::from_bytes_with_nul(b"Speculative\0").unwrap(),
+                node::Flags::empty(),
+                filter,
+            )
+        }
+        .unwrap();
+
+        let frames = node.get_all_frames_async(0..100, node::FetchOptions::for_core(core));
+        for frame in frames {
+            green_frame_test(&frame.unwrap());
+        }
+    }
+
+    #[test]
+    fn completed_frame() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        use crate::core::CoreRef;
+        use anyhow::{anyhow, ensure};
+        use plugins::{ffi, Filter, FrameContext};
+        use video_info::VideoInfo;
+
+        struct Check<'core> {
+            source: Node<'core>,
+            checked: Arc<AtomicUsize>,
+        }
+
+        impl<'core> Filter<'core> for Check<'core> {
+            fn video_info(&self, _api: API, _core: CoreRef<'core>) -> Vec<VideoInfo<'core>> {
+                vec![self.source.info()]
+            }
+
+            fn get_frame_initial(
+                &self,
+                _api: API,
+                core: CoreRef<'core>,
+                context: FrameContext,
+                n: usize,
+            ) -> Result<Option<FrameRef<'core>>, anyhow::Error> {
+                ensure!(context.completed_frame(core).is_none());
+                self.source.request_frame_filter(context, n);
+                Ok(None)
+            }
+
+            fn get_frame(
+                &self,
+                _api: API,
+                core: CoreRef<'core>,
+                context: FrameContext,
+                n: usize,
+            ) -> Result<FrameRef<'core>, anyhow::Error> {
+                let (node, completed) = context
+                    .completed_frame(core)
+                    .ok_or_else(|| anyhow!("No completed frame"))?;
+                ensure!(completed == n);
+                ensure!(node.info().num_frames == self.source.info().num_frames);
+                self.checked.fetch_add(1, Ordering::SeqCst);
+
+                self.source
+                    .get_frame_filter(context, n)
+                    .ok_or_else(|| anyhow!("Couldn't get the source frame"))
+            }
+        }
+
+        let env =
+            vsscript::Environment::from_file("test-vpy/green.vpy", vsscript::EvalFlags::Nothing)
+                .unwrap();
+        let core = env.get_core().unwrap();
+
+        let green = env.get_output(0).unwrap().node;
+
+        let checked = Arc::new(AtomicUsize::new(0));
+        let filter = Box::new(Check {
+            source: green,
+            checked: checked.clone(),
+        });
+        let node = unsafe {
+            ffi::create_filter_node(
+                core,
+                CStr::from_bytes_with_nul(b"Check\0").unwrap(),
+                node::Flags::NO_CACHE,
+                filter,
+            )
+        }
+        .unwrap();
+
+        for frame in node.get_all_frames_async(0..10, node::FetchOptions::for_core(core)) {
+            green_frame_test(&frame.unwrap());
+        }
+        assert_eq!(checked.load(Ordering::SeqCst), 10);
+    }
+
+    #[test]
+    fn core_info() {
+        let env =
+            vsscript::Environment::from_file("test-vpy/green.vpy", vsscript::EvalFlags::Nothing)
+                .unwrap();
+        let info = env.get_core().unwrap().info();
+
+        let summary = format!("{}", info);
+        assert!(summary.starts_with(info.version_string));
+        assert!(summary.contains(&format!("Worker threads: {}\n", info.num_threads)));
+        assert!(summary.contains(&format!(
+            "Max framebuffer cache size: {}\n",
+            info.max_framebuffer_size
+        )));
+
+        let info = core::Info {
+            max_framebuffer_size: 200,
+            used_framebuffer_size: 50,
+            ..info
+        };
+        assert_eq!(info.memory_usage_ratio(), 0.25);
+        assert!(format!("{}", info).contains("Current framebuffer cache size: 50 (25.0%)\n"));
+
+        let info = core::Info {
+            max_framebuffer_size: 0,
+            ..info
+        };
+        assert_eq!(info.memory_usage_ratio(), 0.);
+    }
+
+    #[test]
+    fn plugins() {
+        let env =
+            vsscript::Environment::from_file("test-vpy/green.vpy", vsscript::EvalFlags::Nothing)
+                .unwrap();
+
+        let core = env.get_core().unwrap();
+        let plugins = core.plugins();
+        let ids: Vec<_> = plugins
+            .keys()
+            .filter_map(|key| plugins.get_data_str(key).ok())
+            .filter_map(|id| id.split(';').nth(1))
+            .collect();
+        assert!(ids.contains(&"com.vapoursynth.std"));
+        assert!(ids.contains(&"com.vapoursynth.resize"));
+
+        let std = core.get_plugin_by_id("com.vapoursynth.std");
+        assert!(std.is_ok());
+        let std = std.unwrap();
+        assert!(std.is_some());
+        let std = std.unwrap();
+
+        let functions = std.functions();
+        assert!(functions
+            .keys()
+            .filter_map(|key| functions.get_data_str(key).ok().map(|value| (key, value)))
+            .filter_map(|(key, value)| value.split(';').next().map(|name| (key, name)))
+            .any(|x| x == ("CropRel", "CropRel")));
+
+        let vsscript::Output { node, alpha: _ } = env.get_output(0).unwrap();
+
+        let mut args = OwnedMap::new(API::get().unwrap());
+        args.set_node("clip", &node);
+        args.set_int("left", 100);
+
+        let rv = std.invoke("CropRel", &args).unwrap();
+        assert!(rv.error().is_none());
+
+        let node = rv.get_node("clip");
+        assert!(node.is_ok());
+        let node = node.unwrap();
+
+        let frame = node.get_frame(0).unwrap();
+        assert_eq!(
+            frame.resolution(0),
+            Resolution {
+                width: 1820,
+                height: 1080,
+            }
+        );
+    }
+
+    #[test]
+    fn functions() {
+        let env =
+            vsscript::Environment::from_file("test-vpy/green.vpy", vsscript::EvalFlags::Nothing)
+                .unwrap();
+
+        let core = env.get_core().unwrap();
+        let api = API::get().unwrap();
+
+        let function = Function::new(api, core, |_api, _core, in_, out| {
+            assert_eq!(in_.get_int("hello").unwrap(), 1337);
+            out.set_int("there", 42).unwrap();
+        });
+
+        let mut in_ = OwnedMap::new(api);
+        let mut out = OwnedMap::new(api);
+        in_.set_int("hello", 1337).unwrap();
+
+        function.call(&in_, &mut out);
+
+        assert!(out.error().is_none());
+        assert_eq!(out.get_int("there").unwrap(), 42);
+    }
+
+    #[test]
+    fn function_clone() {
+        let env =
+            vsscript::Environment::from_file("test-vpy/green.vpy", vsscript::EvalFlags::Nothing)
+                .unwrap();
+
+        let core = env.get_core().unwrap();
+        let api = API::get().unwrap();
+
+        let state = std::sync::Arc::new(std::sync::atomic::AtomicI64::new(0));
+        let function = Function::new(api, core, {
+            let state = state.clone();
+            move |_api, _core, in_, out| {
+                let x = in_.get_int("x").unwrap();
+                let total = state.fetch_add(x, std::sync::atomic::Ordering::SeqCst) + x;
+                out.set_int("total", total).unwrap();
+            }
+        });
+        let copy = function.clone();
+
+        // The copy can be stored and called on another thread.
+        let total = std::thread::scope(|s| {
+            s.spawn(move || {
+                let mut in_ = OwnedMap::new(api);
+                let mut out = OwnedMap::new(api);
+                in_.set_int("x", 2).unwrap();
+                copy.call(&in_, &mut out);
+                out.get_int("total").unwrap()
+            })
+            .join()
+            .unwrap()
+        });
+        assert_eq!(total, 2);
+
+        let mut in_ = OwnedMap::new(api);
+        let mut out = OwnedMap::new(api);
+        in_.set_int("x", 3).unwrap();
+        function.call(&in_, &mut out);
+        assert_eq!(out.get_int("total"), Ok(5));
+
+        // The callback is freed exactly once, after the last reference is gone.
+        assert_eq!(std::sync::Arc::strong_count(&state), 2);
+        drop(function);
+        assert_eq!(std::sync::Arc::strong_count(&state), 1);
+    }
+
+    #[test]
+    fn is_constant_image() {
+        let env =
+            vsscript::Environment::from_file("test-vpy/green.vpy", vsscript::EvalFlags::Nothing)
+                .unwrap();
+        let core = env.get_core().unwrap();
+
+        let green = env.get_output(0).unwrap().node;
+
+        assert!(green.is_constant_image(&[0, 50, 99]).unwrap());
+        assert!(green.is_constant_image(&[]).unwrap());
+        assert!(green.is_constant_image(&[0, 100]).is_err());
+
+        let std = core
+            .get_plugin_by_id("com.vapoursynth.std")
+            .unwrap()
+            .unwrap();
+        let mut args = OwnedMap::new(API::get().unwrap());
+        args.set_node("clip", &green).unwrap();
+        args.append_float("color", 255.0).unwrap();
+        args.append_float("color", 0.0).unwrap();
+        args.append_float("color", 0.0).unwrap();
+        let red = std.invoke("BlankClip", &args).unwrap();
+        let red = red.get_node("clip").unwrap();
+
+        // Alternates between green and red frames.
+        let node = core
+            .frame_eval(&[green, red], |n, frames| Ok(frames[n % 2].clone()))
+            .unwrap();
+        assert!(node.is_constant_image(&[0, 2, 4]).unwrap());
+        assert!(!node.is_constant_image(&[0, 1]).unwrap());
+    }
+
+    #[test]
+    fn get_frame_future() {
+        use std::future::Future;
+        use std::pin::pin;
+        use std::sync::Arc;
+        use std::task::{Context, Poll, Wake};
+        use std::thread::{self, Thread};
+
+        struct ThreadWaker(Thread);
+
+        impl Wake for ThreadWaker {
+            fn wake(self: Arc<Self>) {
+                self.0.unpark();
+            }
+        }
+
+        fn block_on<F: Future>(future: F) -> F::Output {
+            let mut future = pin!(future);
+            let waker = Arc::new(ThreadWaker(thread::current())).into();
+            let mut cx = Context::from_waker(&waker);
+
+            loop {
+                match future.as_mut().poll(&mut cx) {
+                    Poll::Ready(x) => return x,
+                    Poll::Pending => thread::park(),
+                }
+            }
+        }
+
+        let env =
+            vsscript::Environment::from_file("test-vpy/green.vpy", vsscript::EvalFlags::Nothing)
+                .unwrap();
+        let green = env.get_output(0).unwrap().node;
+
+        let frame = block_on(green.get_frame_future(0)).unwrap();
+        green_frame_test(&frame);
+
+        let frames = block_on(async {
+            let first = green.get_frame_future(1);
+            let second = green.get_frame_future(2);
+            (first.await, second.await)
+        });
+        green_frame_test(&frames.0.unwrap());
+        green_frame_test(&frames.1.unwrap());
+
+        assert!(block_on(green.get_frame_future(100)).is_err());
+    }
+
+    #[test]
+    fn request_frame_timeout() {
+        use std::thread;
+        use std::time::Duration;
+
+        let env =
+            vsscript::Environment::from_file("test-vpy/green.vpy", vsscript::EvalFlags::Nothing)
+                .unwrap();
+        let core = env.get_core().unwrap();
+        let green = env.get_output(0).unwrap().node;
+
+        let request = green.request_frame(0);
+        assert_eq!(request.frame_number(), 0);
+        let frame = request.wait_timeout(Duration::from_secs(10)).unwrap();
+        green_frame_test(&frame);
+
+        assert!(matches!(
+            green
+                .request_frame(100)
+                .wait_timeout(Duration::from_secs(10)),
+            Err(node::WaitError::GetFrame(_))
+        ));
+
+        let slow = core
+            .frame_eval(std::slice::from_ref(&green), |_, frames| {
+                thread::sleep(Duration::from_millis(500));
+                Ok(frames[0].clone())
+            })
+            .unwrap();
+        assert!(matches!(
+            slow.request_frame(0)
+                .wait_timeout(Duration::from_millis(10)),
+            Err(node::WaitError::Timeout)
+        ));
+
+        // Wait for the abandoned request to finish before tearing down the environment.
+        green_frame_test(&slow.request_frame(0).wait().unwrap());
+    }
+
+    #[test]
+    fn write_y4m() {
+        let env =
+            vsscript::Environment::from_file("test-vpy/green.vpy", vsscript::EvalFlags::Nothing)
+                .unwrap();
+        let core = env.get_core().unwrap();
+
+        let green = env.get_output(0).unwrap().node;
+
+        // y4m has no RGB colorspaces.
+        let mut buf = Vec::new();
+        assert!(green.write_y4m(&mut buf, 0..3, 2).is_err());
+        assert!(buf.is_empty());
+
+        let std = core
+            .get_plugin_by_id("com.vapoursynth.std")
+            .unwrap()
+            .unwrap();
+        let mut args = OwnedMap::new(API::get().unwrap());
+        args.set_node("clip", &green).unwrap();
+        args.set_int("format", PresetFormat::YUV420P8 as i64)
+            .unwrap();
+        let yuv = std.invoke("BlankClip", &args).unwrap();
+        let yuv = yuv.get_node("clip").unwrap();
+
+        yuv.write_y4m(&mut buf, 0..3, 2).unwrap();
+
+        let header = b"YUV4MPEG2 C420 W1920 H1080 F60:1 Ip A0:0 XLENGTH=3\nFRAME\n";
+        assert_eq!(&buf[..header.len()], &header[..]);
+
+        let frame_size = b"FRAME\n".len() + 1920 * 1080 * 3 / 2;
+        assert_eq!(buf.len(), header.len() - b"FRAME\n".len() + frame_size * 3);
+        assert_eq!(
+            &buf[header.len() - b"FRAME\n".len() + frame_size..][..6],
+            b"FRAME\n"
+        );
+    }
+
+    #[test]
+    fn to_planar() {
+        let env =
+            vsscript::Environment::from_file("test-vpy/green.vpy", vsscript::EvalFlags::Nothing)
+                .unwrap();
+        let core = env.get_core().unwrap();
+
+        let green = env.get_output(0).unwrap().node;
+        let planar = green.to_planar(core).unwrap();
+        assert_eq!(planar.info().format, green.info().format);
+
+        let std = core
+            .get_plugin_by_id("com.vapoursynth.std")
+            .unwrap()
+            .unwrap();
+
+        for &(compat, expected) in &[
+            (PresetFormat::CompatBGR32, PresetFormat::RGB24),
+            (PresetFormat::CompatYUY2, PresetFormat::YUV422P8),
+        ] {
+            let mut args = OwnedMap::new(API::get().unwrap());
+            args.set_node("clip", &green).unwrap();
+            args.set_int("format", compat as i64).unwrap();
+            let packed = std.invoke("BlankClip", &args).unwrap();
+            let packed = packed.get_node("clip").unwrap();
+
+            let format = match packed.info().format {
+                Property::Constant(x) => x,
+                Property::Variable => unreachable!(),
+            };
+            assert!(format.is_packed());
+
+            let planar = packed.to_planar(core).unwrap();
+            let format = match planar.info().format {
+                Property::Constant(x) => x,
+                Property::Variable => unreachable!(),
+            };
+            assert!(!format.is_packed());
+            assert_eq!(format.id(), expected.into());
+
+            let frame = planar.get_frame(0).unwrap();
+            assert_eq!(frame.format(), format);
+            assert_eq!(frame.width(0), 1920);
+            assert_eq!(frame.height(0), 1080);
+        }
+    }
+
+    #[test]
+    fn frame_eval() {
+        let env =
+            vsscript::Environment::from_file("test-vpy/green.vpy", vsscript::EvalFlags::Nothing)
+                .unwrap();
+        let core = env.get_core().unwrap();
+
+        let green = env.get_output(0).unwrap().node;
+
+        let std = core
+            .get_plugin_by_id("com.vapoursynth.std")
+            .unwrap()
+            .unwrap();
+        let mut args = OwnedMap::new(API::get().unwrap());
+        args.set_node("clip", &green).unwrap();
+        args.append_float("color", 255.0).unwrap();
+        args.append_float("color", 0.0).unwrap();
+        args.append_float("color", 0.0).unwrap();
+        let red = std.invoke("BlankClip", &args).unwrap();
+        assert_eq!(red.error(), None);
+        let red = red.get_node("clip").unwrap();
+
+        let node = core
+            .frame_eval(&[green, red], |n, frames| {
+                assert_eq!(frames.len(), 2);
+                Ok(frames[n % 2].clone())
+            })
+            .unwrap();
+        #[cfg(feature = "gte-vapoursynth-api-32")]
+        assert_eq!(node.info().num_frames, 100);
+        #[cfg(not(feature = "gte-vapoursynth-api-32"))]
+        assert_eq!(node.info().num_frames, Property::Constant(100));
+
+        for n in 0..4 {
+            let frame = node.get_frame(n).unwrap();
+            let lit_plane = if n % 2 == 0 { 1 } else { 0 };
+
+            for plane in 0..3 {
+                let expected = if plane == lit_plane { 255 } else { 0 };
+                assert!(frame
+                    .plane_row::<u8>(plane, 0)
+                    .iter()
+                    .all(|&x| x == expected));
+            }
+        }
+
+        let node = core
+            .frame_eval(&[node], |_, _| Err(anyhow::anyhow!("no frames today")))
+            .unwrap();
+        let err = node.get_frame(0).unwrap_err();
+        assert!(err.to_string().contains("no frames today"));
+
+        assert!(core
+            .frame_eval(&[], |_, frames| Ok(frames[0].clone()))
+            .is_err());
+    }
+}
+
+// We need either VSScript API 3.2 or the VapourSynth functions.
+#[cfg(any(
+    feature = "vapoursynth-functions",
+    all(feature = "vsscript-functions", feature = "gte-vsscript-api-32")
+))]
+mod need_api {
+    use std::ffi::{CStr, CString};
+    use std::sync::mpsc::{channel, Sender};
+    use std::sync::Mutex;
+
+    use super::*;
+    use prelude::*;
+    use video_info::Resolution;
+
+    #[test]
+    fn maps() {
+        let mut map = OwnedMap::new(API::get().unwrap());
+
+        assert_eq!(map.key_count(), 0);
+
+        assert_eq!(map.touch("test_frame", ValueType::Frame), Ok(()));
+        assert_eq!(map.value_type("test_frame"), Ok(ValueType::Frame));
+        assert_eq!(map.value_count("test_frame"), Ok(0));
+        assert_eq!(
+            map.append_int("test_frame", 42),
+            Err(map::Error::WrongValueType)
+        );
+        assert_eq!(
+            map.append("test_frame", &42),
+            Err(map::Error::WrongValueType)
+        );
+
+        assert_eq!(map.set_int("i", 42), Ok(()));
+        assert_eq!(map.get_int("i"), Ok(42));
+        assert_eq!(map.append_int("i", 43), Ok(()));
+        assert_eq!(map.get_int("i"), Ok(42));
+        {
+            let iter = map.get_int_iter("i");
+            assert!(iter.is_ok());
+            let mut iter = iter.unwrap();
+            assert_eq!(iter.next(), Some(42));
+            assert_eq!(iter.next(), Some(43));
+            assert_eq!(iter.next(), None);
+        }
+
+        assert_eq!(map.set("i", &42), Ok(()));
+        assert_eq!(map.get("i"), Ok(42));
+        assert_eq!(map.append("i", &43), Ok(()));
+        assert_eq!(map.get("i"), Ok(42));
+        {
+            let iter = map.get_iter::<i64>("i");
+            assert!(iter.is_ok());
+            let mut iter = iter.unwrap();
+            assert_eq!(iter.next(), Some(42));
+            assert_eq!(iter.next(), Some(43));
+            assert_eq!(iter.next(), None);
+        }
+
+        #[cfg(feature = "gte-vapoursynth-api-31")]
+        {
+            assert_eq!(map.get_int_array("i"), Ok(&[42, 43][..]));
+
+            assert_eq!(map.set_int_array("ia", &[10, 20, 30]), Ok(()));
+            assert_eq!(map.get_int_array("ia"), Ok(&[10, 20, 30][..]));
+        }
+
+        assert_eq!(map.set_bool("b", true), Ok(()));
+        assert_eq!(map.get_int("b"), Ok(1));
+        assert_eq!(map.get_bool("b"), Ok(true));
+        assert_eq!(map.append_bool("b", false), Ok(()));
+        assert_eq!(map.get_bool("b"), Ok(true));
+        assert_eq!(map.append_int("b", -5), Ok(()));
+        {
+            let iter = map.get_bool_iter("b");
+            assert!(iter.is_ok());
+            let mut iter = iter.unwrap();
+            assert_eq!(iter.next(), Some(true));
+            assert_eq!(iter.next(), Some(false));
+            assert_eq!(iter.next(), Some(true));
+            assert_eq!(iter.next(), None);
+        }
+
+        assert_eq!(map.set("b", &false), Ok(()));
+        assert_eq!(map.get::<bool>("b"), Ok(false));
+        assert_eq!(map.get_int("b"), Ok(0));
+        assert_eq!(map.append("b", &true), Ok(()));
+        assert_eq!(
+            map.get_iter::<bool>("b").unwrap().collect::<Vec<_>>(),
+            [false, true]
+        );
+        assert_eq!(map.get_bool("i"), Ok(true));
+        assert_eq!(map.get_bool("test_frame"), Err(map::Error::WrongValueType));
+
+        assert_eq!(map.set_float("f", 42f64), Ok(()));
+        assert_eq!(map.get_float("f"), Ok(42f64));
+        assert_eq!(map.append_float("f", 43f64), Ok(()));
+        assert_eq!(map.get_float("f"), Ok(42f64));
+        {
+            let iter = map.get_float_iter("f");
+            assert!(iter.is_ok());
+            let mut iter = iter.unwrap();
+            assert_eq!(iter.next(), Some(42f64));
+            assert_eq!(iter.next(), Some(43f64));
+            assert_eq!(iter.next(), None);
+        }
+
+        assert_eq!(map.set("f", &42f64), Ok(()));
+        assert_eq!(map.get("f"), Ok(42f64));
+        assert_eq!(map.append("f", &43f64), Ok(()));
+        assert_eq!(map.get("f"), Ok(42f64));
+        {
+            let iter = map.get_iter::<f64>("f");
+            assert!(iter.is_ok());
+            let mut iter = iter.unwrap();
+            assert_eq!(iter.next(), Some(42f64));
+            assert_eq!(iter.next(), Some(43f64));
+            assert_eq!(iter.next(), None);
+        }
+
+        #[cfg(feature = "gte-vapoursynth-api-31")]
+        {
+            assert_eq!(map.get_float_array("f"), Ok(&[42f64, 43f64][..]));
+
+            assert_eq!(map.set_float_array("fa", &[10f64, 20f64, 30f64]), Ok(()));
+            assert_eq!(map.get_float_array("fa"), Ok(&[10f64, 20f64, 30f64][..]));
+        }
+
+        assert_eq!(map.set_data("d", &[1, 2, 3]), Ok(()));
+        assert_eq!(map.get_data("d"), Ok(&[1, 2, 3][..]));
+        assert_eq!(map.append_data("d", &[4, 5, 6]), Ok(()));
+        assert_eq!(map.get_data("d"), Ok(&[1, 2, 3][..]));
+        {
+            let iter = map.get_data_iter("d");
+            assert!(iter.is_ok());
+            let mut iter = iter.unwrap();
+            assert_eq!(iter.next(), Some(&[1, 2, 3][..]));
+            assert_eq!(iter.next(), Some(&[4, 5, 6][..]));
+            assert_eq!(iter.next(), None);
+        }
+
+        assert_eq!(map.set_int_slice("is", &[1, 2, 3]), Ok(()));
+        assert_eq!(
+            map.get_int_iter("is").unwrap().collect::<Vec<_>>(),
+            [1, 2, 3]
+        );
+        assert_eq!(map.set_int_slice("is", &[4]), Ok(()));
+        assert_eq!(map.get_int_iter("is").unwrap().collect::<Vec<_>>(), [4]);
+        assert_eq!(map.set_float_slice("fs", &[0.5, 1.5]), Ok(()));
+        assert_eq!(
+            map.get_float_iter("fs").unwrap().collect::<Vec<_>>(),
+            [0.5, 1.5]
+        );
+        assert_eq!(map.set_float_slice("fs", &[]), Ok(()));
+        assert_eq!(map.value_type("fs"), Ok(ValueType::Float));
+        assert_eq!(map.value_count("fs"), Ok(0));
+        assert_eq!(map.set_int_slice("fs", &[1]), Ok(()));
+        assert_eq!(map.get_int("fs"), Ok(1));
+
+        assert_eq!(map.value_count_any(&["legacy", "i"]), Ok((1, 2)));
+        assert_eq!(map.value_count_any(&["i", "f"]), Ok((0, 2)));
+        assert_eq!(
+            map.value_count_any(&["legacy", "missing"]),
+            Err(map::Error::KeyNotFound)
+        );
+        assert_eq!(map.value_count_any(&[]), Err(map::Error::KeyNotFound));
+
+        assert_eq!(map.set_data_str("s", "hello"), Ok(()));
+        assert_eq!(map.get_data_str("s"), Ok("hello"));
+        assert_eq!(map.append_data_str("s", "world"), Ok(()));
+        assert_eq!(map.get_data_str("s"), Ok("hello"));
+        assert_eq!(map.get_data_iter("s").unwrap().nth(1), Some(&b"world"[..]));
+        assert_eq!(map.set_data("s", &[0xff, 0xfe]), Ok(()));
+        assert!(matches!(
+            map.get_data_str("s"),
+            Err(map::Error::InvalidUtf8(_))
+        ));
+        assert_eq!(map.get_data_str("i"), Err(map::Error::WrongValueType));
+
+        assert_eq!(map.set("d", &&[1, 2, 3][..]), Ok(()));
+        assert_eq!(map.get("d"), Ok(&[1, 2, 3][..]));
+        assert_eq!(map.append("d", &&[4, 5, 6][..]), Ok(()));
+        assert_eq!(map.get("d"), Ok(&[1, 2, 3][..]));
+        {
+            let iter = map.get_iter::<&[u8]>("d");
+            assert!(iter.is_ok());
+            let mut iter = iter.unwrap();
+            assert_eq!(iter.next(), Some(&[1, 2, 3][..]));
+            assert_eq!(iter.next(), Some(&[4, 5, 6][..]));
+            assert_eq!(iter.next(), None);
+        }
+
+        assert_eq!(map.get_int_or("i", 0), Ok(42));
+        assert_eq!(map.get_int_or("missing", 17), Ok(17));
+        assert_eq!(map.get_int_or("f", 17), Err(map::Error::WrongValueType));
+        assert_eq!(map.get_float_or("f", 0f64), Ok(42f64));
+        assert_eq!(map.get_float_or("missing", 17f64), Ok(17f64));
+        assert_eq!(
+            map.get_float_or("i", 17f64),
+            Err(map::Error::WrongValueType)
+        );
+        assert_eq!(map.get_data_or("d", &[]), Ok(&[1, 2, 3][..]));
+        assert_eq!(map.get_data_or("missing", &[7]), Ok(&[7][..]));
+        assert_eq!(map.get_data_or("i", &[7]), Err(map::Error::WrongValueType));
+        assert_eq!(
+            map.get_int_or("in valid", 17),
+            Err(map::Error::InvalidKey(
+                map::InvalidKeyError::InvalidCharacter(2)
+            ))
+        );
+
+        // TODO: node, frame and function method tests when we can make them.
+
+        assert!(map.contains_key("i"));
+        assert!(map.contains_key("test_frame"));
+        assert!(!map.contains_key("missing"));
+        assert!(!map.contains_key("in valid"));
+        assert!(!map.contains_key(""));
+        assert!(map.contains_key_cstr(&CString::new("i").unwrap()));
+        assert!(!map.contains_key_cstr(&CString::new("missing").unwrap()));
+        assert!(!map.contains_key_cstr(&CString::new("in valid").unwrap()));
+
+        let long_key = "a".repeat(100);
+        assert!(!map.contains_key(&long_key));
+        assert_eq!(map.set_int(&long_key, 1), Ok(()));
+        assert!(map.contains_key(&long_key));
+
+        assert_eq!(map.delete_key("test_frame"), Ok(()));
+        assert_eq!(map.delete_key("test_frame"), Err(map::Error::KeyNotFound));
+        assert!(!map.contains_key("test_frame"));
+
+        let index = map.keys().position(|key| key == "i").unwrap();
+        assert_eq!(map.try_key(index), Ok("i"));
+
+        assert_eq!(map.error(), None);
+        assert_eq!(map.set_error("hello there"), Ok(()));
+        assert_eq!(
+            map.error().as_ref().map(|x| x.as_ref()),
+            Some("hello there")
+        );
+        assert_eq!(map.take_error(), Some("hello there".to_owned()));
+        assert_eq!(map.error(), None);
+        assert_eq!(map.take_error(), None);
+    }
+
+    // This test is commented out because it currently deadlocks due to
+    // https://github.com/vapoursynth/vapoursynth/issues/507
+    //
+    // Uncomment when R48 comes out (that's where this is fixed).
+    //
+    // #[cfg(feature = "gte-vapoursynth-api-34")]
+    // #[test]
+    // fn message_handler() {
+    //     let api = API::get().unwrap();
+    //     let (tx, rx) = channel();
+    //
+    //     // Hopefully no one logs anything here and breaks the test.
+    //     api.set_message_handler(move |message_type, message| {
+    //         assert_eq!(tx.send((message_type, message.to_owned())), Ok(()));
+    //     });
+    //
+    //     assert_eq!(
+    //         api.log(MessageType::Warning, "test warning message"),
+    //         Ok(())
+    //     );
+    //     assert_eq!(
+    //         rx.recv(),
+    //         Ok((
+    //             MessageType::Warning,
+    //             CString::new("test warning message").unwrap()
+    //         ))
+    //     );
+    //
+    //     assert_eq!(api.log(MessageType::Debug, "test debug message"), Ok(()));
+    //     assert_eq!(
+    //         rx.recv(),
+    //         Ok((
+    //             MessageType::Debug,
+    //             CString::new("test debug message").unwrap()
+    //         ))
+    //     );
+    //
+    //     assert_eq!(
+    //         api.log(MessageType::Critical, "test critical message"),
+    //         Ok(())
+    //     );
+    //     assert_eq!(
     //         rx.recv(),
     //         Ok((
     //             MessageType::Critical,
@@ -907,101 +2457,1441 @@ mod need_api {
     //     api.clear_message_handler();
     // }
 
-    #[cfg(feature = "gte-vapoursynth-api-36")]
+    #[cfg(feature = "gte-vapoursynth-api-34")]
+    #[test]
+    fn can_log() {
+        let api = API::get().unwrap();
+        let core = api.create_core(1);
+
+        // The API was retrieved with the version of the enabled features.
+        assert!(core.info().api_version >= vapoursynth_sys::VAPOURSYNTH_API_VERSION);
+        assert_eq!(api.can_log(), core.info().api_version >= (3 << 16) | 4);
+        assert!(api.can_log());
+    }
+
+    #[cfg(feature = "gte-vapoursynth-api-36")]
+    #[test]
+    fn add_message_handler() {
+        let api = API::get().unwrap();
+        let (tx, rx) = channel();
+
+        // Hopefully no one logs anything here and breaks the test.
+        let id = api.add_message_handler(move |message_type, message| {
+            assert_eq!(tx.send((message_type, message.to_owned())), Ok(()));
+        });
+
+        assert_eq!(
+            api.log(MessageType::Warning, "test warning message"),
+            Ok(())
+        );
+        assert_eq!(
+            rx.recv(),
+            Ok((
+                MessageType::Warning,
+                CString::new("test warning message").unwrap()
+            ))
+        );
+
+        assert_eq!(api.log(MessageType::Debug, "test debug message"), Ok(()));
+        assert_eq!(
+            rx.recv(),
+            Ok((
+                MessageType::Debug,
+                CString::new("test debug message").unwrap()
+            ))
+        );
+
+        assert_eq!(
+            api.log(MessageType::Critical, "test critical message"),
+            Ok(())
+        );
+        assert_eq!(
+            rx.recv(),
+            Ok((
+                MessageType::Critical,
+                CString::new("test critical message").unwrap()
+            ))
+        );
+
+        api.remove_message_handler(id);
+
+        {
+            lazy_static! {
+                static ref SENDER: Mutex<Option<Sender<(MessageType, CString)>>> = Mutex::new(None);
+            }
+
+            let (tx, rx) = channel();
+            *SENDER.lock().unwrap() = Some(tx);
+
+            let id = api.add_message_handler_trivial(|message_type, message| {
+                let guard = SENDER.lock().unwrap();
+                let tx = guard.as_ref().unwrap();
+                assert_eq!(tx.send((message_type, message.to_owned())), Ok(()));
+            });
+
+            assert_eq!(
+                api.log(MessageType::Warning, "test warning message"),
+                Ok(())
+            );
+            assert_eq!(
+                rx.recv(),
+                Ok((
+                    MessageType::Warning,
+                    CString::new("test warning message").unwrap()
+                ))
+            );
+
+            assert_eq!(api.log(MessageType::Debug, "test debug message"), Ok(()));
+            assert_eq!(
+                rx.recv(),
+                Ok((
+                    MessageType::Debug,
+                    CString::new("test debug message").unwrap()
+                ))
+            );
+
+            assert_eq!(
+                api.log(MessageType::Critical, "test critical message"),
+                Ok(())
+            );
+            assert_eq!(
+                rx.recv(),
+                Ok((
+                    MessageType::Critical,
+                    CString::new("test critical message").unwrap()
+                ))
+            );
+
+            api.remove_message_handler(id);
+        }
+    }
+
+    #[test]
+    fn frame_layout() {
+        let api = API::get().unwrap();
+        let core = api.create_core(1);
+        let yuv420p8 = core.get_format(PresetFormat::YUV420P8.into()).unwrap();
+
+        let layout = node::FrameLayout::new(
+            yuv420p8,
+            Resolution {
+                width: 640,
+                height: 480,
+            },
+        );
+        assert_eq!(
+            layout.planes,
+            vec![
+                node::PlaneLayout {
+                    width: 640,
+                    height: 480,
+                    size: 640 * 480,
+                    offset: 0,
+                },
+                node::PlaneLayout {
+                    width: 320,
+                    height: 240,
+                    size: 320 * 240,
+                    offset: 640 * 480,
+                },
+                node::PlaneLayout {
+                    width: 320,
+                    height: 240,
+                    size: 320 * 240,
+                    offset: 640 * 480 + 320 * 240,
+                },
+            ]
+        );
+        assert_eq!(layout.size, 640 * 480 * 3 / 2);
+
+        let rgb48 = core.get_format(PresetFormat::RGB48.into()).unwrap();
+        let layout = node::FrameLayout::new(
+            rgb48,
+            Resolution {
+                width: 3,
+                height: 2,
+            },
+        );
+        assert_eq!(layout.planes[2].offset, 24);
+        assert_eq!(layout.size, 36);
+    }
+
+    #[test]
+    fn plane_iterators() {
+        let api = API::get().unwrap();
+        let core = api.create_core(1);
+        let format = core.get_format(PresetFormat::YUV420P8.into()).unwrap();
+        let resolution = Resolution {
+            width: 64,
+            height: 4,
+        };
+        let mut frame = core.solid_frame(format, resolution, &[1, 2, 3]).unwrap();
+
+        let planes: Vec<_> = frame.planes().collect();
+        assert_eq!(planes.len(), 3);
+        for (index, plane) in planes.iter().enumerate() {
+            assert_eq!(plane.index(), index);
+            assert_eq!(plane.width(), frame.width(index));
+            assert_eq!(plane.height(), frame.height(index));
+            assert_eq!(plane.stride(), frame.stride(index));
+            assert_eq!(plane.data_row(0), frame.data_row(index, 0));
+            assert_eq!(plane.data().ok(), frame.data(index).ok());
+        }
+
+        {
+            let mut planes: Vec<_> = frame.planes_mut().collect();
+            assert_eq!(planes.len(), 3);
+
+            // Both chroma planes can be borrowed at the same time.
+            let (u, v) = planes.split_at_mut(2);
+            let (u, v) = (&mut u[1], &mut v[0]);
+            assert_eq!((u.index(), v.index()), (1, 2));
+            assert_eq!((u.width(), u.height()), (32, 2));
+            for row in 0..u.height() {
+                u.data_row_mut(row).copy_from_slice(v.data_row(row));
+                assert!(v.data_row_mut(row).iter().all(|&x| x == 3));
+            }
+
+            if let Ok(data) = planes[0].data_mut() {
+                data.fill(4);
+            }
+        }
+
+        assert!(frame.data_row(1, 1).iter().all(|&x| x == 3));
+        assert!(frame.data_row(2, 1).iter().all(|&x| x == 3));
+        if frame.data(0).is_ok() {
+            assert!(frame.data_row(0, 3).iter().all(|&x| x == 4));
+        }
+    }
+
+    #[test]
+    fn plane_rows() {
+        let api = API::get().unwrap();
+        let core = api.create_core(1);
+        let format = core.get_format(PresetFormat::YUV420P16.into()).unwrap();
+        let resolution = Resolution {
+            width: 6,
+            height: 4,
+        };
+        let mut frame = core.solid_frame(format, resolution, &[0, 0, 0]).unwrap();
+
+        for (i, row) in frame.plane_rows_mut::<u16>(1).enumerate() {
+            row.fill(i as u16 + 1);
+        }
+
+        let rows: Vec<_> = frame.plane_rows::<u16>(1).collect();
+        assert_eq!(rows, [[1, 1, 1], [2, 2, 2]]);
+        assert_eq!(frame.plane_rows::<u16>(0).len(), 4);
+        assert!(frame.plane_rows::<u16>(0).all(|row| row == [0; 6]));
+        assert_eq!(frame.plane_rows::<u16>(2).next_back(), Some(&[0; 3][..]));
+        assert_eq!(frame.plane_to_vec2d::<u16>(1), rows);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn par_plane_rows_mut() {
+        use rayon::prelude::*;
+
+        let api = API::get().unwrap();
+        let core = api.create_core(1);
+        let format = core.get_format(PresetFormat::YUV420P8.into()).unwrap();
+        let resolution = Resolution {
+            width: 3840,
+            height: 2160,
+        };
+        let mut frame = core.solid_frame(format, resolution, &[10, 20, 30]).unwrap();
+
+        for plane in 0..frame.plane_count() {
+            let height = frame.height(plane);
+            assert_eq!(frame.par_plane_rows_mut::<u8>(plane).len(), height);
+            frame
+                .par_plane_rows_mut::<u8>(plane)
+                .for_each(|row| row.iter_mut().for_each(|x| *x = 255 - *x));
+        }
+
+        assert!(frame
+            .plane_rows::<u8>(0)
+            .all(|row| row.iter().all(|&x| x == 245)));
+        assert!(frame
+            .plane_rows::<u8>(1)
+            .all(|row| row.iter().all(|&x| x == 235)));
+        assert!(frame
+            .plane_rows::<u8>(2)
+            .all(|row| row.iter().all(|&x| x == 225)));
+    }
+
+    #[test]
+    fn alpha_frame() {
+        let api = API::get().unwrap();
+        let core = api.create_core(1);
+        let resolution = Resolution {
+            width: 4,
+            height: 2,
+        };
+        let rgb24 = core.get_format(PresetFormat::RGB24.into()).unwrap();
+        let gray8 = core.get_format(PresetFormat::Gray8.into()).unwrap();
+
+        let mut frame = core.solid_frame(rgb24, resolution, &[1, 2, 3]).unwrap();
+        assert!(frame.alpha_frame().is_none());
+
+        frame.props_mut().set_int("_Alpha", 1).unwrap();
+        assert!(frame.alpha_frame().is_none());
+
+        let alpha = core.solid_frame(gray8, resolution, &[255]).unwrap();
+        frame.props_mut().set_frame("_Alpha", &alpha).unwrap();
+
+        let alpha = frame.alpha_frame().unwrap();
+        assert_eq!(alpha.format_id(), PresetFormat::Gray8.into());
+        assert_eq!(alpha.plane_to_vec2d::<u8>(0), vec![vec![255; 4]; 2]);
+    }
+
+    #[test]
+    fn content_hash() {
+        let api = API::get().unwrap();
+        let core = api.create_core(1);
+        let gray8 = core.get_format(PresetFormat::Gray8.into()).unwrap();
+        let resolution = Resolution {
+            width: 7,
+            height: 3,
+        };
+
+        let frame = core.solid_frame(gray8, resolution, &[5]).unwrap();
+        assert_eq!(frame.content_hash(), 0xc849_d6ad_ad07_5cd5);
+
+        // Garbage in the row padding doesn't change the hash.
+        let mut padded = FrameRefMut::copy_of(core, &frame);
+        assert!(padded.stride(0) > 7);
+        for row in 0..3 {
+            let offset = row * padded.stride(0) + 7;
+            unsafe { *padded.data_ptr_mut(0).add(offset) = 0xAA };
+        }
+        assert_eq!(padded.content_hash(), frame.content_hash());
+
+        padded.plane_row_mut::<u8>(0, 2)[6] = 6;
+        assert_ne!(padded.content_hash(), frame.content_hash());
+
+        let other = core.solid_frame(
+            gray8,
+            Resolution {
+                width: 3,
+                height: 7,
+            },
+            &[5],
+        );
+        assert_ne!(other.unwrap().content_hash(), frame.content_hash());
+    }
+
+    #[test]
+    fn owned_core() {
+        let api = API::get().unwrap();
+        let core = core::Core::new(api, 1);
+        assert_eq!(core.core_ref().info().num_threads, 1);
+
+        {
+            let std = core
+                .core_ref()
+                .get_plugin_by_id("com.vapoursynth.std")
+                .unwrap()
+                .unwrap();
+
+            let args = args! {
+                api;
+                "format" => PresetFormat::Gray8 as i64,
+                "width" => 64,
+                "height" => 32,
+                "length" => 10,
+            }
+            .unwrap();
+            let rv = std.invoke_checked("BlankClip", &args).unwrap();
+            let node = rv.get_node("clip").unwrap();
+
+            let frame = node.get_frame(9).unwrap();
+            assert_eq!(frame.format_id(), PresetFormat::Gray8.into());
+            assert_eq!(frame.resolution(0).width, 64);
+            assert_eq!(frame.resolution(0).height, 32);
+        }
+
+        drop(core);
+    }
+
+    #[test]
+    fn new_zeroed() {
+        let api = API::get().unwrap();
+        let core = api.create_core(1);
+        let format = core.get_format(PresetFormat::YUV420P16.into()).unwrap();
+        let resolution = Resolution {
+            width: 6,
+            height: 4,
+        };
+        let mut src = core
+            .solid_frame(format, resolution, &[100, 200, 300])
+            .unwrap();
+        src.props_mut().set_int("Answer", 42).unwrap();
+
+        let frame = FrameRefMut::new_zeroed(core, Some(&src), format, resolution);
+        assert_eq!(frame.format(), format);
+        assert_eq!(frame.plane_to_vec2d::<u16>(0), vec![vec![0; 6]; 4]);
+        assert_eq!(frame.plane_to_vec2d::<u16>(1), vec![vec![0; 3]; 2]);
+        assert_eq!(frame.plane_to_vec2d::<u16>(2), vec![vec![0; 3]; 2]);
+        assert_eq!(frame.props().get_int("Answer"), Ok(42));
+
+        let frame = FrameRefMut::new_zeroed(core, None, format, resolution);
+        assert_eq!(frame.props().key_count(), 0);
+    }
+
+    #[test]
+    fn fill_plane() {
+        let api = API::get().unwrap();
+        let core = api.create_core(1);
+        let format = core.get_format(PresetFormat::YUV420P10.into()).unwrap();
+        let resolution = Resolution {
+            width: 6,
+            height: 4,
+        };
+        let mut frame = unsafe { FrameRefMut::new_uninitialized(core, None, format, resolution) };
+
+        frame.fill(512u16);
+        frame.fill_plane(2, 100u16);
+
+        assert_eq!(frame.plane_to_vec2d::<u16>(0), vec![vec![512; 6]; 4]);
+        assert_eq!(frame.plane_to_vec2d::<u16>(1), vec![vec![512; 3]; 2]);
+        assert_eq!(frame.plane_to_vec2d::<u16>(2), vec![vec![100; 3]; 2]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn fill_wrong_component() {
+        let api = API::get().unwrap();
+        let core = api.create_core(1);
+        let format = core.get_format(PresetFormat::YUV420P10.into()).unwrap();
+        let mut frame = core
+            .solid_frame(
+                format,
+                Resolution {
+                    width: 6,
+                    height: 4,
+                },
+                &[0, 0, 0],
+            )
+            .unwrap();
+
+        frame.fill(0u8);
+    }
+
+    #[test]
+    fn zip_rows() {
+        let api = API::get().unwrap();
+        let core = api.create_core(1);
+        let format = core.get_format(PresetFormat::YUV420P8.into()).unwrap();
+        let resolution = Resolution {
+            width: 6,
+            height: 4,
+        };
+        let mut a = core.solid_frame(format, resolution, &[0, 0, 0]).unwrap();
+        for (i, row) in a.plane_rows_mut::<u8>(0).enumerate() {
+            row.fill(i as u8);
+        }
+        let b = a.deep_copy(core);
+
+        let pairs = frame::zip_rows::<u8>(&a, &b, 0).unwrap();
+        assert_eq!(pairs.len(), 4);
+        for (i, (x, y)) in pairs.enumerate() {
+            assert_eq!(x, y);
+            assert_eq!(x, [i as u8; 6]);
+        }
+        assert_eq!(frame::zip_rows::<u8>(&a, &b, 2).unwrap().count(), 2);
+
+        let other = core
+            .solid_frame(
+                format,
+                Resolution {
+                    width: 8,
+                    height: 4,
+                },
+                &[0, 0, 0],
+            )
+            .unwrap();
+        assert!(matches!(
+            frame::zip_rows::<u8>(&a, &other, 0),
+            Err(frame::MismatchedFrames)
+        ));
+    }
+
+    #[test]
+    #[should_panic]
+    fn plane_rows_wrong_component() {
+        let api = API::get().unwrap();
+        let core = api.create_core(1);
+        let format = core.get_format(PresetFormat::Gray16.into()).unwrap();
+        let resolution = Resolution {
+            width: 6,
+            height: 4,
+        };
+        let frame = core.solid_frame(format, resolution, &[0]).unwrap();
+
+        let _ = frame.plane_rows::<u8>(0);
+    }
+
+    #[cfg(feature = "ndarray")]
+    #[test]
+    fn plane_array() {
+        let api = API::get().unwrap();
+        let core = api.create_core(1);
+        let format = core.get_format(PresetFormat::Gray16.into()).unwrap();
+        let resolution = Resolution {
+            width: 6,
+            height: 3,
+        };
+        let mut frame = core.solid_frame(format, resolution, &[0]).unwrap();
+        assert!(frame.plane::<u16>(0).is_err());
+
+        {
+            let mut view = frame.plane_array_mut::<u16>(0).unwrap();
+            assert_eq!(view.dim(), (3, 6));
+            for ((row, col), x) in view.indexed_iter_mut() {
+                *x = (row * 6 + col) as u16;
+            }
+        }
+
+        assert_eq!(
+            frame.plane_to_vec2d::<u16>(0),
+            [
+                [0, 1, 2, 3, 4, 5],
+                [6, 7, 8, 9, 10, 11],
+                [12, 13, 14, 15, 16, 17]
+            ]
+        );
+
+        let view = frame.plane_array::<u16>(0).unwrap();
+        assert_eq!(view[[2, 1]], 13);
+        assert_eq!(view.column(5).to_vec(), [5, 11, 17]);
+        assert_eq!(view.sum(), (0..18).sum::<u16>());
+
+        assert_eq!(
+            frame.plane_array::<u8>(0).unwrap_err(),
+            frame::PlaneDataError::InvalidComponentType
+        );
+        assert_eq!(
+            frame.plane_array_mut::<f32>(0).unwrap_err(),
+            frame::PlaneDataError::InvalidComponentType
+        );
+    }
+
+    #[test]
+    fn plane_packed_mut() {
+        let api = API::get().unwrap();
+        let core = api.create_core(1);
+        let format = core.get_format(PresetFormat::Gray16.into()).unwrap();
+        let resolution = Resolution {
+            width: 6,
+            height: 3,
+        };
+        let mut frame = core.solid_frame(format, resolution, &[0]).unwrap();
+        assert!(frame.plane::<u16>(0).is_err());
+
+        {
+            let mut plane = frame.plane_packed_mut::<u16>(0);
+            assert!(plane.is_copy());
+            assert_eq!(plane.len(), 18);
+            for (i, x) in plane.iter_mut().enumerate() {
+                *x = i as u16;
+            }
+        }
+
+        assert_eq!(
+            frame.plane_to_vec2d::<u16>(0),
+            [
+                [0, 1, 2, 3, 4, 5],
+                [6, 7, 8, 9, 10, 11],
+                [12, 13, 14, 15, 16, 17]
+            ]
+        );
+
+        let data: Vec<u8> = (0..64).collect();
+        let gray8 = core.get_format(PresetFormat::Gray8.into()).unwrap();
+        let resolution = Resolution {
+            width: 32,
+            height: 2,
+        };
+        let mut frame = core
+            .new_frame_with_data(gray8, resolution, &[&data[..]], None)
+            .unwrap();
+        let padded = frame.plane::<u8>(0).is_err();
+        {
+            let mut plane = frame.plane_packed_mut::<u8>(0);
+            assert_eq!(plane.is_copy(), padded);
+            assert_eq!(&plane[..], &data[..]);
+            plane[40] = 255;
+        }
+        assert_eq!(frame.plane_row::<u8>(0, 1)[8], 255);
+    }
+
+    #[test]
+    fn all_plane_bytes() {
+        let api = API::get().unwrap();
+        let core = api.create_core(1);
+        let resolution = Resolution {
+            width: 6,
+            height: 4,
+        };
+
+        for &format in &[
+            PresetFormat::YUV420P10,
+            PresetFormat::RGB24,
+            PresetFormat::GrayS,
+            PresetFormat::CompatBGR32,
+        ] {
+            let format = core.get_format(format.into()).unwrap();
+            let planes = format.plane_count();
+            let frame = match format.sample_type() {
+                SampleType::Integer => core.solid_frame(format, resolution, &vec![0; planes]),
+                SampleType::Float => core.solid_frame_f(format, resolution, &vec![0.; planes]),
+            }
+            .unwrap();
+
+            let expected: usize = (0..format.plane_count())
+                .map(|plane| frame.height(plane) * frame.data_row(plane, 0).len())
+                .sum();
+            assert_eq!(frame.all_plane_bytes().len(), expected);
+            assert_eq!(
+                expected,
+                node::FrameLayout::new(format, resolution).size,
+                "{}",
+                format.name()
+            );
+        }
+
+        let gray8 = core.get_format(PresetFormat::Gray8.into()).unwrap();
+        let data: Vec<u8> = (0..24).collect();
+        let frame = core
+            .new_frame_with_data(gray8, resolution, &[&data[..]], None)
+            .unwrap();
+        assert_eq!(frame.all_plane_bytes(), data);
+    }
+
+    #[test]
+    fn library_info() {
+        let info = API::get().unwrap().library_info();
+        assert!(!info.version_string.is_empty());
+
+        #[cfg(target_os = "linux")]
+        assert!(info.path.unwrap().starts_with('/'));
+    }
+
+    #[test]
+    fn thread_count() {
+        let api = API::get().unwrap();
+        let core = api.create_core(2);
+        assert_eq!(core.thread_count(), 2);
+
+        #[cfg(feature = "gte-vapoursynth-api-36")]
+        {
+            assert_eq!(core.set_thread_count(3), 3);
+            assert_eq!(core.thread_count(), 3);
+
+            // Zero means autodetection.
+            let detected = core.set_thread_count(0);
+            assert!(detected > 0);
+            assert_eq!(core.thread_count(), detected as usize);
+        }
+    }
+
+    #[test]
+    fn sample_value_range() {
+        let api = API::get().unwrap();
+        let core = api.create_core(1);
+        let range =
+            |format: PresetFormat| core.get_format(format.into()).unwrap().sample_value_range();
+
+        assert_eq!(
+            range(PresetFormat::Gray8),
+            SampleRange::Integer { max: 255 }
+        );
+        assert_eq!(
+            range(PresetFormat::YUV420P10),
+            SampleRange::Integer { max: 1023 }
+        );
+        assert_eq!(
+            range(PresetFormat::RGB48),
+            SampleRange::Integer { max: 65535 }
+        );
+        assert_eq!(
+            range(PresetFormat::RGBS),
+            SampleRange::Float {
+                typical_min: 0.0,
+                typical_max: 1.0,
+            }
+        );
+    }
+
+    #[test]
+    fn plane_alignment() {
+        let api = API::get().unwrap();
+        let core = api.create_core(1);
+        let yuv420p10 = core.get_format(PresetFormat::YUV420P10.into()).unwrap();
+
+        // An odd resolution so the rows need padding.
+        let frame = unsafe {
+            FrameRefMut::new_uninitialized(
+                core,
+                None,
+                yuv420p10,
+                Resolution {
+                    width: 33,
+                    height: 17,
+                },
+            )
+        };
+
+        for plane in 0..3 {
+            assert!(frame.is_plane_aligned(plane, 32));
+            assert!(frame.is_plane_aligned(plane, 1));
+            assert!(frame.stride(plane) >= frame.width(plane) * 2);
+        }
+    }
+
+    #[test]
+    fn map_summary() {
+        let api = API::get().unwrap();
+        let core = api.create_core(1);
+        let mut map = OwnedMap::new(api);
+        assert_eq!(map.summary(), "");
+
+        let gray8 = core.get_format(PresetFormat::Gray8.into()).unwrap();
+        let resolution = Resolution {
+            width: 2,
+            height: 2,
+        };
+        let frame = core
+            .new_frame_with_data(gray8, resolution, &[&[0u8; 4][..]], None)
+            .unwrap();
+
+        map.set_int("int", 42).unwrap();
+        map.set_float("float", 1337.).unwrap();
+        map.set_data("data", b"asd").unwrap();
+        map.append_frame("frame_array", &frame).unwrap();
+        map.append_frame("frame_array", &frame).unwrap();
+        map.touch("optional_int", ValueType::Int).unwrap();
+
+        // The key order depends on the VapourSynth version.
+        let mut entries: Vec<_> = map.summary().split(", ").map(str::to_owned).collect();
+        entries.sort();
+        assert_eq!(
+            entries,
+            [
+                "data: data",
+                "float: float",
+                "frame_array: frame[2]",
+                "int: int",
+                "optional_int: int[0]",
+            ]
+        );
+    }
+
+    #[test]
+    fn map_dump() {
+        let api = API::get().unwrap();
+        let core = api.create_core(1);
+        let mut map = OwnedMap::new(api);
+        assert_eq!(map.dump().to_string(), "");
+
+        let gray8 = core.get_format(PresetFormat::Gray8.into()).unwrap();
+        let resolution = Resolution {
+            width: 2,
+            height: 2,
+        };
+        let frame = core
+            .new_frame_with_data(gray8, resolution, &[&[0u8; 4][..]], None)
+            .unwrap();
+
+        map.set_int("int", 42).unwrap();
+        for x in 0..10 {
+            map.append_int("ints", x).unwrap();
+        }
+        map.set_float("float", 1.).unwrap();
+        map.set_data("text", b"asd").unwrap();
+        map.set_data("long_text", "ä".repeat(20).as_bytes())
+            .unwrap();
+        map.set_data("bytes", &[0, 1, 255]).unwrap();
+        map.append_frame("frames", &frame).unwrap();
+        map.append_frame("frames", &frame).unwrap();
+        map.touch("empty", ValueType::Float).unwrap();
+
+        // The key order depends on the VapourSynth version.
+        let dump = map.dump().to_string();
+        let mut lines: Vec<_> = dump.lines().collect();
+        lines.sort();
+        assert_eq!(
+            lines,
+            [
+                "bytes: data = 0x0001ff",
+                "empty: float[0] = []",
+                "float: float = 1.0",
+                "frames: frame[2] = [<frame>, <frame>]",
+                "int: int = 42",
+                "ints: int[10] = [0, 1, 2, 3, 4, 5, 6, 7, ...]",
+                &format!("long_text: data = \"{}\"...", "ä".repeat(16)),
+                "text: data = \"asd\"",
+            ]
+        );
+    }
+
+    #[test]
+    fn map_iter() {
+        let api = API::get().unwrap();
+        let core = api.create_core(1);
+        let mut map = OwnedMap::new(api);
+        assert_eq!(map.iter().next(), None);
+        assert!(map.entries().next().is_none());
+
+        let gray8 = core.get_format(PresetFormat::Gray8.into()).unwrap();
+        let resolution = Resolution {
+            width: 2,
+            height: 2,
+        };
+        let frame = core
+            .new_frame_with_data(gray8, resolution, &[&[7u8; 4][..]], None)
+            .unwrap();
+
+        map.set_int("int", 42).unwrap();
+        map.append_int("int", 43).unwrap();
+        map.set_data("data", b"asd").unwrap();
+        map.set_frame("frame", &frame).unwrap();
+        map.touch("empty", ValueType::Float).unwrap();
+
+        // The key order depends on the VapourSynth version.
+        let mut items: Vec<_> = map.iter().collect();
+        items.sort_by_key(|&(key, _, _)| key);
+        assert_eq!(
+            items,
+            [
+                ("data", ValueType::Data, 1),
+                ("empty", ValueType::Float, 0),
+                ("frame", ValueType::Frame, 1),
+                ("int", ValueType::Int, 2),
+            ]
+        );
+
+        assert_eq!(map.entries().len(), 4);
+        for (key, entry) in map.entries() {
+            match (key, entry) {
+                ("int", map::Entry::Int(x)) => assert_eq!(x, 42),
+                ("data", map::Entry::Data(x)) => assert_eq!(x, b"asd"),
+                ("frame", map::Entry::Frame(x)) => assert_eq!(x.plane_row::<u8>(0, 1), &[7, 7]),
+                ("empty", map::Entry::Empty(x)) => assert_eq!(x, ValueType::Float),
+                (key, entry) => panic!("unexpected entry {}: {:?}", key, entry),
+            }
+        }
+    }
+
+    #[cfg(feature = "serde")]
     #[test]
-    fn add_message_handler() {
+    fn map_serde() {
         let api = API::get().unwrap();
-        let (tx, rx) = channel();
+        let core = api.create_core(1);
+        let mut map = OwnedMap::new(api);
 
-        // Hopefully no one logs anything here and breaks the test.
-        let id = api.add_message_handler(move |message_type, message| {
-            assert_eq!(tx.send((message_type, message.to_owned())), Ok(()));
-        });
+        map.set_int("int", 42).unwrap();
+        for x in [1, 2, 3] {
+            map.append_int("ints", x).unwrap();
+        }
+        map.set_float("float", 0.5).unwrap();
+        map.touch("floats", ValueType::Float).unwrap();
+        map.set_data("data", &[0, 1, 2, 255]).unwrap();
 
+        let json = serde_json::to_string(&map).unwrap();
+        let single: OwnedMap = serde_json::from_str(r#"{"int": {"int": 42}}"#).unwrap();
         assert_eq!(
-            api.log(MessageType::Warning, "test warning message"),
-            Ok(())
+            serde_json::to_string(&single).unwrap(),
+            r#"{"int":{"int":42}}"#
         );
+
+        let copy: OwnedMap = serde_json::from_str(&json).unwrap();
+        assert_eq!(copy.key_count(), 5);
+        assert_eq!(copy.get_int("int"), Ok(42));
         assert_eq!(
-            rx.recv(),
-            Ok((
-                MessageType::Warning,
-                CString::new("test warning message").unwrap()
-            ))
+            copy.get_int_iter("ints").unwrap().collect::<Vec<_>>(),
+            [1, 2, 3]
         );
+        assert_eq!(copy.get_float("float"), Ok(0.5));
+        assert_eq!(copy.value_type("floats"), Ok(ValueType::Float));
+        assert_eq!(copy.value_count("floats"), Ok(0));
+        assert_eq!(copy.get_data("data"), Ok(&[0, 1, 2, 255][..]));
+
+        let gray8 = core.get_format(PresetFormat::Gray8.into()).unwrap();
+        let resolution = Resolution {
+            width: 2,
+            height: 2,
+        };
+        let frame = core
+            .new_frame_with_data(gray8, resolution, &[&[0u8; 4][..]], None)
+            .unwrap();
+        map.set_frame("frame", &frame).unwrap();
+        assert!(serde_json::to_string(&map).is_err());
+
+        assert!(serde_json::from_str::<OwnedMap>(r#"{"x": {"data": "!"}}"#).is_err());
+        assert!(serde_json::from_str::<OwnedMap>(r#"{"x": {"clip": 1}}"#).is_err());
+    }
 
-        assert_eq!(api.log(MessageType::Debug, "test debug message"), Ok(()));
+    #[test]
+    fn map_merge() {
+        let api = API::get().unwrap();
+        let core = api.create_core(1);
+
+        let gray8 = core.get_format(PresetFormat::Gray8.into()).unwrap();
+        let resolution = Resolution {
+            width: 2,
+            height: 2,
+        };
+        let frame = core
+            .new_frame_with_data(gray8, resolution, &[&[7u8; 4][..]], None)
+            .unwrap();
+
+        let mut base = OwnedMap::new(api);
+        base.set_int("i", 1).unwrap();
+        base.set_data("d", b"base").unwrap();
+        base.set_frame("frame", &frame).unwrap();
+        base.touch("empty", ValueType::Float).unwrap();
+
+        let copy = OwnedMap::clone_from_map(api, &base);
+        assert_eq!(copy.key_count(), 4);
+        assert_eq!(copy.get_int("i"), Ok(1));
+        assert_eq!(copy.get_data("d"), Ok(&b"base"[..]));
+        assert_eq!(copy.get_frame("frame").unwrap().data_row(0, 0), [7, 7]);
+        assert_eq!(copy.value_count("empty"), Ok(0));
+
+        let mut other = OwnedMap::new(api);
+        other.set_int("i", 2).unwrap();
+        other.set_float("f", 0.5).unwrap();
+
+        let mut replaced = OwnedMap::clone_from_map(api, &base);
+        assert_eq!(replaced.merge(&other, map::MergeMode::Replace), Ok(()));
+        assert_eq!(replaced.get_int_iter("i").unwrap().collect::<Vec<_>>(), [2]);
+        assert_eq!(replaced.get_float("f"), Ok(0.5));
+        assert_eq!(replaced.get_data("d"), Ok(&b"base"[..]));
+
+        let mut appended = OwnedMap::clone_from_map(api, &base);
+        assert_eq!(appended.merge(&other, map::MergeMode::Append), Ok(()));
         assert_eq!(
-            rx.recv(),
-            Ok((
-                MessageType::Debug,
-                CString::new("test debug message").unwrap()
-            ))
+            appended.get_int_iter("i").unwrap().collect::<Vec<_>>(),
+            [1, 2]
         );
+        assert_eq!(appended.get_float("f"), Ok(0.5));
 
+        other.set_data("frame", b"not a frame").unwrap();
+        let mut unchanged = OwnedMap::clone_from_map(api, &base);
         assert_eq!(
-            api.log(MessageType::Critical, "test critical message"),
-            Ok(())
+            unchanged.merge(&other, map::MergeMode::Append),
+            Err(map::Error::WrongValueType)
         );
+        assert_eq!(unchanged.get_int_iter("i").unwrap().count(), 1);
+        assert!(!unchanged.contains_key("f"));
+    }
+
+    #[test]
+    fn non_utf8_keys() {
+        let api = API::get().unwrap();
+        let mut map = OwnedMap::new(api);
+
+        let key = CStr::from_bytes_with_nul(b"bad\xff\0").unwrap();
+        // Newer VapourSynth versions reject such keys altogether.
+        if unsafe { map.append_int_raw_unchecked(key, 1) }.is_ok() {
+            assert_eq!(map.key_count(), 1);
+            assert!(map.try_key(0).is_err());
+        }
+
+        let core = api.create_core(1);
+        let gray8 = core.get_format(PresetFormat::Gray8.into()).unwrap();
+        assert_eq!(gray8.try_name(), Ok("Gray8"));
+    }
+
+    #[test]
+    fn blend_into() {
+        let api = API::get().unwrap();
+        let core = api.create_core(1);
+        let gray8 = core.get_format(PresetFormat::Gray8.into()).unwrap();
+        let resolution = Resolution {
+            width: 4,
+            height: 2,
+        };
+        let new_frame = |value: u8| {
+            core.new_frame_with_data(gray8, resolution, &[&[value; 8][..]], None)
+                .unwrap()
+        };
+
+        let white = new_frame(255);
+        let black = new_frame(0);
+        let mut dst = new_frame(1);
+        let check = |frame: &Frame, value: u8| {
+            for row in 0..2 {
+                assert_eq!(frame.plane_row::<u8>(0, row), &[value; 4]);
+            }
+        };
+
+        assert_eq!(white.blend_into(&black, 0.5, &mut dst), Ok(()));
+        check(&dst, 128);
+        assert_eq!(white.blend_into(&black, 1., &mut dst), Ok(()));
+        check(&dst, 255);
+        assert_eq!(white.blend_into(&black, 0., &mut dst), Ok(()));
+        check(&dst, 0);
+
+        // Integer results are clamped.
+        assert_eq!(white.blend_into(&black, 2., &mut dst), Ok(()));
+        check(&dst, 255);
+        assert_eq!(white.blend_into(&black, -1., &mut dst), Ok(()));
+        check(&dst, 0);
+
+        let grays = core.get_format(PresetFormat::GrayS.into()).unwrap();
+        let a = core
+            .new_frame_with_data(grays, resolution, &[&[1f32; 8][..]], None)
+            .unwrap();
+        let b = core
+            .new_frame_with_data(grays, resolution, &[&[0f32; 8][..]], None)
+            .unwrap();
+        let mut dst = core
+            .new_frame_with_data(grays, resolution, &[&[0f32; 8][..]], None)
+            .unwrap();
+        assert_eq!(a.blend_into(&b, 0.25, &mut dst), Ok(()));
+        assert_eq!(dst.plane_row::<f32>(0, 0), &[0.25; 4]);
+
+        let small = core
+            .new_frame_with_data(
+                gray8,
+                Resolution {
+                    width: 2,
+                    height: 2,
+                },
+                &[&[0u8; 4][..]],
+                None,
+            )
+            .unwrap();
+        let mut dst = new_frame(0);
         assert_eq!(
-            rx.recv(),
-            Ok((
-                MessageType::Critical,
-                CString::new("test critical message").unwrap()
-            ))
+            white.blend_into(&small, 0.5, &mut dst),
+            Err(frame::BlendError::MismatchedFrames(frame::MismatchedFrames))
         );
 
-        api.remove_message_handler(id);
+        let mut dst = core
+            .new_frame_with_data(grays, resolution, &[&[0f32; 8][..]], None)
+            .unwrap();
+        assert_eq!(
+            white.blend_into(&black, 0.5, &mut dst),
+            Err(frame::BlendError::MismatchedFrames(frame::MismatchedFrames))
+        );
 
-        {
-            lazy_static! {
-                static ref SENDER: Mutex<Option<Sender<(MessageType, CString)>>> = Mutex::new(None);
-            }
+        let grayh = core.get_format(PresetFormat::GrayH.into()).unwrap();
+        let a = FrameRefMut::new_zeroed(core, None, grayh, resolution);
+        let b = FrameRefMut::new_zeroed(core, None, grayh, resolution);
+        let mut dst = FrameRefMut::new_zeroed(core, None, grayh, resolution);
+        #[cfg(feature = "f16-pixel-type")]
+        assert_eq!(a.blend_into(&b, 0.5, &mut dst), Ok(()));
+        #[cfg(not(feature = "f16-pixel-type"))]
+        assert_eq!(
+            a.blend_into(&b, 0.5, &mut dst),
+            Err(frame::BlendError::UnsupportedSampleType)
+        );
+    }
 
-            let (tx, rx) = channel();
-            *SENDER.lock().unwrap() = Some(tx);
+    #[test]
+    fn plane_with_dims() {
+        let api = API::get().unwrap();
+        let core = api.create_core(1);
+        let format = core.get_format(PresetFormat::YUV420P8.into()).unwrap();
+        let resolution = Resolution {
+            width: 128,
+            height: 4,
+        };
 
-            let id = api.add_message_handler_trivial(|message_type, message| {
-                let guard = SENDER.lock().unwrap();
-                let tx = guard.as_ref().unwrap();
-                assert_eq!(tx.send((message_type, message.to_owned())), Ok(()));
-            });
+        let frame = core
+            .solid_frame(format, resolution, &[16, 128, 128])
+            .unwrap();
 
-            assert_eq!(
-                api.log(MessageType::Warning, "test warning message"),
-                Ok(())
-            );
-            assert_eq!(
-                rx.recv(),
-                Ok((
-                    MessageType::Warning,
-                    CString::new("test warning message").unwrap()
-                ))
-            );
+        let (data, width, height) = frame.plane_with_dims::<u8>(0).unwrap();
+        assert_eq!((width, height), (128, 4));
+        assert_eq!(data.len(), width * height);
 
-            assert_eq!(api.log(MessageType::Debug, "test debug message"), Ok(()));
-            assert_eq!(
-                rx.recv(),
-                Ok((
-                    MessageType::Debug,
-                    CString::new("test debug message").unwrap()
-                ))
-            );
+        let (data, width, height) = frame.plane_with_dims::<u8>(1).unwrap();
+        assert_eq!((width, height), (64, 2));
+        assert_eq!(data.len(), width * height);
+        assert!(data.iter().all(|&x| x == 128));
+    }
+
+    #[test]
+    fn solid_frame() {
+        let api = API::get().unwrap();
+        let core = api.create_core(1);
+        let resolution = Resolution {
+            width: 4,
+            height: 2,
+        };
+
+        let format = core.get_format(PresetFormat::YUV420P10.into()).unwrap();
+        let frame = core
+            .solid_frame(format, resolution, &[512, 512, 512])
+            .unwrap();
+        assert_eq!(frame.plane_to_vec2d::<u16>(0), vec![vec![512; 4]; 2]);
+        assert_eq!(frame.plane_to_vec2d::<u16>(1), vec![vec![512; 2]; 1]);
+        assert_eq!(frame.plane_to_vec2d::<u16>(2), vec![vec![512; 2]; 1]);
+
+        let frame = core
+            .solid_frame(format, resolution, &[-1, 1023, 5000])
+            .unwrap();
+        assert_eq!(frame.plane_to_vec2d::<u16>(0), vec![vec![0; 4]; 2]);
+        assert_eq!(frame.plane_to_vec2d::<u16>(1), vec![vec![1023; 2]; 1]);
+        assert_eq!(frame.plane_to_vec2d::<u16>(2), vec![vec![1023; 2]; 1]);
+
+        assert_eq!(
+            core.solid_frame(format, resolution, &[512, 512]).err(),
+            Some(frame::PlaneDataError::WrongPlaneCount {
+                expected: 3,
+                got: 2
+            })
+        );
+        assert_eq!(
+            core.solid_frame_f(format, resolution, &[0.5, 0., 0.]).err(),
+            Some(frame::PlaneDataError::InvalidComponentType)
+        );
 
+        let format = core.get_format(PresetFormat::YUV444PS.into()).unwrap();
+        let frame = core
+            .solid_frame_f(format, resolution, &[0.5, 0., -0.25])
+            .unwrap();
+        assert_eq!(frame.plane_to_vec2d::<f32>(0), vec![vec![0.5; 4]; 2]);
+        assert_eq!(frame.plane_to_vec2d::<f32>(1), vec![vec![0.; 4]; 2]);
+        assert_eq!(frame.plane_to_vec2d::<f32>(2), vec![vec![-0.25; 4]; 2]);
+        assert_eq!(
+            core.solid_frame(format, resolution, &[0, 0, 0]).err(),
+            Some(frame::PlaneDataError::InvalidComponentType)
+        );
+    }
+
+    #[test]
+    fn invert() {
+        let api = API::get().unwrap();
+        let core = api.create_core(1);
+        let resolution = Resolution {
+            width: 2,
+            height: 2,
+        };
+
+        let format = core.get_format(PresetFormat::GrayS.into()).unwrap();
+        let data = [0f32, 0.25, 1., 1.5];
+        let mut frame = core
+            .new_frame_with_data(format, resolution, &[&data[..]], None)
+            .unwrap();
+        frame.invert().unwrap();
+        assert_eq!(
+            frame.plane_to_vec2d::<f32>(0),
+            vec![vec![1., 0.75], vec![0., -0.5]]
+        );
+
+        let format = core.get_format(PresetFormat::Gray16.into()).unwrap();
+        let data = [0u16, 1, 32768, 65535];
+        let mut frame = core
+            .new_frame_with_data(format, resolution, &[&data[..]], None)
+            .unwrap();
+        frame.invert().unwrap();
+        assert_eq!(
+            frame.plane_to_vec2d::<u16>(0),
+            vec![vec![65535, 65534], vec![32767, 0]]
+        );
+
+        let format = core.get_format(PresetFormat::YUV444PS.into()).unwrap();
+        let luma = [0.25f32; 4];
+        let chroma = [0.25f32; 4];
+        let mut frame = core
+            .new_frame_with_data(
+                format,
+                resolution,
+                &[&luma[..], &chroma[..], &chroma[..]],
+                None,
+            )
+            .unwrap();
+        frame.invert().unwrap();
+        assert_eq!(frame.plane_to_vec2d::<f32>(0), vec![vec![0.75; 2]; 2]);
+        assert_eq!(frame.plane_to_vec2d::<f32>(1), vec![vec![-0.25; 2]; 2]);
+
+        #[cfg(not(feature = "f16-pixel-type"))]
+        {
+            let format = core.get_format(PresetFormat::GrayH.into()).unwrap();
+            let mut frame = FrameRefMut::new_zeroed(core, None, format, resolution);
             assert_eq!(
-                api.log(MessageType::Critical, "test critical message"),
-                Ok(())
+                frame.invert(),
+                Err(frame::PlaneDataError::InvalidComponentType)
             );
+        }
+    }
+
+    #[test]
+    fn apply_matrix3x3() {
+        let api = API::get().unwrap();
+        let core = api.create_core(1);
+        let rgb24 = core.get_format(PresetFormat::RGB24.into()).unwrap();
+        let resolution = Resolution {
+            width: 4,
+            height: 2,
+        };
+
+        let planes: [&[u8]; 3] = [&[10; 8], &[20; 8], &[200; 8]];
+        let mut frame = core
+            .new_frame_with_data(rgb24, resolution, &planes, None)
+            .unwrap();
+        let check = |frame: &Frame, expected: [u8; 3]| {
+            for (plane, &value) in expected.iter().enumerate() {
+                for row in 0..2 {
+                    assert_eq!(frame.plane_row::<u8>(plane, row), &[value; 4]);
+                }
+            }
+        };
+
+        let identity = [[1., 0., 0.], [0., 1., 0.], [0., 0., 1.]];
+        assert_eq!(frame.apply_matrix3x3(identity), Ok(()));
+        check(&frame, [10, 20, 200]);
+
+        // Swap the red and the blue channels.
+        let swap = [[0., 0., 1.], [0., 1., 0.], [1., 0., 0.]];
+        assert_eq!(frame.apply_matrix3x3(swap), Ok(()));
+        check(&frame, [200, 20, 10]);
+
+        // Integer results are clamped.
+        let scale = [[2., 0., 0.], [0., -1., 0.], [0., 0., 1.5]];
+        assert_eq!(frame.apply_matrix3x3(scale), Ok(()));
+        check(&frame, [255, 0, 15]);
+
+        let gray8 = core.get_format(PresetFormat::Gray8.into()).unwrap();
+        let mut frame = core
+            .new_frame_with_data(gray8, resolution, &[&[0u8; 8][..]], None)
+            .unwrap();
+        assert_eq!(
+            frame.apply_matrix3x3(identity),
+            Err(frame::Matrix3x3Error::NotRgb)
+        );
+
+        let rgbh = core.get_format(PresetFormat::RGBH.into()).unwrap();
+        let mut frame = FrameRefMut::new_zeroed(core, None, rgbh, resolution);
+        #[cfg(feature = "f16-pixel-type")]
+        {
+            assert_eq!(frame.apply_matrix3x3(swap), Ok(()));
+            assert_eq!(frame.plane_row::<half::f16>(0, 0), &[half::f16::ZERO; 4]);
+        }
+        #[cfg(not(feature = "f16-pixel-type"))]
+        assert_eq!(
+            frame.apply_matrix3x3(swap),
+            Err(frame::Matrix3x3Error::UnsupportedSampleType)
+        );
+    }
+
+    #[test]
+    fn deep_copy() {
+        let api = API::get().unwrap();
+        let core = api.create_core(1);
+        let rgb24 = core.get_format(PresetFormat::RGB24.into()).unwrap();
+        let resolution = Resolution {
+            width: 1920,
+            height: 1080,
+        };
+        let plane_size = 1920 * 1080;
+
+        let mut src = core.solid_frame(rgb24, resolution, &[1, 2, 3]).unwrap();
+        src.props_mut().set_int("Answer", 42).unwrap();
+        let used = || core.info().used_framebuffer_size as usize;
+        let base = used();
+
+        let mut copy = src.deep_copy(core);
+        assert!(used() - base >= plane_size * 3);
+        assert_eq!(copy.props().get_int("Answer"), Ok(42));
+        for plane in 0..3 {
+            assert_ne!(copy.data_ptr(plane), src.data_ptr(plane));
             assert_eq!(
-                rx.recv(),
-                Ok((
-                    MessageType::Critical,
-                    CString::new("test critical message").unwrap()
-                ))
+                copy.plane_row::<u8>(plane, 1079),
+                src.plane_row::<u8>(plane, 1079)
             );
+        }
 
-            api.remove_message_handler(id);
+        // The copy owns its planes, so writing to it doesn't allocate.
+        let before_write = used();
+        copy.plane_row_mut::<u8>(0, 0)[0] = 255;
+        assert_eq!(used(), before_write);
+        assert_eq!(copy.plane_row::<u8>(0, 0)[0], 255);
+        assert_eq!(src.plane_row::<u8>(0, 0)[0], 1);
+    }
+
+    #[test]
+    fn copy_on_write_allocations() {
+        let api = API::get().unwrap();
+        let core = api.create_core(1);
+        let rgb24 = core.get_format(PresetFormat::RGB24.into()).unwrap();
+        let resolution = Resolution {
+            width: 1920,
+            height: 1080,
+        };
+        let plane_size = 1920 * 1080;
+
+        let data = vec![0u8; plane_size];
+        let src = core
+            .new_frame_with_data(rgb24, resolution, &[&data, &data, &data], None)
+            .unwrap();
+        let used = || core.info().used_framebuffer_size as usize;
+        let base = used();
+
+        // Sharing every plane doesn't allocate anything.
+        let mut shared = FrameRefMut::copy_of(core, &src);
+        let swapped = FrameRefMut::new_from_planes(
+            core,
+            rgb24,
+            resolution,
+            &[(&src, 2), (&src, 1), (&src, 0)],
+            None,
+        )
+        .unwrap();
+        assert_eq!(used(), base);
+
+        // Modifying a single row copies only that row's plane.
+        shared.plane_row_mut::<u8>(1, 0)[0] = 255;
+        let one_plane = used() - base;
+        assert!(one_plane >= plane_size);
+        assert!(one_plane < plane_size * 2);
+
+        // Rewriting the whole frame allocates every plane.
+        let before_full = used();
+        let full = core
+            .new_frame_with_data(rgb24, resolution, &[&data, &data, &data], None)
+            .unwrap();
+        assert!(used() - before_full >= plane_size * 3);
+
+        assert_eq!(src.plane_row::<u8>(1, 0)[0], 0);
+        assert_eq!(shared.plane_row::<u8>(1, 0)[0], 255);
+        assert_eq!(swapped.plane_row::<u8>(0, 0)[0], 0);
+        drop(full);
+
+        let gray8 = core.get_format(PresetFormat::Gray8.into()).unwrap();
+        let small = Resolution {
+            width: 16,
+            height: 16,
+        };
+        assert_eq!(
+            FrameRefMut::new_from_planes(core, gray8, small, &[(&src, 0)], None).err(),
+            Some(frame::PlaneDataError::MismatchedSourcePlane(0))
+        );
+        assert_eq!(
+            FrameRefMut::new_from_planes(core, gray8, resolution, &[(&src, 3)], None).err(),
+            Some(frame::PlaneDataError::MismatchedSourcePlane(0))
+        );
+        assert!(FrameRefMut::new_from_planes(core, gray8, resolution, &[(&src, 1)], None).is_ok());
+    }
+
+    #[test]
+    fn new_from_some_planes() {
+        let api = API::get().unwrap();
+        let core = api.create_core(1);
+        let yuv420p8 = core.get_format(PresetFormat::YUV420P8.into()).unwrap();
+        let resolution = Resolution {
+            width: 8,
+            height: 4,
+        };
+        let mut src = core
+            .solid_frame(yuv420p8, resolution, &[10, 20, 30])
+            .unwrap();
+        src.props_mut().set_int("Answer", 42).unwrap();
+
+        let frame = FrameRefMut::new_from_some_planes(
+            core,
+            yuv420p8,
+            resolution,
+            &[None, Some((&src, 2)), Some((&src, 1))],
+            Some(&src),
+        )
+        .unwrap();
+        assert_eq!(frame.plane_to_vec2d::<u8>(0), vec![vec![0; 8]; 4]);
+        assert_eq!(frame.plane_to_vec2d::<u8>(1), vec![vec![30; 4]; 2]);
+        assert_eq!(frame.plane_to_vec2d::<u8>(2), vec![vec![20; 4]; 2]);
+        assert_eq!(frame.props().get_int("Answer"), Ok(42));
+
+        assert_eq!(
+            FrameRefMut::new_from_some_planes(core, yuv420p8, resolution, &[None, None], None)
+                .err(),
+            Some(frame::PlaneDataError::WrongPlaneCount {
+                expected: 3,
+                got: 2
+            })
+        );
+        assert_eq!(
+            FrameRefMut::new_from_some_planes(
+                core,
+                yuv420p8,
+                resolution,
+                &[None, Some((&src, 0)), None],
+                None
+            )
+            .err(),
+            Some(frame::PlaneDataError::MismatchedSourcePlane(1))
+        );
+    }
+
+    #[test]
+    fn copy_props_from() {
+        let api = API::get().unwrap();
+        let core = api.create_core(1);
+        let gray8 = core.get_format(PresetFormat::Gray8.into()).unwrap();
+        let resolution = Resolution {
+            width: 4,
+            height: 2,
+        };
+        let mut src = core.solid_frame(gray8, resolution, &[1]).unwrap();
+        src.props_mut().set_int("_DurationNum", 1001).unwrap();
+
+        let mut frame = core.solid_frame(gray8, resolution, &[2]).unwrap();
+        frame.props_mut().set_int("Stale", 1).unwrap();
+        frame.copy_props_from(core, &src);
+
+        assert_eq!(frame.props().get_int("_DurationNum"), Ok(1001));
+        assert_eq!(frame.props().get_int("Stale"), Err(map::Error::KeyNotFound));
+        assert_eq!(frame.plane_to_vec2d::<u8>(0), vec![vec![2; 4]; 2]);
+    }
+
+    #[test]
+    fn new_frame_with_data() {
+        let api = API::get().unwrap();
+        let core = api.create_core(1);
+        let gray8 = core.get_format(PresetFormat::Gray8.into()).unwrap();
+        let resolution = Resolution {
+            width: 7,
+            height: 3,
+        };
+
+        let data: Vec<u8> = (0..21).map(|x| x * 10).collect();
+        let frame = core.new_frame_with_data(gray8, resolution, &[&data], None);
+        assert!(frame.is_ok());
+        let frame = frame.unwrap();
+
+        assert_eq!(frame.format().id(), PresetFormat::Gray8.into());
+        assert_eq!(frame.resolution(0), resolution);
+        for row in 0..3 {
+            assert_eq!(frame.plane_row::<u8>(0, row), &data[row * 7..(row + 1) * 7]);
         }
+
+        assert_eq!(
+            core.new_frame_with_data(gray8, resolution, &[&data[1..]], None)
+                .err(),
+            Some(frame::PlaneDataError::WrongPlaneLength {
+                plane: 0,
+                expected: 21,
+                got: 20,
+            })
+        );
+        assert_eq!(
+            core.new_frame_with_data(gray8, resolution, &[&data, &data], None)
+                .err(),
+            Some(frame::PlaneDataError::WrongPlaneCount {
+                expected: 1,
+                got: 2,
+            })
+        );
+        assert_eq!(
+            core.new_frame_with_data(gray8, resolution, &[&[0u16; 21][..]], None)
+                .err(),
+            Some(frame::PlaneDataError::InvalidComponentType)
+        );
     }
 
     #[test]
@@ -1037,3 +3927,307 @@ mod need_api {
         }
     }
 }
+
+#[test]
+fn filter_args() {
+    use plugins::{normalize_args, InvalidArgsError};
+
+    assert_eq!(
+        normalize_args("blah:clip;moo:int[]:opt;asdf:float:opt;"),
+        Ok("blah:clip;moo:int[]:opt;asdf:float:opt;".to_owned())
+    );
+    assert_eq!(
+        normalize_args("clip:clip;;frames:frame[]:opt:empty"),
+        Ok("clip:clip;frames:frame[]:opt:empty;".to_owned())
+    );
+    assert_eq!(normalize_args(""), Ok(String::new()));
+
+    assert_eq!(
+        normalize_args("clip:clip;1st:int"),
+        Err(InvalidArgsError::InvalidName {
+            index: 1,
+            name: "1st".to_owned(),
+        })
+    );
+    assert_eq!(
+        normalize_args("clip: clip"),
+        Err(InvalidArgsError::UnknownType {
+            name: "clip".to_owned(),
+            type_name: " clip".to_owned(),
+        })
+    );
+    assert_eq!(
+        normalize_args("clip"),
+        Err(InvalidArgsError::MissingType {
+            name: "clip".to_owned(),
+        })
+    );
+    assert_eq!(
+        normalize_args("x:int:optional"),
+        Err(InvalidArgsError::UnknownFlag {
+            name: "x".to_owned(),
+            flag: "optional".to_owned(),
+        })
+    );
+    assert_eq!(
+        normalize_args("x:int[]:opt:opt"),
+        Err(InvalidArgsError::DuplicateFlag {
+            name: "x".to_owned(),
+            flag: "opt".to_owned(),
+        })
+    );
+    assert_eq!(
+        normalize_args("x:int:empty"),
+        Err(InvalidArgsError::EmptyFlagOnNonArray {
+            name: "x".to_owned(),
+        })
+    );
+    assert_eq!(
+        normalize_args("x:int;x:float"),
+        Err(InvalidArgsError::DuplicateName {
+            name: "x".to_owned(),
+        })
+    );
+
+    let err = normalize_args("clip:clip;moo:integer").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Argument \"moo\" has an unknown type \"integer\""
+    );
+}
+
+#[test]
+// The macro-generated code isn't external here, so it's subject to lints.
+#[allow(clippy::extra_unused_lifetimes)]
+fn filter_parameters() {
+    use crate::core::CoreRef;
+    use anyhow::Error;
+    use api::API;
+    use frame::FrameRef;
+    use function::Function;
+    use map::{Map, ValueIter, ValueType};
+    use node::Node;
+    use plugins::{ArgSpec, Filter, FilterArgument, FilterFunction, InvalidArgsError, ParamInfo};
+
+    make_filter_function! {
+        ArgumentTestFunction, "ArgumentTest"
+
+        #[allow(clippy::too_many_arguments)]
+        fn create_argument_test<'core>(
+            _api: API,
+            _core: CoreRef<'core>,
+            int: i64,
+            float: f64,
+            data: &[u8],
+            node: Node<'core>,
+            frame: FrameRef<'core>,
+            function: Function<'core>,
+            optional_int: Option<i64>,
+            flag: Option<bool>,
+            frame_array: ValueIter<'_, 'core, FrameRef<'core>>,
+            optional_frame_array: Option<ValueIter<'_, 'core, FrameRef<'core>>>,
+        ) -> Result<Option<Box<dyn Filter<'core> + 'core>>, Error> {
+            Ok(None)
+        }
+    }
+
+    let param = |name: &str, value_type, array, optional| ParamInfo {
+        name: name.to_owned(),
+        value_type,
+        array,
+        optional,
+        empty: array,
+    };
+
+    assert_eq!(
+        ArgumentTestFunction::new().parameters(),
+        Ok(vec![
+            param("int", ValueType::Int, false, false),
+            param("float", ValueType::Float, false, false),
+            param("data", ValueType::Data, false, false),
+            param("node", ValueType::Node, false, false),
+            param("frame", ValueType::Frame, false, false),
+            param("function", ValueType::Function, false, false),
+            param("optional_int", ValueType::Int, false, true),
+            param("flag", ValueType::Int, false, true),
+            param("frame_array", ValueType::Frame, true, false),
+            param("optional_frame_array", ValueType::Frame, true, true),
+        ])
+    );
+
+    let spec = ArgSpec::new()
+        .arg("int", ValueType::Int)
+        .arg("float", ValueType::Float)
+        .arg("data", ValueType::Data)
+        .arg("node", ValueType::Node)
+        .arg("frame", ValueType::Frame)
+        .arg("function", ValueType::Function)
+        .arg("optional_int", ValueType::Int)
+        .optional()
+        .arg("flag", ValueType::Int)
+        .optional()
+        .arg("frame_array", ValueType::Frame)
+        .array()
+        .empty()
+        .param(param("optional_frame_array", ValueType::Frame, true, true));
+    assert_eq!(spec.params().len(), 10);
+    assert_eq!(
+        spec.build().as_deref(),
+        Ok(ArgumentTestFunction::new().args())
+    );
+
+    assert_eq!(ArgSpec::new().build(), Ok(String::new()));
+    assert_eq!(
+        ArgSpec::new()
+            .arg("clip", ValueType::Node)
+            .arg("a;b", ValueType::Int)
+            .build(),
+        Err(InvalidArgsError::InvalidName {
+            index: 1,
+            name: "a;b".to_owned()
+        })
+    );
+    assert_eq!(
+        ArgSpec::new()
+            .arg("x", ValueType::Int)
+            .arg("x", ValueType::Float)
+            .build(),
+        Err(InvalidArgsError::DuplicateName {
+            name: "x".to_owned()
+        })
+    );
+    assert_eq!(
+        ArgSpec::new().arg("x", ValueType::Int).empty().build(),
+        Err(InvalidArgsError::EmptyFlagOnNonArray {
+            name: "x".to_owned()
+        })
+    );
+}
+
+#[test]
+fn property_display() {
+    use video_info::{Framerate, Property, Resolution};
+
+    let framerate = Property::Constant(Framerate {
+        numerator: 60,
+        denominator: 1,
+    });
+    assert_eq!(framerate.display_fps(), "60/1 (60.000 fps)");
+
+    let framerate = Property::Constant(Framerate {
+        numerator: 30000,
+        denominator: 1001,
+    });
+    assert_eq!(framerate.display_fps(), "30000/1001 (29.970 fps)");
+    assert_eq!(Property::<Framerate>::Variable.display_fps(), "Variable");
+
+    let resolution = Property::Constant(Resolution {
+        width: 1920,
+        height: 1080,
+    });
+    assert_eq!(resolution.display_resolution(), "1920×1080");
+    assert_eq!(
+        Property::<Resolution>::Variable.display_resolution(),
+        "Variable"
+    );
+}
+
+#[test]
+fn collect_ordered() {
+    use node::collect_ordered;
+
+    let completions = vec![(12, 'c'), (10, 'a'), (13, 'd'), (11, 'b')];
+    assert_eq!(
+        collect_ordered(10..14, completions),
+        vec!['a', 'b', 'c', 'd']
+    );
+
+    // Only as many completions as there are frames are taken.
+    let mut completions = vec![(1, 'b'), (0, 'a'), (2, 'c')].into_iter();
+    assert_eq!(collect_ordered(0..2, completions.by_ref()), vec!['a', 'b']);
+    assert_eq!(completions.next(), Some((2, 'c')));
+
+    assert_eq!(collect_ordered(5..5, Vec::<(usize, ())>::new()), vec![]);
+}
+
+#[test]
+#[should_panic(expected = "frame 1 was completed twice")]
+fn collect_ordered_duplicate() {
+    node::collect_ordered(0..2, vec![(1, ()), (1, ())]);
+}
+
+#[test]
+fn register_invalid_args() {
+    use std::ffi::{c_char, c_void, CStr};
+    use std::sync::Mutex;
+
+    use plugins::{ffi, Filter, FilterFunction};
+
+    static REGISTERED: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+    unsafe extern "system" fn register(
+        name: *const c_char,
+        _args: *const c_char,
+        _args_func: vapoursynth_sys::VSPublicFunction,
+        function_data: *mut c_void,
+        _plugin: *mut vapoursynth_sys::VSPlugin,
+    ) {
+        let name = CStr::from_ptr(name).to_str().unwrap().to_owned();
+        REGISTERED.lock().unwrap().push(name);
+
+        // The function data would be owned by VapourSynth from now on.
+        drop(Box::from_raw(
+            function_data as *mut ffi::FilterFunctionData<Function>,
+        ));
+    }
+
+    struct Function(&'static str, &'static str);
+
+    impl FilterFunction for Function {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn args(&self) -> &str {
+            self.1
+        }
+
+        fn create<'core>(
+            &self,
+            _api: api::API,
+            _core: core::CoreRef<'core>,
+            _args: &map::Map<'core>,
+        ) -> Result<Option<Box<dyn Filter<'core> + 'core>>, anyhow::Error> {
+            unreachable!()
+        }
+    }
+
+    let register_func = register as vapoursynth_sys::VSRegisterFunction as *const c_void;
+    unsafe {
+        assert!(matches!(
+            ffi::call_register_func(
+                register_func,
+                std::ptr::null_mut(),
+                Function("Bad", "clip:node;"),
+            ),
+            Err(plugin::RegisterFunctionError::InvalidArgs(_))
+        ));
+        assert!(matches!(
+            ffi::call_register_func(
+                register_func,
+                std::ptr::null_mut(),
+                Function("Bad\0", "clip:clip;"),
+            ),
+            Err(plugin::RegisterFunctionError::CStringConversion(_))
+        ));
+        assert!(ffi::call_register_func(
+            register_func,
+            std::ptr::null_mut(),
+            Function("Good", "clip:clip"),
+        )
+        .is_ok());
+    }
+
+    // The invalid functions are skipped without affecting the rest of the plugin.
+    assert_eq!(*REGISTERED.lock().unwrap(), ["Good"]);
+}
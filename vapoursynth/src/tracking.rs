@@ -0,0 +1,50 @@
+//! Tracking of live VapourSynth object references.
+//!
+//! With the `track-allocations` feature enabled, the crate counts every map, node and frame
+//! reference its wrappers acquire and release, which makes it possible to check that some code
+//! doesn't leak references. Without the feature the hooks compile down to nothing.
+//!
+//! The counters are process-wide, so tests relying on them must not run concurrently with other
+//! code that creates VapourSynth objects.
+
+#[cfg(feature = "track-allocations")]
+use std::sync::atomic::{AtomicIsize, Ordering};
+
+/// Kinds of tracked references.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum Resource {
+    /// Maps owned by an `OwnedMap`.
+    Map,
+    /// Node references owned by a `Node`.
+    Node,
+    /// Frame references owned by a `FrameRef` or a `FrameRefMut`.
+    Frame,
+}
+
+#[cfg(feature = "track-allocations")]
+static COUNTERS: [AtomicIsize; 3] = [
+    AtomicIsize::new(0),
+    AtomicIsize::new(0),
+    AtomicIsize::new(0),
+];
+
+/// Returns the number of currently live references of the given kind.
+#[cfg(feature = "track-allocations")]
+#[inline]
+pub fn live(resource: Resource) -> isize {
+    COUNTERS[resource as usize].load(Ordering::SeqCst)
+}
+
+/// Records that a reference was acquired.
+#[inline(always)]
+pub(crate) fn acquired(resource: Resource) {
+    #[cfg(feature = "track-allocations")]
+    COUNTERS[resource as usize].fetch_add(1, Ordering::SeqCst);
+}
+
+/// Records that a reference was released or its ownership was handed over to VapourSynth.
+#[inline(always)]
+pub(crate) fn released(resource: Resource) {
+    #[cfg(feature = "track-allocations")]
+    COUNTERS[resource as usize].fetch_sub(1, Ordering::SeqCst);
+}
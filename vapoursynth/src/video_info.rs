@@ -129,6 +129,19 @@ impl<'core> VideoInfo<'core> {
         }
     }
 
+    /// Returns `true` if the clip is an instance of the built-in Cache filter.
+    #[inline]
+    pub fn is_cache_node(&self) -> bool {
+        self.flags.contains(node::Flags::IS_CACHE)
+    }
+
+    /// Returns `true` if the clip's filter prefers linear frame access, like source filters do.
+    #[cfg(feature = "gte-vapoursynth-api-33")]
+    #[inline]
+    pub fn wants_linear_access(&self) -> bool {
+        self.flags.contains(node::Flags::MAKE_LINEAR)
+    }
+
     /// Converts the Rust struct into a C struct.
     pub(crate) fn ffi_type(self) -> ffi::VSVideoInfo {
         let format = match self.format {
@@ -172,6 +185,31 @@ impl<'core> VideoInfo<'core> {
     }
 }
 
+impl Property<Framerate> {
+    /// Formats the framerate for display, like `"30000/1001 (29.970 fps)"`, or `"Variable"`.
+    pub fn display_fps(&self) -> String {
+        match *self {
+            Property::Variable => "Variable".to_owned(),
+            Property::Constant(x) => format!(
+                "{}/{} ({:.3} fps)",
+                x.numerator,
+                x.denominator,
+                x.numerator as f64 / x.denominator as f64
+            ),
+        }
+    }
+}
+
+impl Property<Resolution> {
+    /// Formats the resolution for display, like `"1920×1080"`, or `"Variable"`.
+    pub fn display_resolution(&self) -> String {
+        match *self {
+            Property::Variable => "Variable".to_owned(),
+            Property::Constant(x) => format!("{}×{}", x.width, x.height),
+        }
+    }
+}
+
 impl<T> From<T> for Property<T>
 where
     T: Debug + Clone + Copy + Eq + PartialEq,
@@ -9,7 +9,8 @@ use vapoursynth_sys as ffi;
 
 use crate::api::API;
 use crate::core::CoreRef;
-use crate::map::Map;
+use crate::frame::Frame;
+use crate::map::{self, Map, OwnedMap};
 use crate::node::Node;
 use crate::vsscript::errors::Result;
 use crate::vsscript::*;
@@ -43,6 +44,20 @@ enum EvaluateScriptArgs<'a> {
     File(&'a Path, EvalFlags),
 }
 
+/// The number of output indices probed by `Environment::get_outputs()`.
+#[cfg(any(feature = "vapoursynth-functions", feature = "gte-vsscript-api-32"))]
+const PROBED_OUTPUT_INDICES: i32 = 100;
+
+/// A node marked for output in a script, along with its alpha clip.
+#[derive(Debug, Clone)]
+pub struct Output<'core> {
+    /// The output node.
+    pub node: Node<'core>,
+
+    /// The alpha clip, if the script attached one to the output.
+    pub alpha: Option<Node<'core>>,
+}
+
 /// A wrapper for the VSScript environment.
 #[derive(Debug)]
 pub struct Environment {
@@ -148,6 +163,67 @@ impl Environment {
         Ok(environment)
     }
 
+    /// Creates a script environment and evaluates a script contained in a file, forwarding the
+    /// VapourSynth messages emitted during the evaluation to `progress`.
+    ///
+    /// This is useful for providing feedback while slow scripts load, for example ones indexing
+    /// large sources. A message handler is added for the duration of the evaluation and removed
+    /// afterwards, so previously added handlers keep receiving messages. Message handlers are
+    /// global, so messages emitted by other threads during the evaluation are forwarded as well.
+    /// Messages which aren't valid UTF-8 are converted lossily.
+    #[cfg(all(
+        feature = "gte-vapoursynth-api-36",
+        any(feature = "vapoursynth-functions", feature = "gte-vsscript-api-32")
+    ))]
+    pub fn from_file_with_callback<P, F>(path: P, flags: EvalFlags, progress: F) -> Result<Self>
+    where
+        P: AsRef<Path>,
+        F: Fn(&str) + Sync,
+    {
+        use crate::api::MessageHandlerId;
+
+        /// Removes the message handler when dropped, even on panic.
+        struct Guard(API, MessageHandlerId);
+
+        impl Drop for Guard {
+            #[inline]
+            fn drop(&mut self) {
+                self.0.remove_message_handler(self.1);
+            }
+        }
+
+        /// A lifetime-erased pointer to the callback.
+        struct Progress(*const (dyn Fn(&str) + Sync));
+
+        // The callback is `Sync`, so calling it from other threads is fine.
+        unsafe impl Send for Progress {}
+
+        impl Progress {
+            #[inline]
+            unsafe fn call(&self, message: &str) {
+                (*self.0)(message)
+            }
+        }
+
+        let api = API::get().ok_or(Error::NoAPI)?;
+
+        let progress: &(dyn Fn(&str) + Sync) = &progress;
+        // The handler is removed before `progress` goes out of scope, and removing a handler waits
+        // for any running calls to finish.
+        let progress = Progress(unsafe {
+            std::mem::transmute::<&(dyn Fn(&str) + Sync), &'static (dyn Fn(&str) + Sync)>(progress)
+        });
+
+        let _guard = Guard(
+            api,
+            api.add_message_handler(move |_, message| unsafe {
+                progress.call(&message.to_string_lossy());
+            }),
+        );
+
+        Self::from_file(path, flags)
+    }
+
     /// Evaluates a script contained in a string.
     #[inline]
     pub fn eval_script(&mut self, script: &str) -> Result<()> {
@@ -168,48 +244,81 @@ impl Environment {
         }
     }
 
-    /// Retrieves a node from the script environment. A node in the script must have been marked
-    /// for output with the requested index.
-    #[cfg(all(
-        not(feature = "gte-vsscript-api-31"),
-        feature = "vapoursynth-functions"
-    ))]
+    /// Retrieves an output from the script environment. A node in the script must have been
+    /// marked for output with the requested index.
+    ///
+    /// The alpha clip is only supported starting from VSScript API 3.1. With older API versions
+    /// `Output::alpha` is always `None`.
+    #[cfg(any(feature = "vapoursynth-functions", feature = "gte-vsscript-api-32"))]
     #[inline]
-    pub fn get_output(&self, index: i32) -> Result<Node> {
+    pub fn get_output(&self, index: i32) -> Result<Output<'_>> {
         // Node needs the API.
         API::get().ok_or(Error::NoAPI)?;
 
-        let node_handle = unsafe { ffi::vsscript_getOutput(self.handle.as_ptr(), index) };
+        #[cfg(feature = "gte-vsscript-api-31")]
+        let (node_handle, alpha_handle) = {
+            let mut alpha_handle = ptr::null_mut();
+            let node_handle =
+                unsafe { ffi::vsscript_getOutput2(self.handle.as_ptr(), index, &mut alpha_handle) };
+            (node_handle, alpha_handle)
+        };
+
+        #[cfg(not(feature = "gte-vsscript-api-31"))]
+        let (node_handle, alpha_handle) = (
+            unsafe { ffi::vsscript_getOutput(self.handle.as_ptr(), index) },
+            ptr::null_mut::<ffi::VSNodeRef>(),
+        );
+
         if node_handle.is_null() {
-            Err(Error::NoOutput)
-        } else {
-            Ok(unsafe { Node::from_ptr(node_handle) })
+            return Err(Error::NoOutput);
         }
+
+        let node = unsafe { Node::from_ptr(node_handle) };
+        let alpha = unsafe { alpha_handle.as_mut().map(|p| Node::from_ptr(p)) };
+
+        Ok(Output { node, alpha })
     }
 
-    /// Retrieves a node from the script environment. A node in the script must have been marked
-    /// for output with the requested index. The second node, if any, contains the alpha clip.
-    #[cfg(all(
-        feature = "gte-vsscript-api-31",
-        any(feature = "vapoursynth-functions", feature = "gte-vsscript-api-32")
-    ))]
+    /// Returns whether the output at the given index has an alpha clip attached.
+    ///
+    /// Returns an error if there's no output at the index.
+    #[cfg(any(feature = "vapoursynth-functions", feature = "gte-vsscript-api-32"))]
     #[inline]
-    pub fn get_output(&self, index: i32) -> Result<(Node, Option<Node>)> {
-        // Node needs the API.
-        API::get().ok_or(Error::NoAPI)?;
-
-        let mut alpha_handle = ptr::null_mut();
-        let node_handle =
-            unsafe { ffi::vsscript_getOutput2(self.handle.as_ptr(), index, &mut alpha_handle) };
+    pub fn has_alpha(&self, index: i32) -> Result<bool> {
+        Ok(self.get_output(index)?.alpha.is_some())
+    }
 
-        if node_handle.is_null() {
-            return Err(Error::NoOutput);
+    /// Retrieves every node marked for output in the script, ordered by the output index.
+    ///
+    /// VSScript has no way of enumerating the outputs, so this probes the indices from 0 to 99
+    /// with `get_output()`. Gaps between the indices are allowed, but outputs at negative indices
+    /// or at indices above 99 aren't returned.
+    #[cfg(any(feature = "vapoursynth-functions", feature = "gte-vsscript-api-32"))]
+    pub fn get_outputs(&self) -> Result<Vec<(i32, Output<'_>)>> {
+        let mut outputs = Vec::new();
+
+        for index in 0..PROBED_OUTPUT_INDICES {
+            match self.get_output(index) {
+                Ok(output) => outputs.push((index, output)),
+                Err(Error::NoOutput) => {}
+                Err(err) => return Err(err),
+            }
         }
 
-        let node = unsafe { Node::from_ptr(node_handle) };
-        let alpha_node = unsafe { alpha_handle.as_mut().map(|p| Node::from_ptr(p)) };
+        Ok(outputs)
+    }
 
-        Ok((node, alpha_node))
+    /// Returns the indices of the nodes marked for output in the script, in ascending order.
+    ///
+    /// See `get_outputs()` for the range of indices which are checked.
+    #[cfg(any(feature = "vapoursynth-functions", feature = "gte-vsscript-api-32"))]
+    #[inline]
+    pub fn output_indices(&self) -> Result<Vec<i32>> {
+        Ok(self
+            .get_outputs()?
+            .into_iter()
+            .map(|(index, _)| index)
+            .collect())
     }
 
     /// Cancels a node set for output. The node will no longer be available to `get_output()`.
@@ -226,7 +335,7 @@ impl Environment {
     /// Retrieves the VapourSynth core that was created in the script environment. If a VapourSynth
     /// core has not been created yet, it will be created now, with the default options.
     #[cfg(any(feature = "vapoursynth-functions", feature = "gte-vsscript-api-32"))]
-    pub fn get_core(&self) -> Result<CoreRef> {
+    pub fn get_core(&self) -> Result<CoreRef<'_>> {
         // CoreRef needs the API.
         API::get().ok_or(Error::NoAPI)?;
 
@@ -261,6 +370,64 @@ impl Environment {
         }
     }
 
+    /// Sets a single variable in the script environment with a map filled in by `set`.
+    #[cfg(any(feature = "vapoursynth-functions", feature = "gte-vsscript-api-32"))]
+    fn set_variable_with<'elem, F>(&self, name: &str, set: F) -> Result<()>
+    where
+        F: FnOnce(&mut OwnedMap<'elem>, &str) -> map::Result<()>,
+    {
+        let api = API::get().ok_or(Error::NoAPI)?;
+
+        let mut variables = OwnedMap::new(api);
+        set(&mut variables, name).map_err(|err| match err {
+            map::Error::InvalidKey(err) => Error::InvalidVariableName(err),
+            map::Error::CStringConversion(err) => Error::CStringConversion(err),
+            err => Error::Map(err),
+        })?;
+        self.set_variables(&variables)
+    }
+
+    /// Sets an integer variable in the script environment.
+    #[cfg(any(feature = "vapoursynth-functions", feature = "gte-vsscript-api-32"))]
+    #[inline]
+    pub fn set_variable_int(&self, name: &str, x: i64) -> Result<()> {
+        self.set_variable_with(name, |map, name| map.set_int(name, x))
+    }
+
+    /// Sets a floating point variable in the script environment.
+    #[cfg(any(feature = "vapoursynth-functions", feature = "gte-vsscript-api-32"))]
+    #[inline]
+    pub fn set_variable_float(&self, name: &str, x: f64) -> Result<()> {
+        self.set_variable_with(name, |map, name| map.set_float(name, x))
+    }
+
+    /// Sets a data variable in the script environment.
+    #[cfg(any(feature = "vapoursynth-functions", feature = "gte-vsscript-api-32"))]
+    #[inline]
+    pub fn set_variable_data(&self, name: &str, x: &[u8]) -> Result<()> {
+        self.set_variable_with(name, |map, name| map.set_data(name, x))
+    }
+
+    /// Sets a clip variable in the script environment.
+    #[cfg(any(feature = "vapoursynth-functions", feature = "gte-vsscript-api-32"))]
+    #[inline]
+    pub fn set_variable_node(&self, name: &str, x: &Node) -> Result<()> {
+        self.set_variable_with(name, |map, name| map.set_node(name, x))
+    }
+
+    /// Sets script variables from the properties of `frame`.
+    ///
+    /// Every integer, floating point and data property becomes a variable with the same name and
+    /// value. Node, frame and function properties are skipped.
+    #[cfg(any(feature = "vapoursynth-functions", feature = "gte-vsscript-api-32"))]
+    pub fn set_variables_from_props(&self, frame: &Frame) -> Result<()> {
+        let api = API::get().ok_or(Error::NoAPI)?;
+
+        let mut variables = OwnedMap::new(api);
+        frame.props().copy_plain_values_to(&mut variables);
+        self.set_variables(&variables)
+    }
+
     /// Deletes a variable from the script environment.
     pub fn clear_variable(&self, name: &str) -> Result<()> {
         let name = CString::new(name)?;
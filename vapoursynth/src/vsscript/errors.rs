@@ -3,6 +3,8 @@ use std::{fmt, io};
 
 use thiserror::Error;
 
+use crate::map::{self, InvalidKeyError};
+
 /// The error type for `vsscript` operations.
 #[derive(Error, Debug)]
 pub enum Error {
@@ -18,6 +20,10 @@ pub enum Error {
     VSScript(#[source] VSScriptError),
     #[error("There's no such variable")]
     NoSuchVariable,
+    #[error("The variable name is invalid")]
+    InvalidVariableName(#[source] InvalidKeyError),
+    #[error("Couldn't build the variable map")]
+    Map(#[source] map::Error),
     #[error("Couldn't get the core")]
     NoCore,
     #[error("There's no output on the requested index")]
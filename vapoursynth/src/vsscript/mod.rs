@@ -1,5 +1,7 @@
 //! VapourSynth script-related things.
 
+#[cfg(all(feature = "gte-vsscript-api-31", not(feature = "gte-vsscript-api-32")))]
+use std::sync::atomic::{AtomicBool, Ordering};
 #[cfg(not(feature = "gte-vsscript-api-32"))]
 use std::sync::Mutex;
 use std::sync::Once;
@@ -7,22 +9,54 @@ use vapoursynth_sys as ffi;
 
 #[cfg(not(feature = "gte-vsscript-api-32"))]
 lazy_static! {
-    static ref FFI_CALL_MUTEX: Mutex<()> = Mutex::new(());
+    pub(crate) static ref FFI_CALL_MUTEX: Mutex<()> = Mutex::new(());
 }
 
+/// Whether the loaded VSScript library is older than API 3.2, set in `maybe_initialize()`.
+#[cfg(all(feature = "gte-vsscript-api-31", not(feature = "gte-vsscript-api-32")))]
+static NEEDS_FFI_CALL_MUTEX: AtomicBool = AtomicBool::new(true);
+
 // Some `vsscript_*` function calls have threading issues. Protect them with a mutex.
 // https://github.com/vapoursynth/vapoursynth/issues/367
 macro_rules! call_vsscript {
     ($call:expr) => {{
-        // Fixed in VSScript API 3.2.
-        // TODO: also not needed when we're running API 3.2 even without a feature.
         #[cfg(not(feature = "gte-vsscript-api-32"))]
-        let _lock = crate::vsscript::FFI_CALL_MUTEX.lock();
+        let _lock = if crate::vsscript::ffi_calls_serialized() {
+            Some(crate::vsscript::FFI_CALL_MUTEX.lock())
+        } else {
+            None
+        };
 
         $call
     }};
 }
 
+/// Returns `true` if `vsscript_*` calls are serialized with a global mutex.
+///
+/// The threading issues were fixed in VSScript API 3.2. With the `gte-vsscript-api-32` feature
+/// the mutex doesn't exist at all. With `gte-vsscript-api-31` the version of the loaded library
+/// is checked at runtime, so the mutex is skipped when running against 3.2 or newer. Older
+/// libraries can't report their version, so every call is serialized.
+///
+/// The result is only meaningful after `maybe_initialize()`.
+#[inline]
+pub(crate) fn ffi_calls_serialized() -> bool {
+    #[cfg(feature = "gte-vsscript-api-32")]
+    {
+        false
+    }
+
+    #[cfg(all(feature = "gte-vsscript-api-31", not(feature = "gte-vsscript-api-32")))]
+    {
+        NEEDS_FFI_CALL_MUTEX.load(Ordering::Acquire)
+    }
+
+    #[cfg(not(feature = "gte-vsscript-api-31"))]
+    {
+        true
+    }
+}
+
 /// Ensures `vsscript_init()` has been called at least once.
 // TODO: `vsscript_init()` is already thread-safe with `std::call_once()`, maybe this can be done
 // differently to remove the thread protection on Rust's side? An idea is to have a special type
@@ -58,6 +92,11 @@ pub(crate) fn maybe_initialize() {
                     my_minor, minor
                 );
             }
+
+            #[cfg(not(feature = "gte-vsscript-api-32"))]
+            if (major, minor) >= (3, 2) {
+                NEEDS_FFI_CALL_MUTEX.store(false, Ordering::Release);
+            }
         }
     });
 }
@@ -66,4 +105,4 @@ mod errors;
 pub use self::errors::{Error, VSScriptError};
 
 mod environment;
-pub use self::environment::{Environment, EvalFlags};
+pub use self::environment::{Environment, EvalFlags, Output};
@@ -0,0 +1,160 @@
+// These tests live in their own binary because the reference counters are process-wide: any
+// concurrently running test creating VapourSynth objects would throw them off.
+#![cfg(all(
+    feature = "track-allocations",
+    feature = "vsscript-functions",
+    any(feature = "vapoursynth-functions", feature = "gte-vsscript-api-32")
+))]
+
+extern crate vapoursynth;
+
+use std::sync::mpsc::channel;
+use std::sync::{Mutex, MutexGuard};
+
+use vapoursynth::prelude::*;
+use vapoursynth::tracking::{live, Resource};
+
+static LOCK: Mutex<()> = Mutex::new(());
+
+/// Serializes the tests and remembers the counters at the start.
+struct Checkpoint {
+    counts: [isize; 3],
+    _guard: MutexGuard<'static, ()>,
+}
+
+impl Checkpoint {
+    fn new() -> Self {
+        let guard = LOCK.lock().unwrap_or_else(|err| err.into_inner());
+
+        Self {
+            counts: Self::counts(),
+            _guard: guard,
+        }
+    }
+
+    fn counts() -> [isize; 3] {
+        [
+            live(Resource::Map),
+            live(Resource::Node),
+            live(Resource::Frame),
+        ]
+    }
+
+    fn assert_balanced(&self) {
+        assert_eq!(Self::counts(), self.counts);
+    }
+}
+
+fn output_node(env: &Environment) -> Node<'_> {
+    env.get_output(0).unwrap().node
+}
+
+#[test]
+fn maps() {
+    let checkpoint = Checkpoint::new();
+
+    {
+        let api = API::get().unwrap();
+        let mut map = OwnedMap::new(api);
+        map.set_int("hello", 1).unwrap();
+        map.append_data("world", b"!").unwrap();
+
+        assert_eq!(live(Resource::Map), checkpoint.counts[0] + 1);
+    }
+
+    checkpoint.assert_balanced();
+}
+
+#[test]
+fn invoke() {
+    let checkpoint = Checkpoint::new();
+
+    {
+        let env = Environment::from_file("test-vpy/green.vpy", EvalFlags::Nothing).unwrap();
+        let core = env.get_core().unwrap();
+        let std = core
+            .get_plugin_by_id("com.vapoursynth.std")
+            .unwrap()
+            .unwrap();
+
+        let mut args = OwnedMap::new(API::get().unwrap());
+        args.set_int("length", 10).unwrap();
+
+        let rv = std.invoke("BlankClip", &args).unwrap();
+        assert_eq!(rv.error(), None);
+
+        let node = rv.get_node("clip").unwrap();
+        let frame = node.get_frame(0).unwrap();
+        let _clone = frame.clone();
+    }
+
+    checkpoint.assert_balanced();
+}
+
+#[test]
+fn get_frame() {
+    let checkpoint = Checkpoint::new();
+
+    {
+        let env = Environment::from_file("test-vpy/green.vpy", EvalFlags::Nothing).unwrap();
+        let node = output_node(&env);
+
+        for n in 0..100 {
+            let frame = node.get_frame(n).unwrap();
+            let frame: FrameRef = FrameRefMut::copy_of(env.get_core().unwrap(), &frame).into();
+            drop(frame);
+        }
+
+        assert!(node.get_frame(100).is_err());
+    }
+
+    checkpoint.assert_balanced();
+}
+
+#[test]
+fn get_frame_async() {
+    let checkpoint = Checkpoint::new();
+
+    {
+        let env = Environment::from_file("test-vpy/green.vpy", EvalFlags::Nothing).unwrap();
+        let node = output_node(&env);
+
+        let (tx, rx) = channel();
+
+        // Frame 100 doesn't exist, so the last request fails.
+        for n in 0..101 {
+            let tx = tx.clone();
+
+            node.get_frame_async(n, move |frame, _, node| {
+                // Drop everything before signaling so the counters are settled.
+                drop(frame);
+                drop(node);
+
+                assert_eq!(tx.send(()), Ok(()));
+            });
+        }
+
+        for _ in 0..101 {
+            assert_eq!(rx.recv(), Ok(()));
+        }
+    }
+
+    checkpoint.assert_balanced();
+}
+
+#[test]
+fn environment_drop() {
+    let checkpoint = Checkpoint::new();
+
+    {
+        let env = Environment::from_file("test-vpy/alpha.vpy", EvalFlags::Nothing).unwrap();
+        let node = output_node(&env);
+        let _frame = node.get_frame(0).unwrap();
+
+        let mut map = OwnedMap::new(API::get().unwrap());
+        env.get_variable("video", &mut map).unwrap();
+        let _video = map.get_node("video").unwrap();
+    }
+
+    checkpoint.assert_balanced();
+}